@@ -288,6 +288,8 @@ pub struct SwapState {
     pub tick: i32,
     // the current liquidity in range
     pub liquidity: u128,
+    // the liquidity of the pending limit orders whose range contains the current price
+    pub order_liquidity: u128,
 }
 #[derive(Default)]
 struct StepComputations {
@@ -385,12 +387,21 @@ fn swap_compute(
         sqrt_price_x64: pool_state.sqrt_price_x64,
         tick: pool_state.tick_current,
         liquidity: pool_state.liquidity,
+        order_liquidity: 0,
     };
 
     let mut tick_array_current = tick_arrays.pop_front().unwrap();
     if tick_array_current.start_tick_index != current_vaild_tick_array_start_index {
         return Result::Err("tick array start tick index does not match");
     }
+    let tick_spacing = i32::from(pool_state.tick_spacing);
+    if is_pool_current_tick_array {
+        let tick_lower_index = state.tick - state.tick.rem_euclid(tick_spacing);
+        state.order_liquidity = tick_array_current
+            .get_order_liquidity(tick_lower_index, pool_state.tick_spacing)
+            + tick_array_current
+                .get_order_liquidity(tick_lower_index + tick_spacing, pool_state.tick_spacing);
+    }
     let mut tick_array_start_index_vec = VecDeque::new();
     tick_array_start_index_vec.push_back(tick_array_current.start_tick_index);
     let mut loop_count = 0;
@@ -466,7 +477,7 @@ fn swap_compute(
         let swap_step = swap_math::compute_swap_step(
             state.sqrt_price_x64,
             target_price,
-            state.liquidity,
+            state.liquidity + state.order_liquidity,
             state.amount_specified_remaining,
             fee,
             is_base_input,
@@ -508,6 +519,14 @@ fn swap_compute(
                 }
                 state.liquidity =
                     liquidity_math::add_delta(state.liquidity, liquidity_net).unwrap();
+                state.order_liquidity = tick_array_current.get_order_liquidity(
+                    if zero_for_one {
+                        step.tick_next - tick_spacing
+                    } else {
+                        step.tick_next + tick_spacing
+                    },
+                    pool_state.tick_spacing,
+                );
             }
 
             state.tick = if zero_for_one {
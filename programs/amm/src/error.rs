@@ -105,4 +105,64 @@ pub enum ErrorCode {
     MaxTokenOverflow,
     #[msg("calculate overflow")]
     CalculateOverflow,
+    #[msg("Invalid dynamic fee param")]
+    InvalidDynamicFeeParam,
+    #[msg(
+        "Limit order range must be one tick spacing wide and on the input token side of the price"
+    )]
+    InvalidLimitOrderRange,
+    #[msg("Limit order is already filled")]
+    LimitOrderFilled,
+    #[msg("Limit order is not filled yet")]
+    LimitOrderNotFilled,
+    #[msg("Flash loan is not repaid with fee")]
+    FlashLoanNotRepaid,
+    #[msg("Observation is out of the range covered by the oracle")]
+    ObservationOutOfRange,
+    #[msg("Observation account can grow by at most 10240 bytes per instruction")]
+    ObservationCardinalityIncreaseTooLarge,
+    #[msg("Invalid observation update duration")]
+    InvalidObservationUpdateDuration,
+    #[msg("Tick is not initialized")]
+    TickNotInitialized,
+    #[msg("Invalid pool fee param")]
+    InvalidPoolFeeParam,
+    #[msg("Referrer token account must hold the input token")]
+    InvalidReferrerTokenAccount,
+    #[msg("Swap hook must be an executable program other than this one")]
+    InvalidSwapHookProgram,
+    #[msg("Swap hook program of the pool is missing from the remaining accounts")]
+    MissingSwapHookProgram,
+    #[msg("Compound tip rate must not exceed the fee rate denominator")]
+    InvalidCompoundTipRate,
+    #[msg("Rebalance must move the position to a different range")]
+    RebalanceToSameRange,
+    #[msg("Rebalance swap amount exceeds the tokens taken out of the position")]
+    RebalanceSwapAmountTooLarge,
+    #[msg("Merged positions must be distinct, owned by the signer and share the pool and range")]
+    InvalidMergePosition,
+    #[msg("Position is locked")]
+    PositionLocked,
+    #[msg("Unlock timestamp must be later than the current time and the current lock")]
+    InvalidUnlockTimestamp,
+    #[msg("Missing the reward extension of a crossed tick array")]
+    MissingTickArrayRewardExtension,
+    #[msg("The reward extension of the position must be updated first")]
+    RewardExtensionNotUpdated,
+    #[msg("Invalid reward boost param")]
+    InvalidRewardBoostParam,
+    #[msg("The reward schedule is full")]
+    RewardScheduleFull,
+    #[msg("The funded amount is too small to extend the reward")]
+    InvalidRewardFundAmount,
+    #[msg("The reward has reached the max number of funders")]
+    RewardFundersFull,
+    #[msg("Referral fee rate is above the amm config cap")]
+    InvalidReferralFeeRate,
+    #[msg("The payer can not refer its own swap")]
+    ReferrerIsPayer,
+    #[msg("The next epoch of the reward schedule must be advanced first")]
+    RewardScheduleNotAdvanced,
+    #[msg("Missing the oracle extension of a crossed tick array")]
+    MissingTickArrayOracleExtension,
 }
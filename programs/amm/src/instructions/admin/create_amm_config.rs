@@ -55,6 +55,7 @@ pub fn create_amm_config(
         tick_spacing: amm_config.tick_spacing,
         fund_fee_rate: amm_config.fund_fee_rate,
         fund_owner: amm_config.fund_owner,
+        max_referral_fee_rate: amm_config.max_referral_fee_rate,
     });
 
     Ok(())
@@ -21,3 +21,18 @@ pub use transfer_reward_owner::*;
 
 pub mod update_pool_status;
 pub use update_pool_status::*;
+
+pub mod update_pool_dynamic_fee;
+pub use update_pool_dynamic_fee::*;
+
+pub mod update_observation_duration;
+pub use update_observation_duration::*;
+
+pub mod update_pool_fee;
+pub use update_pool_fee::*;
+
+pub mod update_pool_reward_boost;
+pub use update_pool_reward_boost::*;
+
+pub mod update_pool_hook;
+pub use update_pool_hook::*;
@@ -28,6 +28,7 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -
             let new_fund_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
             set_new_fund_owner(amm_config, new_fund_owner);
         }
+        Some(5) => update_max_referral_fee_rate(amm_config, value),
         _ => return err!(ErrorCode::InvalidUpdateConfigFlag),
     }
 
@@ -39,6 +40,7 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -
         tick_spacing: amm_config.tick_spacing,
         fund_fee_rate: amm_config.fund_fee_rate,
         fund_owner: amm_config.fund_owner,
+        max_referral_fee_rate: amm_config.max_referral_fee_rate,
     });
 
     Ok(())
@@ -46,7 +48,10 @@ pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -
 
 fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u32) {
     assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        protocol_fee_rate + amm_config.fund_fee_rate + amm_config.max_referral_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.protocol_fee_rate = protocol_fee_rate;
 }
 
@@ -57,10 +62,22 @@ fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u3
 
 fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u32) {
     assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        fund_fee_rate + amm_config.protocol_fee_rate + amm_config.max_referral_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
     amm_config.fund_fee_rate = fund_fee_rate;
 }
 
+fn update_max_referral_fee_rate(amm_config: &mut Account<AmmConfig>, max_referral_fee_rate: u32) {
+    assert!(max_referral_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+    assert!(
+        max_referral_fee_rate + amm_config.protocol_fee_rate + amm_config.fund_fee_rate
+            <= FEE_RATE_DENOMINATOR_VALUE
+    );
+    amm_config.max_referral_fee_rate = max_referral_fee_rate;
+}
+
 fn set_new_owner(amm_config: &mut Account<AmmConfig>, new_owner: Pubkey) {
     #[cfg(feature = "enable-log")]
     msg!(
@@ -0,0 +1,34 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdateObservationDuration<'info> {
+    #[account(
+        address = crate::admin::id()
+    )]
+    pub authority: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+pub fn update_observation_duration(
+    ctx: Context<UpdateObservationDuration>,
+    update_duration: u32,
+) -> Result<()> {
+    require!(
+        update_duration > 0 && update_duration <= OBSERVATION_UPDATE_DURATION_MAX,
+        ErrorCode::InvalidObservationUpdateDuration
+    );
+    let mut observation_state = ctx.accounts.observation_state.load_mut()?;
+    observation_state.update_duration = update_duration;
+
+    emit!(ObservationUpdateDurationChangeEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        update_duration,
+    });
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolDynamicFee<'info> {
+    #[account(
+        address = crate::admin::id()
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The observations of the pool, the window is bounded by their history
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+pub fn update_pool_dynamic_fee(
+    ctx: Context<UpdatePoolDynamicFee>,
+    enabled: bool,
+    volatility_window: u32,
+    volatility_fee_rate: u32,
+    min_fee_rate: u32,
+    max_fee_rate: u32,
+) -> Result<()> {
+    require!(
+        min_fee_rate <= max_fee_rate && max_fee_rate < FEE_RATE_DENOMINATOR_VALUE,
+        ErrorCode::InvalidDynamicFeeParam
+    );
+    // the window can't reach back further than the observations kept
+    require!(
+        !enabled
+            || (volatility_window > 0
+                && volatility_window <= ctx.accounts.observation_state.load()?.history_duration()),
+        ErrorCode::InvalidDynamicFeeParam
+    );
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.dynamic_fee_info = DynamicFeeInfo {
+        enabled,
+        volatility_window,
+        volatility_fee_rate,
+        min_fee_rate,
+        max_fee_rate,
+        ..Default::default()
+    };
+
+    emit!(PoolDynamicFeeChangeEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        enabled,
+        volatility_window,
+        volatility_fee_rate,
+        min_fee_rate,
+        max_fee_rate,
+    });
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolFee<'info> {
+    /// The admin or an operation owner
+    pub authority: Signer<'info>,
+
+    /// load info from the account to judge the authority
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: AccountLoader<'info, OperationState>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn update_pool_fee(
+    ctx: Context<UpdatePoolFee>,
+    enabled: bool,
+    trade_fee_rate: u32,
+    protocol_fee_rate: u32,
+    fund_fee_rate: u32,
+) -> Result<()> {
+    require!(
+        ctx.accounts.authority.key() == crate::admin::id()
+            || ctx
+                .accounts
+                .operation_state
+                .load()?
+                .validate_operation_owner(ctx.accounts.authority.key()),
+        ErrorCode::NotApproved
+    );
+    require!(
+        trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE
+            && u64::from(protocol_fee_rate) + u64::from(fund_fee_rate)
+                <= u64::from(FEE_RATE_DENOMINATOR_VALUE),
+        ErrorCode::InvalidPoolFeeParam
+    );
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.fee_override = PoolFeeOverride {
+        enabled,
+        trade_fee_rate,
+        protocol_fee_rate,
+        fund_fee_rate,
+        ..Default::default()
+    };
+
+    emit!(PoolFeeChangeEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        enabled,
+        trade_fee_rate,
+        protocol_fee_rate,
+        fund_fee_rate,
+    });
+    Ok(())
+}
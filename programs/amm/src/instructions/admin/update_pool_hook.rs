@@ -0,0 +1,35 @@
+use crate::states::*;
+use crate::util::check_swap_hook_program;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolHook<'info> {
+    #[account(
+        address = crate::admin::id()
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    // remaining accounts
+    // hook_program: optional, the new hook program, the hook is removed if not given
+}
+
+pub fn update_pool_hook(ctx: Context<UpdatePoolHook>) -> Result<()> {
+    let hook_program = match ctx.remaining_accounts.first() {
+        Some(hook_program) => {
+            check_swap_hook_program(hook_program)?;
+            hook_program.key()
+        }
+        None => Pubkey::default(),
+    };
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.hook_program = hook_program;
+
+    emit!(PoolHookChangeEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        hook_program,
+    });
+    Ok(())
+}
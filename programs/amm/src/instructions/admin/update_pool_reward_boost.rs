@@ -0,0 +1,77 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePoolRewardBoost<'info> {
+    /// The admin or an operation owner
+    pub authority: Signer<'info>,
+
+    /// load info from the account to judge the authority
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: AccountLoader<'info, OperationState>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn update_pool_reward_boost(
+    ctx: Context<UpdatePoolRewardBoost>,
+    boosted_reward_flags: u8,
+    curve: u8,
+    reference_tick_width: u32,
+    max_boost_rate: u32,
+) -> Result<()> {
+    require!(
+        ctx.accounts.authority.key() == crate::admin::id()
+            || ctx
+                .accounts
+                .operation_state
+                .load()?
+                .validate_operation_owner(ctx.accounts.authority.key()),
+        ErrorCode::NotApproved
+    );
+    require!(
+        u32::from(boosted_reward_flags) < 1 << REWARD_NUM
+            && (boosted_reward_flags == 0 || max_boost_rate != 0)
+            && (max_boost_rate == 0
+                || (max_boost_rate >= REWARD_BOOST_RATE_DENOMINATOR
+                    && reference_tick_width > 0
+                    && curve <= RewardBoostCurve::Linear as u8)),
+        ErrorCode::InvalidRewardBoostParam
+    );
+
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.update_reward_infos(Clock::get()?.unix_timestamp as u64)?;
+    // A reward can only switch between the liquidity and the boosted liquidity before it emits
+    let changed_reward_flags = pool_state.reward_boost.boosted_reward_flags ^ boosted_reward_flags;
+    for i in 0..REWARD_NUM {
+        if changed_reward_flags & (1 << i) != 0 {
+            require!(
+                pool_state.reward_infos[i].reward_growth_global_x64 == 0,
+                ErrorCode::InvalidRewardBoostParam
+            );
+        }
+    }
+    pool_state.reward_boost = RewardBoost {
+        boosted_reward_flags,
+        curve,
+        reference_tick_width,
+        max_boost_rate,
+        ..Default::default()
+    };
+
+    emit!(PoolRewardBoostChangeEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        boosted_reward_flags,
+        curve,
+        reference_tick_width,
+        max_boost_rate,
+    });
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use super::{get_limit_order_tickarray_bitmap_extension, update_limit_order_tick_array};
+use crate::error::ErrorCode;
+use crate::libraries::liquidity_math;
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CancelLimitOrder<'info> {
+    /// The owner of the order, receives the rent of the closed order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The pool the order is placed in
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The pending order to cancel
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = limit_order.pool_id == pool_state.key(),
+        close = owner
+    )]
+    pub limit_order: Box<Account<'info, LimitOrderState>>,
+
+    /// Stores the lower and upper tick of the order range
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for receive amount_0
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for receive amount_1
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining account
+    // #[account(
+    //     seeds = [
+    //         POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
+    //         pool_state.key().as_ref(),
+    //     ],
+    //     bump
+    // )]
+    // pub tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+/// Cancel a pending limit order. The price may be inside the order range, in which case
+/// the order is partly converted and both tokens are returned.
+pub fn cancel_limit_order<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CancelLimitOrder<'info>>,
+    amount_0_min: u64,
+    amount_1_min: u64,
+) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+    let liquidity = limit_order.liquidity;
+    let (amount_0, amount_1) = {
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity) {
+            return err!(ErrorCode::NotApproved);
+        }
+        let tick_lower_index = limit_order.tick_lower_index;
+        let tick_upper_index = limit_order.tick_upper_index(pool_state.tick_spacing);
+
+        let tick_array = &mut ctx.accounts.tick_array.load_mut()?;
+        let mut tick_lower_state =
+            *tick_array.get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
+        let mut tick_upper_state =
+            *tick_array.get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
+        require!(
+            !limit_order.is_filled(if limit_order.zero_for_one {
+                &tick_upper_state
+            } else {
+                &tick_lower_state
+            }),
+            ErrorCode::LimitOrderFilled
+        );
+
+        let updated_reward_infos =
+            pool_state.update_reward_infos(Clock::get()?.unix_timestamp as u64)?;
+        let liquidity_delta = -i128::try_from(liquidity).unwrap();
+        let flip_tick_lower = tick_lower_state.update_liquidity_gross(
+            pool_state.tick_current,
+            liquidity_delta,
+            pool_state.fee_growth_global_0_x64,
+            pool_state.fee_growth_global_1_x64,
+            pool_state.seconds_per_liquidity_global_x64,
+            &updated_reward_infos,
+        )?;
+        let flip_tick_upper = tick_upper_state.update_liquidity_gross(
+            pool_state.tick_current,
+            liquidity_delta,
+            pool_state.fee_growth_global_0_x64,
+            pool_state.fee_growth_global_1_x64,
+            pool_state.seconds_per_liquidity_global_x64,
+            &updated_reward_infos,
+        )?;
+        if limit_order.zero_for_one {
+            tick_upper_state.update_order_liquidity(liquidity_delta)?;
+        } else {
+            tick_lower_state.update_order_liquidity(liquidity_delta)?;
+        }
+        if flip_tick_lower {
+            tick_lower_state.clear();
+        }
+        if flip_tick_upper {
+            tick_upper_state.clear();
+        }
+        tick_array.update_tick_state(
+            tick_lower_index,
+            pool_state.tick_spacing,
+            tick_lower_state,
+        )?;
+        tick_array.update_tick_state(
+            tick_upper_index,
+            pool_state.tick_spacing,
+            tick_upper_state,
+        )?;
+
+        let tickarray_bitmap_extension = get_limit_order_tickarray_bitmap_extension(
+            pool_state,
+            tick_array.start_tick_index,
+            ctx.remaining_accounts,
+        )?;
+        update_limit_order_tick_array(
+            pool_state,
+            tick_array,
+            tickarray_bitmap_extension,
+            u8::from(flip_tick_lower) + u8::from(flip_tick_upper),
+            false,
+        )?;
+
+        // The order is partly converted when the price is inside its range, its liquidity is
+        // not part of the pool liquidity so only the tokens are returned
+        liquidity_math::get_delta_amounts_signed(
+            pool_state.tick_current,
+            pool_state.sqrt_price_x64,
+            tick_lower_index,
+            tick_upper_index,
+            liquidity_delta,
+        )?
+    };
+
+    let transfer_fee_0 = get_transfer_fee(ctx.accounts.vault_0_mint.clone(), amount_0)?;
+    let transfer_fee_1 = get_transfer_fee(ctx.accounts.vault_1_mint.clone(), amount_1)?;
+    require_gte!(
+        amount_0 - transfer_fee_0,
+        amount_0_min,
+        ErrorCode::PriceSlippageCheck
+    );
+    require_gte!(
+        amount_1 - transfer_fee_1,
+        amount_1_min,
+        ErrorCode::PriceSlippageCheck
+    );
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.recipient_token_account_0,
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.recipient_token_account_1,
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+
+    emit!(CancelLimitOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower_index: limit_order.tick_lower_index,
+        liquidity,
+        amount_0,
+        amount_1,
+    });
+
+    Ok(())
+}
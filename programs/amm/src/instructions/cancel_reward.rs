@@ -0,0 +1,208 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::{transfer_from_pool_vault_to_user, AccountLoad};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(reward_index: u8)]
+pub struct CancelReward<'info> {
+    /// The reward authority or the admin
+    pub authority: Signer<'info>,
+
+    /// The reward authority's token account to refund the unemitted reward to
+    #[account(
+        mut,
+        token::mint = reward_vault_mint
+    )]
+    pub funder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: The queued epochs of the reward, the account only exists once an epoch was queued
+    #[account(
+        mut,
+        seeds = [
+            REWARD_SCHEDULE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &[reward_index],
+        ],
+        bump,
+    )]
+    pub reward_schedule: UncheckedAccount<'info>,
+
+    /// CHECK: The deposits into the reward, the account only exists once the reward was funded
+    #[account(
+        mut,
+        seeds = [
+            REWARD_FUNDING_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &[reward_index],
+        ],
+        bump,
+    )]
+    pub reward_funding: UncheckedAccount<'info>,
+
+    /// The vault of the cancelled reward
+    #[account(mut)]
+    pub reward_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of reward token vault
+    #[account(address = reward_token_vault.mint)]
+    pub reward_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining accounts
+    // the token account of every funder of the reward, in the order of their deposits
+    // then the token account of the funder of the pending epoch and of every queued epoch, in time order
+}
+
+pub fn cancel_reward<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CancelReward<'info>>,
+    reward_index: u8,
+) -> Result<()> {
+    let index = usize::from(reward_index);
+    if index >= REWARD_NUM {
+        return err!(ErrorCode::InvalidRewardIndex);
+    }
+
+    let current_timestamp = u64::try_from(Clock::get()?.unix_timestamp).unwrap();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    // Everything emitted up to now stays owed to the positions
+    pool_state.update_reward_infos(current_timestamp)?;
+
+    let mut reward_info = pool_state.reward_infos[index];
+    if !reward_info.initialized() {
+        return err!(ErrorCode::UnInitializedRewardInfo);
+    }
+    require!(
+        ctx.accounts.authority.key() == reward_info.authority
+            || ctx.accounts.authority.key() == crate::admin::id(),
+        ErrorCode::NotApproved
+    );
+    require_keys_eq!(
+        ctx.accounts.reward_token_vault.key(),
+        reward_info.token_vault
+    );
+    require_keys_eq!(
+        ctx.accounts.funder_token_account.owner,
+        reward_info.authority
+    );
+    require_gt!(
+        reward_info.end_time,
+        current_timestamp,
+        ErrorCode::InvalidRewardPeriod
+    );
+
+    // Everything in the vault is refunded but the emitted reward not claimed yet
+    let unemitted_amount = ctx
+        .accounts
+        .reward_token_vault
+        .amount
+        .checked_sub(
+            reward_info
+                .reward_total_emissioned
+                .checked_sub(reward_info.reward_claimed)
+                .ok_or(ErrorCode::CalculateOverflow)?,
+        )
+        .ok_or(ErrorCode::CalculateOverflow)?;
+
+    // The deposits are the end of the current epoch, so they are refunded first out of what is
+    // left of it, the funding of the pending and queued epochs goes back to who queued them
+    let epoch_unemitted_amount = unemitted_amount
+        .checked_sub(pool_state.reward_scheduled_amounts[index])
+        .ok_or(ErrorCode::CalculateOverflow)?;
+    let mut funder_refund_amounts = [0; REWARD_FUNDER_NUM];
+    let mut funders = Vec::new();
+    if !ctx.accounts.reward_funding.data_is_empty() {
+        let mut reward_funding =
+            AccountLoad::<RewardFundingState>::load_data_mut(&ctx.accounts.reward_funding)?;
+        funder_refund_amounts =
+            reward_funding.get_refund_amounts(reward_info.open_time, epoch_unemitted_amount);
+        funders = reward_funding
+            .get_deposits(reward_info.open_time)
+            .iter()
+            .map(|deposit| deposit.funder)
+            .collect();
+        reward_funding.clear();
+    }
+
+    // The pending and queued epochs are cancelled along with the reward
+    let mut epoch_fundings = Vec::new();
+    if !ctx.accounts.reward_schedule.data_is_empty() {
+        let mut reward_schedule =
+            AccountLoad::<RewardScheduleState>::load_data_mut(&ctx.accounts.reward_schedule)?;
+        epoch_fundings = reward_schedule.get_epoch_fundings(&pool_state);
+        reward_schedule.clear();
+    }
+    let refund_amount = unemitted_amount
+        .checked_sub(funder_refund_amounts.iter().sum())
+        .and_then(|amount| {
+            amount.checked_sub(epoch_fundings.iter().map(|(_, amount)| amount).sum())
+        })
+        .ok_or(ErrorCode::CalculateOverflow)?;
+    pool_state.reward_pending_epochs[index] = RewardEpoch::default();
+    pool_state.reward_scheduled_amounts[index] = 0;
+
+    // A reward not opened yet is cancelled before it emits anything
+    if reward_info.open_time > current_timestamp {
+        reward_info.open_time = current_timestamp;
+        reward_info.last_update_time = current_timestamp;
+    }
+    reward_info.end_time = current_timestamp;
+    reward_info.reward_state = RewardState::Ended as u8;
+    pool_state.reward_infos[index] = reward_info;
+    drop(pool_state);
+
+    emit!(RewardCancelledEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        reward_index,
+        end_time: current_timestamp,
+        refund_amount,
+    });
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.reward_token_vault,
+        &ctx.accounts.funder_token_account,
+        Some(ctx.accounts.reward_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        refund_amount,
+    )?;
+
+    let refunds = funders
+        .into_iter()
+        .zip(funder_refund_amounts)
+        .chain(epoch_fundings)
+        .collect::<Vec<_>>();
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        refunds.len(),
+        ErrorCode::InvalidRewardInputAccountNumber
+    );
+    for (i, (funder, amount)) in refunds.into_iter().enumerate() {
+        let funder_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(&ctx.remaining_accounts[i])?;
+        require_keys_eq!(funder_token_account.owner, funder);
+        require_keys_eq!(
+            funder_token_account.mint,
+            ctx.accounts.reward_token_vault.mint
+        );
+        transfer_from_pool_vault_to_user(
+            &ctx.accounts.pool_state,
+            &ctx.accounts.reward_token_vault,
+            &funder_token_account,
+            Some(ctx.accounts.reward_vault_mint.clone()),
+            &ctx.accounts.token_program,
+            Some(ctx.accounts.token_program_2022.to_account_info()),
+            amount,
+        )?;
+    }
+    Ok(())
+}
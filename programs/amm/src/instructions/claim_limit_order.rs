@@ -0,0 +1,159 @@
+use super::{get_limit_order_tickarray_bitmap_extension, update_limit_order_tick_array};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+pub struct ClaimLimitOrder<'info> {
+    /// The owner of the order, receives the rent of the closed order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The pool the order is placed in
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The filled order to claim
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = limit_order.pool_id == pool_state.key(),
+        close = owner
+    )]
+    pub limit_order: Box<Account<'info, LimitOrderState>>,
+
+    /// Stores the lower and upper tick of the order range
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// The vault of the token bought by the order
+    #[account(mut)]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the order output
+    #[account(
+        mut,
+        token::mint = output_vault.mint
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the output vault
+    #[account(
+        address = output_vault.mint
+    )]
+    pub output_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining account
+    // #[account(
+    //     seeds = [
+    //         POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
+    //         pool_state.key().as_ref(),
+    //     ],
+    //     bump
+    // )]
+    // pub tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+/// Claim a filled limit order. Its liquidity was taken out of the range by the swap that filled
+/// it, so only the reserved tick liquidity is released and the output token is paid.
+pub fn claim_limit_order<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ClaimLimitOrder<'info>>,
+) -> Result<()> {
+    let limit_order = &ctx.accounts.limit_order;
+    let amount_out = {
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity) {
+            return err!(ErrorCode::NotApproved);
+        }
+        // The order is paid in the token it bought
+        require_keys_eq!(
+            ctx.accounts.output_vault.key(),
+            if limit_order.zero_for_one {
+                pool_state.token_vault_1
+            } else {
+                pool_state.token_vault_0
+            }
+        );
+        let tick_lower_index = limit_order.tick_lower_index;
+        let tick_upper_index = limit_order.tick_upper_index(pool_state.tick_spacing);
+
+        let tick_array = &mut ctx.accounts.tick_array.load_mut()?;
+        let mut tick_lower_state =
+            *tick_array.get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
+        let mut tick_upper_state =
+            *tick_array.get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
+        require!(
+            limit_order.is_filled(if limit_order.zero_for_one {
+                &tick_upper_state
+            } else {
+                &tick_lower_state
+            }),
+            ErrorCode::LimitOrderNotFilled
+        );
+
+        let flip_tick_lower = tick_lower_state.release_filled_liquidity(limit_order.liquidity);
+        let flip_tick_upper = tick_upper_state.release_filled_liquidity(limit_order.liquidity);
+        if flip_tick_lower {
+            tick_lower_state.clear();
+        }
+        if flip_tick_upper {
+            tick_upper_state.clear();
+        }
+        tick_array.update_tick_state(
+            tick_lower_index,
+            pool_state.tick_spacing,
+            tick_lower_state,
+        )?;
+        tick_array.update_tick_state(
+            tick_upper_index,
+            pool_state.tick_spacing,
+            tick_upper_state,
+        )?;
+
+        let tickarray_bitmap_extension = get_limit_order_tickarray_bitmap_extension(
+            pool_state,
+            tick_array.start_tick_index,
+            ctx.remaining_accounts,
+        )?;
+        update_limit_order_tick_array(
+            pool_state,
+            tick_array,
+            tickarray_bitmap_extension,
+            u8::from(flip_tick_lower) + u8::from(flip_tick_upper),
+            false,
+        )?;
+
+        limit_order.get_filled_amount(pool_state.tick_spacing)?
+    };
+
+    let amount_out_transfer_fee =
+        get_transfer_fee(ctx.accounts.output_vault_mint.clone(), amount_out)?;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.output_vault,
+        &ctx.accounts.recipient_token_account,
+        Some(ctx.accounts.output_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_out,
+    )?;
+
+    emit!(ClaimLimitOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower_index: limit_order.tick_lower_index,
+        zero_for_one: limit_order.zero_for_one,
+        amount_out,
+        amount_out_transfer_fee,
+    });
+
+    Ok(())
+}
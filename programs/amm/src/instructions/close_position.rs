@@ -50,9 +50,11 @@ pub struct ClosePosition<'info> {
     pub token_program: Program<'info, Token>,
     // /// Reserved for upgrade
     // pub token_program_2022: Program<'info, Token2022>,
+    // remaining accounts
+    // position_reward_extension: must add account if the position has one, closed along with it
 }
 
-pub fn close_position<'a, 'b, 'c, 'info>(
+pub fn close_position<'a, 'b, 'c: 'info, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, ClosePosition<'info>>,
 ) -> Result<()> {
     if ctx.accounts.personal_position.liquidity != 0
@@ -67,6 +69,13 @@ pub fn close_position<'a, 'b, 'c, 'info>(
         );
         return err!(ErrorCode::ClosePositionErr);
     }
+    if ctx
+        .accounts
+        .personal_position
+        .is_locked(Clock::get()?.unix_timestamp as u64)
+    {
+        return err!(ErrorCode::PositionLocked);
+    }
 
     for i in 0..ctx.accounts.personal_position.reward_infos.len() {
         if ctx.accounts.personal_position.reward_infos[i].reward_amount_owed != 0 {
@@ -79,6 +88,28 @@ pub fn close_position<'a, 'b, 'c, 'info>(
         }
     }
 
+    if ctx.accounts.personal_position.reward_extension_update_time != 0 {
+        let position_reward_extension_key =
+            PositionRewardExtension::key(ctx.accounts.personal_position.key());
+        let account_info = ctx
+            .remaining_accounts
+            .iter()
+            .find(|account_info| account_info.key() == position_reward_extension_key)
+            .ok_or(ErrorCode::ClosePositionErr)?;
+        let position_reward_extension = Account::<PositionRewardExtension>::try_from(account_info)?;
+        for i in 0..position_reward_extension.reward_infos.len() {
+            if position_reward_extension.reward_infos[i].reward_amount_owed != 0 {
+                msg!(
+                    "remaing extension reward index:{},amount:{}",
+                    i,
+                    position_reward_extension.reward_infos[i].reward_amount_owed,
+                );
+                return err!(ErrorCode::ClosePositionErr);
+            }
+        }
+        position_reward_extension.close(ctx.accounts.nft_owner.to_account_info())?;
+    }
+
     burn(
         &ctx.accounts.nft_owner,
         &ctx.accounts.position_nft_mint,
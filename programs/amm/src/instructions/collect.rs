@@ -0,0 +1,259 @@
+use super::{
+    burn_liquidity, calculate_latest_token_fees, check_unclaimed_fees_and_vault, collect_rewards,
+};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::transfer_from_pool_vault_to_user;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::cell::RefMut;
+use std::ops::DerefMut;
+
+#[derive(Accounts)]
+pub struct Collect<'info> {
+    /// The position owner or delegated authority
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Collect fees and rewards of this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The destination token account for the collected token_0 fee, can be owned by anyone
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the collected token_1 fee, can be owned by anyone
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // rewards are collected only if these are given, for every initialized reward:
+    // reward_token_vault, recipient_token_account, reward_vault_mint
+}
+
+pub fn collect<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, Collect<'info>>,
+    amount_0_requested: u64,
+    amount_1_requested: u64,
+) -> Result<()> {
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let personal_position = &mut ctx.accounts.personal_position;
+    let protocol_position = &mut ctx.accounts.protocol_position;
+    let (amount_0, amount_1);
+    {
+        let mut pool_state = pool_state_loader.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
+            && !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectReward)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        poke_personal_position(
+            &mut pool_state,
+            personal_position,
+            protocol_position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+        )?;
+
+        // The fees are left owed to the position while fee collection is disabled,
+        // or while the position is locked without allowing it
+        let fees_collectable = !personal_position.is_locked(Clock::get()?.unix_timestamp as u64)
+            || personal_position.lock_allow_collect_fee;
+        (amount_0, amount_1) =
+            if pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee) && fees_collectable {
+                (
+                    amount_0_requested.min(personal_position.token_fees_owed_0),
+                    amount_1_requested.min(personal_position.token_fees_owed_1),
+                )
+            } else {
+                (0, 0)
+            };
+
+        // Ensure the pool has enough unclaimed fees to cover the collected amounts
+        require_gte!(
+            pool_state.total_fees_token_0 - pool_state.total_fees_claimed_token_0,
+            amount_0
+        );
+        require_gte!(
+            pool_state.total_fees_token_1 - pool_state.total_fees_claimed_token_1,
+            amount_1
+        );
+
+        personal_position.token_fees_owed_0 -= amount_0;
+        personal_position.token_fees_owed_1 -= amount_1;
+        pool_state.total_fees_claimed_token_0 = pool_state
+            .total_fees_claimed_token_0
+            .checked_add(amount_0)
+            .unwrap();
+        pool_state.total_fees_claimed_token_1 = pool_state
+            .total_fees_claimed_token_1
+            .checked_add(amount_1)
+            .unwrap();
+    }
+
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.recipient_token_account_0,
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.recipient_token_account_1,
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+    check_unclaimed_fees_and_vault(
+        pool_state_loader,
+        ctx.accounts.token_vault_0.deref_mut(),
+        ctx.accounts.token_vault_1.deref_mut(),
+    )?;
+
+    // The tick array bitmap extension is not needed, skip it if given out of habit
+    let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state_loader.key());
+    let reward_accounts: Vec<&AccountInfo> = ctx
+        .remaining_accounts
+        .iter()
+        .filter(|account_info| account_info.key() != tick_array_bitmap_extension_key)
+        .collect();
+    if !reward_accounts.is_empty() {
+        collect_rewards(
+            pool_state_loader,
+            reward_accounts.as_slice(),
+            &ctx.accounts.token_program,
+            Some(ctx.accounts.token_program_2022.to_account_info()),
+            personal_position,
+            true,
+        )?;
+    }
+
+    emit!(CollectPersonalFeeEvent {
+        position_nft_mint: personal_position.nft_mint,
+        recipient_token_account_0: ctx.accounts.recipient_token_account_0.key(),
+        recipient_token_account_1: ctx.accounts.recipient_token_account_1.key(),
+        amount_0,
+        amount_1,
+    });
+
+    Ok(())
+}
+
+/// Brings the fees and rewards owed to a position up to date without changing its liquidity
+pub fn poke_personal_position<'info>(
+    pool_state: &mut RefMut<PoolState>,
+    personal_position: &mut PersonalPositionState,
+    protocol_position: &mut ProtocolPositionState,
+    tick_array_lower_loader: &AccountLoader<'info, TickArrayState>,
+    tick_array_upper_loader: &AccountLoader<'info, TickArrayState>,
+) -> Result<()> {
+    if personal_position.liquidity == 0 {
+        return Ok(());
+    }
+    burn_liquidity(
+        pool_state,
+        tick_array_lower_loader,
+        tick_array_upper_loader,
+        protocol_position,
+        None,
+        0,
+    )?;
+    settle_personal_position(personal_position, protocol_position)
+}
+
+/// Brings the fees and rewards owed to a position up to date with its protocol position,
+/// which must have been updated first
+pub fn settle_personal_position(
+    personal_position: &mut PersonalPositionState,
+    protocol_position: &ProtocolPositionState,
+) -> Result<()> {
+    personal_position.token_fees_owed_0 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_0,
+        personal_position.fee_growth_inside_0_last_x64,
+        protocol_position.fee_growth_inside_0_last_x64,
+        personal_position.liquidity,
+    );
+    personal_position.token_fees_owed_1 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_1,
+        personal_position.fee_growth_inside_1_last_x64,
+        protocol_position.fee_growth_inside_1_last_x64,
+        personal_position.liquidity,
+    );
+    personal_position.fee_growth_inside_0_last_x64 = protocol_position.fee_growth_inside_0_last_x64;
+    personal_position.fee_growth_inside_1_last_x64 = protocol_position.fee_growth_inside_1_last_x64;
+    personal_position.update_rewards(protocol_position.reward_growth_inside, true)
+}
@@ -0,0 +1,154 @@
+use super::update_position_reward_extension_rewards;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::transfer_from_pool_vault_to_user;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+pub struct CollectExtensionRewards<'info> {
+    /// The position owner or delegated authority
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The position earning the extension rewards
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The reward extension of the pool
+    #[account(
+        mut,
+        seeds = [
+            REWARD_EXTENSION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump = reward_extension.bump,
+    )]
+    pub reward_extension: Box<Account<'info, RewardExtension>>,
+
+    /// The reward extension of the tick array of the lower tick
+    #[account(constraint = tick_array_lower_reward_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    /// The reward extension of the tick array of the upper tick
+    #[account(constraint = tick_array_upper_reward_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    /// The extension rewards owed to the position
+    #[account(
+        mut,
+        seeds = [
+            POSITION_REWARD_EXTENSION_SEED.as_bytes(),
+            personal_position.key().as_ref(),
+        ],
+        bump = position_reward_extension.bump,
+    )]
+    pub position_reward_extension: Box<Account<'info, PositionRewardExtension>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+    // remaining accounts
+    // for every initialized extension reward, in reward order:
+    // reward_token_vault, recipient_token_account, reward_vault_mint
+}
+
+pub fn collect_extension_rewards<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CollectExtensionRewards<'info>>,
+) -> Result<()> {
+    if !ctx
+        .accounts
+        .pool_state
+        .load()?
+        .get_status_by_bit(PoolStatusBitIndex::CollectReward)
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let position_reward_extension = &mut ctx.accounts.position_reward_extension;
+    let reward_extension = &mut ctx.accounts.reward_extension;
+    update_position_reward_extension_rewards(
+        &ctx.accounts.pool_state,
+        &mut ctx.accounts.personal_position,
+        position_reward_extension,
+        &ctx.accounts.tick_array_lower_reward_extension,
+        &ctx.accounts.tick_array_upper_reward_extension,
+    )?;
+    let reward_growths = ctx.accounts.pool_state.load()?.extension_reward_growths;
+
+    let reward_group_account_num = 3;
+    let valid_reward_count = reward_extension
+        .reward_infos
+        .iter()
+        .filter(|reward_info| reward_info.initialized())
+        .count();
+    if ctx.remaining_accounts.len() != valid_reward_count * reward_group_account_num {
+        return err!(ErrorCode::InvalidRewardInputAccountNumber);
+    }
+
+    let mut reward_amounts = [0u64; EXTENSION_REWARD_NUM];
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    for i in 0..valid_reward_count {
+        let reward_token_vault =
+            InterfaceAccount::<TokenAccount>::try_from(remaining_accounts.next().unwrap())?;
+        let recipient_token_account =
+            InterfaceAccount::<TokenAccount>::try_from(remaining_accounts.next().unwrap())?;
+        let reward_vault_mint = Box::new(InterfaceAccount::<Mint>::try_from(
+            remaining_accounts.next().unwrap(),
+        )?);
+        require_keys_eq!(reward_token_vault.mint, recipient_token_account.mint);
+        require_keys_eq!(
+            reward_token_vault.key(),
+            reward_extension.reward_infos[i].token_vault
+        );
+
+        let reward_amount_owed = position_reward_extension.reward_infos[i].reward_amount_owed;
+        if reward_amount_owed == 0 {
+            continue;
+        }
+        let unclaimed_reward = reward_growths[i]
+            .reward_total_emissioned
+            .checked_sub(reward_extension.reward_infos[i].reward_claimed)
+            .unwrap();
+        require_gte!(unclaimed_reward, reward_amount_owed);
+
+        let transfer_amount = reward_amount_owed.min(reward_token_vault.amount);
+        if transfer_amount > 0 {
+            position_reward_extension.reward_infos[i].reward_amount_owed =
+                reward_amount_owed.checked_sub(transfer_amount).unwrap();
+            reward_extension.reward_infos[i].reward_claimed = reward_extension.reward_infos[i]
+                .reward_claimed
+                .checked_add(transfer_amount)
+                .unwrap();
+
+            transfer_from_pool_vault_to_user(
+                &ctx.accounts.pool_state,
+                &reward_token_vault,
+                &recipient_token_account,
+                Some(reward_vault_mint),
+                &ctx.accounts.token_program,
+                Some(ctx.accounts.token_program_2022.to_account_info()),
+                transfer_amount,
+            )?;
+        }
+        reward_amounts[i] = transfer_amount;
+    }
+
+    emit!(CollectExtensionRewardsEvent {
+        position_nft_mint: ctx.accounts.personal_position.nft_mint,
+        reward_amounts,
+    });
+
+    Ok(())
+}
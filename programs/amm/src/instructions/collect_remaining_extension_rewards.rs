@@ -0,0 +1,114 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::transfer_from_pool_vault_to_user;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{self, Token},
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct CollectRemainingExtensionRewards<'info> {
+    /// The authority who initialized the extension reward
+    pub reward_funder: Signer<'info>,
+    /// The funder's reward token account
+    #[account(
+        mut,
+        token::mint = reward_vault_mint
+    )]
+    pub funder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    /// The reward extension of the pool
+    #[account(
+        seeds = [
+            REWARD_EXTENSION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump = reward_extension.bump,
+    )]
+    pub reward_extension: Box<Account<'info, RewardExtension>>,
+    /// Reward vault transfer remaining token to founder token account
+    #[account(mut)]
+    pub reward_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// The mint of reward token vault
+    #[account(
+        address = reward_token_vault.mint
+    )]
+    pub reward_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = token::ID)]
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn collect_remaining_extension_rewards(
+    ctx: Context<CollectRemainingExtensionRewards>,
+    reward_index: u8,
+) -> Result<()> {
+    let amount_remaining = get_remaining_extension_reward_amount(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.reward_extension,
+        &ctx.accounts.reward_token_vault,
+        &ctx.accounts.reward_funder.key(),
+        reward_index,
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.reward_token_vault,
+        &ctx.accounts.funder_token_account,
+        Some(ctx.accounts.reward_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_remaining,
+    )?;
+
+    Ok(())
+}
+
+fn get_remaining_extension_reward_amount(
+    pool_state_loader: &AccountLoader<PoolState>,
+    reward_extension: &RewardExtension,
+    reward_token_vault: &InterfaceAccount<TokenAccount>,
+    reward_funder: &Pubkey,
+    reward_index: u8,
+) -> Result<u64> {
+    let reward_index = reward_index as usize;
+    require_gt!(EXTENSION_REWARD_NUM, reward_index);
+
+    let current_timestamp = u64::try_from(Clock::get()?.unix_timestamp).unwrap();
+    let mut pool_state = pool_state_loader.load_mut()?;
+    pool_state.update_reward_infos(current_timestamp)?;
+
+    let reward_info = reward_extension.reward_infos[reward_index];
+    let reward_growth = pool_state.extension_reward_growths[reward_index];
+    if !reward_info.initialized() {
+        return err!(ErrorCode::UnInitializedRewardInfo);
+    }
+
+    // Only once the reward has ended, nothing more is emitted then
+    require_eq!(
+        reward_growth.last_update_time,
+        reward_growth.end_time,
+        ErrorCode::NotApproved
+    );
+
+    // The extension rewards are funded by their authority rather than the pool owner
+    require_keys_eq!(*reward_funder, reward_info.authority);
+    require_keys_eq!(reward_token_vault.key(), reward_info.token_vault);
+
+    // The emitted rewards not claimed yet stay owed to the positions, the rest, not emitted
+    // while the pool had no liquidity in range, goes back to the authority
+    let amount_remaining = reward_token_vault
+        .amount
+        .checked_sub(
+            reward_growth
+                .reward_total_emissioned
+                .checked_sub(reward_info.reward_claimed)
+                .unwrap(),
+        )
+        .unwrap();
+
+    Ok(amount_remaining)
+}
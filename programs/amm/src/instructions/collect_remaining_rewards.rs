@@ -104,7 +104,7 @@ fn get_remaining_reward_amount(
     // Ensure that the reward token vault matches the expected token vault in the reward information
     require_keys_eq!(reward_token_vault.key(), reward_info.token_vault);
 
-    // Calculate the remaining amount of the reward token
+    // Calculate the remaining amount of the reward token, the funding of the queued epochs stays
     let amount_remaining = reward_token_vault
         .amount
         .checked_sub(
@@ -113,7 +113,8 @@ fn get_remaining_reward_amount(
                 .checked_sub(reward_info.reward_claimed)
                 .unwrap(),
         )
-        .unwrap();
+        .unwrap()
+        .saturating_sub(pool_state.reward_scheduled_amounts[reward_index as usize]);
 
     // Return the remaining amount
     Ok(amount_remaining)
@@ -0,0 +1,303 @@
+use super::{check_unclaimed_fees_and_vault, mint_liquidity, poke_personal_position};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::{transfer_from_pool_vault_to_user, AccountLoad};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::ops::DerefMut;
+
+#[derive(Accounts)]
+pub struct CompoundPosition<'info> {
+    /// The position owner, or the compound keeper approved by the owner
+    pub authority: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Compound the fees of this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // compound_keeper_state: required for the keeper
+    // tip_token_account_0, tip_token_account_1: optional, the keeper's token accounts receiving the tip
+}
+
+pub fn compound_position<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, CompoundPosition<'info>>,
+) -> Result<()> {
+    let personal_position = &mut ctx.accounts.personal_position;
+    require_eq!(ctx.accounts.nft_account.amount, 1, ErrorCode::NotApproved);
+    // The keeper is only approved while the NFT stays with the owner who set it
+    let compound_keeper_state = find_compound_keeper_state(ctx.remaining_accounts)?;
+    let is_keeper = compound_keeper_state
+        .as_ref()
+        .map(|compound_keeper_state| {
+            compound_keeper_state.is_keeper(
+                personal_position.nft_mint,
+                ctx.accounts.nft_account.owner,
+                ctx.accounts.authority.key(),
+            )
+        })
+        .unwrap_or(false);
+    require!(
+        is_keeper || ctx.accounts.nft_account.owner == ctx.accounts.authority.key(),
+        ErrorCode::NotApproved
+    );
+
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state_loader.key());
+    let tick_array_bitmap_extension = ctx
+        .remaining_accounts
+        .iter()
+        .find(|account_info| account_info.key() == tick_array_bitmap_extension_key);
+    let tip_accounts: Vec<&AccountInfo> = ctx
+        .remaining_accounts
+        .iter()
+        .filter(|account_info| {
+            *account_info.owner == Token::id() || *account_info.owner == Token2022::id()
+        })
+        .collect();
+    // Only the keeper is tipped, and only if it tells where to send the tip
+    let (tip_token_account_0, tip_token_account_1) = if is_keeper && tip_accounts.len() >= 2 {
+        let tip_token_account_0 =
+            Box::new(InterfaceAccount::<TokenAccount>::try_from(tip_accounts[0])?);
+        let tip_token_account_1 =
+            Box::new(InterfaceAccount::<TokenAccount>::try_from(tip_accounts[1])?);
+        require_keys_eq!(tip_token_account_0.mint, ctx.accounts.token_vault_0.mint);
+        require_keys_eq!(tip_token_account_1.mint, ctx.accounts.token_vault_1.mint);
+        (Some(tip_token_account_0), Some(tip_token_account_1))
+    } else {
+        (None, None)
+    };
+
+    let protocol_position = &mut ctx.accounts.protocol_position;
+    let (liquidity, amount_0, amount_1, tip_amount_0, tip_amount_1);
+    {
+        let mut pool_state = pool_state_loader.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        // The fees leave what is owed to the position, the lock must allow taking them
+        let current_timestamp = Clock::get()?.unix_timestamp as u64;
+        personal_position.check_unlocked(0, current_timestamp)?;
+
+        poke_personal_position(
+            &mut pool_state,
+            personal_position,
+            protocol_position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+        )?;
+
+        // The keeper is tipped on the reinvested amounts, so they are sized to leave room for it
+        let tip_rate = match (&compound_keeper_state, &tip_token_account_0) {
+            (Some(compound_keeper_state), Some(_)) => compound_keeper_state.tip_rate,
+            _ => 0,
+        };
+        let available_0 = personal_position.token_fees_owed_0
+            - get_compound_tip(personal_position.token_fees_owed_0, tip_rate);
+        let available_1 = personal_position.token_fees_owed_1
+            - get_compound_tip(personal_position.token_fees_owed_1, tip_rate);
+
+        // Keep one unit of each token as a margin for the amounts being rounded up
+        let tick_lower_index = personal_position.tick_lower_index;
+        let tick_upper_index = personal_position.tick_upper_index;
+        liquidity = liquidity_math::get_liquidity_from_amounts(
+            pool_state.sqrt_price_x64,
+            tick_math::get_sqrt_price_at_tick(tick_lower_index)?,
+            tick_math::get_sqrt_price_at_tick(tick_upper_index)?,
+            available_0.saturating_sub(1),
+            available_1.saturating_sub(1),
+        );
+        require_gt!(liquidity, 0, ErrorCode::InvaildLiquidity);
+
+        let liquidity_before = pool_state.liquidity;
+        (amount_0, amount_1) = mint_liquidity(
+            &mut pool_state,
+            &AccountLoad::<TickArrayState>::try_from(
+                &ctx.accounts.tick_array_lower.to_account_info(),
+            )?,
+            &AccountLoad::<TickArrayState>::try_from(
+                &ctx.accounts.tick_array_upper.to_account_info(),
+            )?,
+            protocol_position,
+            tick_array_bitmap_extension,
+            liquidity,
+            tick_lower_index,
+            tick_upper_index,
+        )?;
+        require_gte!(available_0, amount_0, ErrorCode::PriceSlippageCheck);
+        require_gte!(available_1, amount_1, ErrorCode::PriceSlippageCheck);
+        tip_amount_0 = get_compound_tip(amount_0, tip_rate);
+        tip_amount_1 = get_compound_tip(amount_1, tip_rate);
+
+        emit!(LiquidityChangeEvent {
+            pool_state: pool_state.key(),
+            tick: pool_state.tick_current,
+            tick_lower: tick_lower_index,
+            tick_upper: tick_upper_index,
+            liquidity_before,
+            liquidity_after: pool_state.liquidity,
+        });
+
+        // The reinvested fees stay in the vaults as liquidity, what is left over stays owed
+        let claimed_0 = amount_0 + tip_amount_0;
+        let claimed_1 = amount_1 + tip_amount_1;
+        require_gte!(
+            pool_state.total_fees_token_0 - pool_state.total_fees_claimed_token_0,
+            claimed_0
+        );
+        require_gte!(
+            pool_state.total_fees_token_1 - pool_state.total_fees_claimed_token_1,
+            claimed_1
+        );
+        personal_position.token_fees_owed_0 -= claimed_0;
+        personal_position.token_fees_owed_1 -= claimed_1;
+        pool_state.total_fees_claimed_token_0 = pool_state
+            .total_fees_claimed_token_0
+            .checked_add(claimed_0)
+            .unwrap();
+        pool_state.total_fees_claimed_token_1 = pool_state
+            .total_fees_claimed_token_1
+            .checked_add(claimed_1)
+            .unwrap();
+
+        // The position joins the range with the fee and reward growth it was just poked to
+        personal_position.fee_growth_inside_0_last_x64 =
+            protocol_position.fee_growth_inside_0_last_x64;
+        personal_position.fee_growth_inside_1_last_x64 =
+            protocol_position.fee_growth_inside_1_last_x64;
+        personal_position.update_rewards(protocol_position.reward_growth_inside, true)?;
+        personal_position.check_reward_extension_updated(current_timestamp)?;
+        personal_position.liquidity = personal_position.liquidity.checked_add(liquidity).unwrap();
+    }
+
+    if let (Some(tip_token_account_0), Some(tip_token_account_1)) =
+        (tip_token_account_0, tip_token_account_1)
+    {
+        transfer_from_pool_vault_to_user(
+            pool_state_loader,
+            &ctx.accounts.token_vault_0,
+            &tip_token_account_0,
+            Some(ctx.accounts.vault_0_mint.clone()),
+            &ctx.accounts.token_program,
+            Some(ctx.accounts.token_program_2022.to_account_info()),
+            tip_amount_0,
+        )?;
+        transfer_from_pool_vault_to_user(
+            pool_state_loader,
+            &ctx.accounts.token_vault_1,
+            &tip_token_account_1,
+            Some(ctx.accounts.vault_1_mint.clone()),
+            &ctx.accounts.token_program,
+            Some(ctx.accounts.token_program_2022.to_account_info()),
+            tip_amount_1,
+        )?;
+    }
+    check_unclaimed_fees_and_vault(
+        pool_state_loader,
+        ctx.accounts.token_vault_0.deref_mut(),
+        ctx.accounts.token_vault_1.deref_mut(),
+    )?;
+
+    emit!(CompoundPositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        liquidity,
+        amount_0,
+        amount_1,
+        tip_amount_0,
+        tip_amount_1,
+    });
+
+    Ok(())
+}
+
+/// The share of a reinvested fee amount paid to the keeper
+pub fn get_compound_tip(fee_amount: u64, tip_rate: u32) -> u64 {
+    (u128::from(fee_amount) * u128::from(tip_rate) / u128::from(FEE_RATE_DENOMINATOR_VALUE)) as u64
+}
+
+#[cfg(test)]
+mod compound_position_test {
+    use super::*;
+
+    #[test]
+    fn get_compound_tip_test() {
+        assert_eq!(get_compound_tip(1_000_000, 0), 0);
+        assert_eq!(get_compound_tip(1_000_000, 10_000), 10_000);
+        assert_eq!(get_compound_tip(99, 10_000), 0);
+        assert_eq!(
+            get_compound_tip(u64::MAX, FEE_RATE_DENOMINATOR_VALUE),
+            u64::MAX
+        );
+        // the tip on a reinvested amount fits in what its sizing left over
+        let fees_owed = 1_234_567;
+        let reinvested = fees_owed - get_compound_tip(fees_owed, 30_000);
+        assert!(reinvested + get_compound_tip(reinvested, 30_000) <= fees_owed);
+    }
+}
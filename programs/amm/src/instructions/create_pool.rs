@@ -107,6 +107,8 @@ pub struct CreatePool<'info> {
     pub system_program: Program<'info, System>,
     /// Sysvar for program account
     pub rent: Sysvar<'info, Rent>,
+    // remaining accounts
+    // hook_program: optional, the program called before and after every swap of the pool
 }
 
 pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u64) -> Result<()> {
@@ -155,6 +157,10 @@ pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u6
         ctx.accounts.token_mint_1.as_ref(),
         ctx.accounts.observation_state.key(),
     )?;
+    if let Some(hook_program) = ctx.remaining_accounts.first() {
+        util::check_swap_hook_program(hook_program)?;
+        pool_state.hook_program = hook_program.key();
+    }
 
     // Initialize the tick array bitmap with the pool ID
     ctx.accounts
@@ -172,6 +178,7 @@ pub fn create_pool(ctx: Context<CreatePool>, sqrt_price_x64: u128, open_time: u6
         tick,
         token_vault_0: ctx.accounts.token_vault_0.key(),
         token_vault_1: ctx.accounts.token_vault_1.key(),
+        hook_program: pool_state.hook_program,
     });
     Ok(())
 }
@@ -470,6 +470,14 @@ pub fn decrease_liquidity_and_update_position<'a, 'b, 'c: 'info, 'info>(
     tick_array_bitmap_extension: Option<&'c AccountInfo<'info>>,
     liquidity: u128,
 ) -> Result<(u64, u64, u64, u64)> {
+    // A locked position keeps its liquidity, and its fees unless the lock allows taking them
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    personal_position.check_unlocked(liquidity, current_timestamp)?;
+    // The rewards of the reward extension must be accrued over the liquidity before it changes
+    if liquidity > 0 {
+        personal_position.check_reward_extension_updated(current_timestamp)?;
+    }
+
     // Load the mutable reference to the pool state
     let mut pool_state = pool_state_loader.load_mut()?;
     let mut decrease_amount_0 = 0;
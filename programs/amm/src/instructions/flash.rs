@@ -0,0 +1,326 @@
+use crate::error::ErrorCode;
+use crate::libraries::{big_num::U128, fixed_point_64, full_math::MulDiv};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+/// The instruction name the callback program must implement, called as
+/// `flash_callback(fee_0: u64, fee_1: u64, data: Vec<u8>)` with the Anchor discriminator
+pub const FLASH_CALLBACK_NAME: &str = "global:flash_callback";
+
+#[derive(Accounts)]
+pub struct Flash<'info> {
+    /// The user borrowing the tokens
+    pub payer: Signer<'info>,
+
+    /// The factory state to read the fee rates, unless the pool overrides them
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The pool to borrow from
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the borrowed amount_0
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the borrowed amount_1
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK: The program called back once the tokens are lent, it must repay the pool vaults
+    #[account(
+        executable,
+        constraint = callback_program.key() != crate::id()
+    )]
+    pub callback_program: UncheckedAccount<'info>,
+    // remaining accounts
+    // passed to the callback program as they are given
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct FlashCallbackArgs {
+    fee_0: u64,
+    fee_1: u64,
+    data: Vec<u8>,
+}
+
+pub fn flash<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, Flash<'info>>,
+    amount_0: u64,
+    amount_1: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+    require!(amount_0 > 0 || amount_1 > 0, ErrorCode::ZeroAmountSpecified);
+    let trade_fee_rate = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Flash) {
+            return err!(ErrorCode::NotApproved);
+        }
+        // the fee is shared by the in range liquidity
+        require_gt!(pool_state.liquidity, 0, ErrorCode::LiquidityInsufficient);
+        pool_state.get_fee_rates(&ctx.accounts.amm_config).0
+    };
+    let fee_0 = get_flash_fee(amount_0, trade_fee_rate)?;
+    let fee_1 = get_flash_fee(amount_1, trade_fee_rate)?;
+
+    let balance_0_before = ctx.accounts.token_vault_0.amount;
+    let balance_1_before = ctx.accounts.token_vault_1.amount;
+
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.recipient_token_account_0,
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        &ctx.accounts.pool_state,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.recipient_token_account_1,
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+
+    // Hand over to the borrower. The runtime does not allow the callback to reenter this program,
+    // so the pool can not be swapped or withdrawn from until the loan is checked below.
+    let mut callback_data = hash(FLASH_CALLBACK_NAME.as_bytes()).to_bytes()[..8].to_vec();
+    callback_data.extend(FlashCallbackArgs { fee_0, fee_1, data }.try_to_vec()?);
+    let callback_ix = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|account_info| AccountMeta {
+                pubkey: account_info.key(),
+                is_signer: account_info.is_signer,
+                is_writable: account_info.is_writable,
+            })
+            .collect(),
+        data: callback_data,
+    };
+    let mut callback_accounts = ctx.remaining_accounts.to_vec();
+    callback_accounts.push(ctx.accounts.callback_program.to_account_info());
+    invoke(&callback_ix, &callback_accounts)?;
+
+    // The vaults must hold at least what they held before plus the fee
+    ctx.accounts.token_vault_0.reload()?;
+    ctx.accounts.token_vault_1.reload()?;
+    let paid_0 = ctx
+        .accounts
+        .token_vault_0
+        .amount
+        .checked_sub(balance_0_before)
+        .ok_or(ErrorCode::FlashLoanNotRepaid)?;
+    let paid_1 = ctx
+        .accounts
+        .token_vault_1
+        .amount
+        .checked_sub(balance_1_before)
+        .ok_or(ErrorCode::FlashLoanNotRepaid)?;
+    require_gte!(paid_0, fee_0, ErrorCode::FlashLoanNotRepaid);
+    require_gte!(paid_1, fee_1, ErrorCode::FlashLoanNotRepaid);
+
+    {
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        if paid_0 > 0 {
+            update_flash_fees(pool_state, &ctx.accounts.amm_config, paid_0, true)?;
+        }
+        if paid_1 > 0 {
+            update_flash_fees(pool_state, &ctx.accounts.amm_config, paid_1, false)?;
+        }
+    }
+
+    emit!(FlashEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        sender: ctx.accounts.payer.key(),
+        amount_0,
+        amount_1,
+        fee_0,
+        fee_1,
+        paid_0,
+        paid_1,
+    });
+
+    Ok(())
+}
+
+/// The fee a flash loan of `amount` is charged at `trade_fee_rate`, rounded up
+pub fn get_flash_fee(amount: u64, trade_fee_rate: u32) -> Result<u64> {
+    Ok(U128::from(amount)
+        .mul_div_ceil(
+            U128::from(trade_fee_rate),
+            U128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+        .ok_or(ErrorCode::CalculateOverflow)?
+        .as_u64())
+}
+
+/// Splits a flash loan fee into the protocol and fund fees like a swap fee, and credits
+/// the rest to the in range liquidity
+pub fn update_flash_fees(
+    pool_state: &mut PoolState,
+    amm_config: &AmmConfig,
+    fee_amount: u64,
+    is_token_0: bool,
+) -> Result<()> {
+    let (_, protocol_fee_rate, fund_fee_rate) = pool_state.get_fee_rates(amm_config);
+    let protocol_fee = U128::from(fee_amount)
+        .checked_mul(protocol_fee_rate.into())
+        .unwrap()
+        .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
+        .unwrap()
+        .as_u64();
+    let fund_fee = U128::from(fee_amount)
+        .checked_mul(fund_fee_rate.into())
+        .unwrap()
+        .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
+        .unwrap()
+        .as_u64();
+    let lp_fee = fee_amount
+        .checked_sub(protocol_fee)
+        .unwrap()
+        .checked_sub(fund_fee)
+        .unwrap();
+    let fee_growth_global_x64_delta = U128::from(lp_fee)
+        .mul_div_floor(
+            U128::from(fixed_point_64::Q64),
+            U128::from(pool_state.liquidity),
+        )
+        .unwrap()
+        .as_u128();
+
+    if is_token_0 {
+        pool_state.fee_growth_global_0_x64 = pool_state
+            .fee_growth_global_0_x64
+            .checked_add(fee_growth_global_x64_delta)
+            .unwrap();
+        pool_state.total_fees_token_0 = pool_state.total_fees_token_0.checked_add(lp_fee).unwrap();
+        pool_state.protocol_fees_token_0 = pool_state
+            .protocol_fees_token_0
+            .checked_add(protocol_fee)
+            .unwrap();
+        pool_state.fund_fees_token_0 = pool_state.fund_fees_token_0.checked_add(fund_fee).unwrap();
+    } else {
+        pool_state.fee_growth_global_1_x64 = pool_state
+            .fee_growth_global_1_x64
+            .checked_add(fee_growth_global_x64_delta)
+            .unwrap();
+        pool_state.total_fees_token_1 = pool_state.total_fees_token_1.checked_add(lp_fee).unwrap();
+        pool_state.protocol_fees_token_1 = pool_state
+            .protocol_fees_token_1
+            .checked_add(protocol_fee)
+            .unwrap();
+        pool_state.fund_fees_token_1 = pool_state.fund_fees_token_1.checked_add(fund_fee).unwrap();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod flash_test {
+    use super::*;
+    use crate::states::pool_test::build_pool;
+    use std::convert::identity;
+
+    #[test]
+    fn get_flash_fee_test() {
+        assert_eq!(get_flash_fee(1_000_000, 2500).unwrap(), 2500);
+        // rounded up
+        assert_eq!(get_flash_fee(1, 2500).unwrap(), 1);
+        assert_eq!(get_flash_fee(0, 2500).unwrap(), 0);
+    }
+
+    #[test]
+    fn update_flash_fees_test() {
+        let pool_state_refcel = build_pool(0, 60, 1 << 64, 1 << 64);
+        let pool_state = &mut pool_state_refcel.borrow_mut();
+        pool_state.fee_growth_global_0_x64 = 0;
+        pool_state.fee_growth_global_1_x64 = 0;
+        let amm_config = AmmConfig {
+            protocol_fee_rate: 120000,
+            fund_fee_rate: 40000,
+            ..Default::default()
+        };
+        update_flash_fees(pool_state, &amm_config, 1_000_000, true).unwrap();
+        assert_eq!(identity(pool_state.protocol_fees_token_0), 120000);
+        assert_eq!(identity(pool_state.fund_fees_token_0), 40000);
+        assert_eq!(identity(pool_state.total_fees_token_0), 840000);
+        // one unit of liquidity per Q64, so the growth equals the lp fee
+        assert_eq!(identity(pool_state.fee_growth_global_0_x64), 840000);
+        assert_eq!(identity(pool_state.fee_growth_global_1_x64), 0);
+        assert_eq!(identity(pool_state.total_fees_token_1), 0);
+    }
+
+    #[test]
+    fn update_flash_fees_with_fee_override_test() {
+        let pool_state_refcel = build_pool(0, 60, 1 << 64, 1 << 64);
+        let pool_state = &mut pool_state_refcel.borrow_mut();
+        pool_state.fee_growth_global_1_x64 = 0;
+        pool_state.fee_override = PoolFeeOverride {
+            enabled: true,
+            trade_fee_rate: 100,
+            protocol_fee_rate: 0,
+            fund_fee_rate: 100000,
+            ..Default::default()
+        };
+        let amm_config = AmmConfig {
+            protocol_fee_rate: 120000,
+            fund_fee_rate: 40000,
+            ..Default::default()
+        };
+        update_flash_fees(pool_state, &amm_config, 1_000_000, false).unwrap();
+        assert_eq!(identity(pool_state.protocol_fees_token_1), 0);
+        assert_eq!(identity(pool_state.fund_fees_token_1), 100000);
+        assert_eq!(identity(pool_state.fee_growth_global_1_x64), 900000);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::ErrorCode;
+use crate::util::transfer_from_user_to_pool_vault;
+use crate::{states::*, util};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(reward_index: u8)]
+pub struct FundReward<'info> {
+    /// Anyone can fund a reward
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    /// The funder's reward token account
+    #[account(
+        mut,
+        token::mint = reward_vault_mint
+    )]
+    pub funder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The deposits into the reward, created with the first one
+    #[account(
+        init_if_needed,
+        seeds = [
+            REWARD_FUNDING_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &[reward_index],
+        ],
+        bump,
+        payer = funder,
+        space = RewardFundingState::LEN
+    )]
+    pub reward_funding: AccountLoader<'info, RewardFundingState>,
+
+    /// The vault of the funded reward
+    #[account(mut)]
+    pub reward_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of reward token vault
+    #[account(address = reward_token_vault.mint)]
+    pub reward_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn fund_reward(ctx: Context<FundReward>, reward_index: u8, amount: u64) -> Result<()> {
+    let index = usize::from(reward_index);
+    if index >= REWARD_NUM {
+        return err!(ErrorCode::InvalidRewardIndex);
+    }
+
+    let current_timestamp = u64::try_from(Clock::get()?.unix_timestamp).unwrap();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    pool_state.update_reward_infos(current_timestamp)?;
+
+    let mut reward_info = pool_state.reward_infos[index];
+    if !reward_info.initialized() {
+        return err!(ErrorCode::UnInitializedRewardInfo);
+    }
+    // The funding is created with the first deposit
+    let mut reward_funding = match ctx.accounts.reward_funding.load_mut() {
+        Ok(reward_funding) => reward_funding,
+        Err(_) => {
+            let mut reward_funding = ctx.accounts.reward_funding.load_init()?;
+            reward_funding.bump = ctx.bumps.reward_funding;
+            reward_funding.pool_id = ctx.accounts.pool_state.key();
+            reward_funding.reward_index = reward_index;
+            reward_funding
+        }
+    };
+    require_keys_eq!(
+        ctx.accounts.reward_token_vault.key(),
+        reward_info.token_vault
+    );
+    // An ended reward is restarted by its authority, extending it would emit retroactively
+    require_gt!(
+        reward_info.end_time,
+        current_timestamp,
+        ErrorCode::InvalidRewardPeriod
+    );
+
+    // The reward lasts longer at the current emission rate for the amount the vault receives
+    let received_amount = amount
+        .checked_sub(util::get_transfer_fee(
+            ctx.accounts.reward_vault_mint.clone(),
+            amount,
+        )?)
+        .unwrap();
+    // Like the extension of a running reward by its authority, a deposit must fund a whole
+    // reward period, so that the few funder slots cannot be taken with dust deposits
+    let extend_period = reward_info.get_funded_period(received_amount);
+    require_gte!(
+        extend_period,
+        reward_period_limit::MIN_REWARD_PERIOD,
+        ErrorCode::InvalidRewardFundAmount
+    );
+    reward_info.end_time = reward_info.end_time.checked_add(extend_period).unwrap();
+
+    // The reward must still end before its next epoch opens. The epochs still in the schedule
+    // open after the pending one, so they are only unknown here while it is empty.
+    let pending_epoch = pool_state.reward_pending_epochs[index];
+    if pending_epoch.initialized() {
+        require_gte!(
+            pending_epoch.open_time,
+            reward_info.end_time,
+            ErrorCode::InvalidRewardPeriod
+        );
+    } else {
+        require_eq!(
+            pool_state.reward_scheduled_amounts[index],
+            0,
+            ErrorCode::RewardScheduleNotAdvanced
+        );
+    }
+    pool_state.reward_infos[index] = reward_info;
+    // Refunded to the funder as far as it is not emitted when the reward is cancelled
+    reward_funding.deposit(
+        reward_info.open_time,
+        ctx.accounts.funder.key(),
+        received_amount,
+    )?;
+
+    emit!(RewardFundedEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        reward_index,
+        funder: ctx.accounts.funder.key(),
+        amount: received_amount,
+        end_time: reward_info.end_time,
+    });
+
+    transfer_from_user_to_pool_vault(
+        &ctx.accounts.funder,
+        &ctx.accounts.funder_token_account,
+        &ctx.accounts.reward_token_vault,
+        Some(ctx.accounts.reward_vault_mint.clone()),
+        &ctx.accounts.reward_token_program.to_account_info(),
+        Some(ctx.accounts.reward_token_program.to_account_info()),
+        amount,
+    )
+}
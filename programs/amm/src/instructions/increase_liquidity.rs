@@ -339,6 +339,8 @@ pub fn increase_liquidity<'a, 'b, 'c: 'info, 'info>(
     personal_position.update_rewards(protocol_position.reward_growth_inside, true)?;
 
     // Increase the user's liquidity
+    // The rewards of the reward extension must be accrued over the liquidity before it changes
+    personal_position.check_reward_extension_updated(Clock::get()?.unix_timestamp as u64)?;
     personal_position.liquidity = personal_position.liquidity.checked_add(liquidity).unwrap();
 
     // Emit an event with the details of the liquidity increase
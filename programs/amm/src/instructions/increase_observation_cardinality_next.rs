@@ -0,0 +1,78 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct IncreaseObservationCardinalityNext<'info> {
+    /// Pays the rent of the grown observation account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The pool whose oracle is grown
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle observations of the pool
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// To transfer the rent
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows the observation account to hold `observation_cardinality_next` observations.
+/// The oracle starts using them once its index wraps around, nothing is done if the
+/// account can already hold as many.
+pub fn increase_observation_cardinality_next(
+    ctx: Context<IncreaseObservationCardinalityNext>,
+    observation_cardinality_next: u16,
+) -> Result<()> {
+    let observation_cardinality_next_old = {
+        let (observation_state, observation_extension) =
+            load_observations_mut(ctx.accounts.observation_state.as_ref())?;
+        observation_state.cardinality_next(&observation_extension) as u16
+    };
+    if observation_cardinality_next <= observation_cardinality_next_old {
+        return Ok(());
+    }
+
+    let observation_info = ctx.accounts.observation_state.to_account_info();
+    let account_len = ObservationState::account_len(observation_cardinality_next)
+        .max(observation_info.data_len());
+    require_gte!(
+        MAX_PERMITTED_DATA_INCREASE,
+        account_len - observation_info.data_len(),
+        ErrorCode::ObservationCardinalityIncreaseTooLarge
+    );
+
+    let required_lamports = Rent::get()?
+        .minimum_balance(account_len)
+        .saturating_sub(observation_info.lamports());
+    if required_lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: observation_info.clone(),
+                },
+            ),
+            required_lamports,
+        )?;
+    }
+    // The new observations are zeroed, i.e. marked as not written yet
+    observation_info.realloc(account_len, true)?;
+
+    ctx.accounts
+        .observation_state
+        .load_mut()?
+        .observation_cardinality_next = observation_cardinality_next;
+
+    emit!(IncreaseObservationCardinalityNextEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        observation_cardinality_next_old,
+        observation_cardinality_next_new: observation_cardinality_next,
+    });
+    Ok(())
+}
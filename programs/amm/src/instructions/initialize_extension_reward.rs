@@ -0,0 +1,168 @@
+use super::InitializeRewardParam;
+use crate::error::ErrorCode;
+use crate::libraries::{fixed_point_64, full_math::MulDiv, U256};
+use crate::util::transfer_from_user_to_pool_vault;
+use crate::{states::*, util};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct InitializeExtensionReward<'info> {
+    /// The founder deposit reward token to vault
+    #[account(mut)]
+    pub reward_funder: Signer<'info>,
+
+    // The funder's reward token account
+    #[account(
+        mut,
+        token::mint = reward_token_mint
+    )]
+    pub funder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Set reward for this pool
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The reward extension of the pool, created with its first reward
+    #[account(
+        init_if_needed,
+        seeds = [
+            REWARD_EXTENSION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = reward_funder,
+        space = RewardExtension::LEN
+    )]
+    pub reward_extension: Box<Account<'info, RewardExtension>>,
+
+    /// load info from the account to judge reward permission
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: AccountLoader<'info, OperationState>,
+
+    /// Reward mint
+    pub reward_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// A pda, reward vault
+    #[account(
+        init,
+        seeds =[
+            POOL_REWARD_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            reward_token_mint.key().as_ref(),
+        ],
+        bump,
+        payer = reward_funder,
+        token::mint = reward_token_mint,
+        token::authority = pool_state,
+        token::token_program = reward_token_program,
+    )]
+    pub reward_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn initialize_extension_reward(
+    ctx: Context<InitializeExtensionReward>,
+    param: InitializeRewardParam,
+) -> Result<()> {
+    // Check if the reward token mint is supported
+    if !util::is_supported_mint(&ctx.accounts.reward_token_mint).unwrap() {
+        return err!(ErrorCode::NotSupportMint);
+    }
+
+    // Ensure the reward funder is authorized
+    {
+        let operation_state = ctx.accounts.operation_state.load()?;
+        require!(
+            ctx.accounts.reward_funder.key() == crate::admin::id()
+                || ctx.accounts.reward_funder.key() == ctx.accounts.pool_state.load()?.owner
+                || operation_state.validate_operation_owner(ctx.accounts.reward_funder.key()),
+            ErrorCode::NotApproved
+        );
+    }
+
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    param.check(current_timestamp)?;
+
+    let reward_amount = U256::from(param.end_time - param.open_time)
+        .mul_div_ceil(
+            U256::from(param.emissions_per_second_x64),
+            U256::from(fixed_point_64::Q64),
+        )
+        .unwrap()
+        .as_u64();
+    let reward_amount_with_transfer_fee = reward_amount
+        .checked_add(
+            util::get_transfer_inverse_fee(ctx.accounts.reward_token_mint.clone(), reward_amount)
+                .unwrap(),
+        )
+        .unwrap();
+    require_gte!(
+        ctx.accounts.funder_token_account.amount,
+        reward_amount_with_transfer_fee
+    );
+
+    let reward_token_mint = ctx.accounts.reward_token_mint.key();
+    let reward_extension = &mut ctx.accounts.reward_extension;
+    let index = {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        // The reward mints of the pool and of its extension must all differ
+        require!(
+            pool_state
+                .reward_infos
+                .iter()
+                .all(|reward_info| reward_info.token_mint != reward_token_mint)
+                && reward_extension
+                    .reward_infos
+                    .iter()
+                    .all(|reward_info| reward_info.token_mint != reward_token_mint),
+            ErrorCode::RewardTokenAlreadyInUse
+        );
+        let index = reward_extension
+            .reward_infos
+            .iter()
+            .position(|reward_info| !reward_info.initialized())
+            .ok_or(ErrorCode::FullRewardInfo)?;
+
+        // Bring the running rewards up to date before the new one starts
+        pool_state.update_reward_infos(current_timestamp)?;
+        pool_state.extension_reward_growths[index] = ExtensionRewardGrowth {
+            open_time: param.open_time,
+            end_time: param.end_time,
+            last_update_time: param.open_time,
+            emissions_per_second_x64: param.emissions_per_second_x64,
+            reward_total_emissioned: 0,
+            reward_growth_global_x64: 0,
+        };
+        index
+    };
+
+    reward_extension.bump = ctx.bumps.reward_extension;
+    reward_extension.pool_id = ctx.accounts.pool_state.key();
+    reward_extension.reward_infos[index] = ExtensionRewardInfo {
+        token_mint: reward_token_mint,
+        token_vault: ctx.accounts.reward_token_vault.key(),
+        authority: ctx.accounts.reward_funder.key(),
+        reward_claimed: 0,
+    };
+
+    transfer_from_user_to_pool_vault(
+        &ctx.accounts.reward_funder,
+        &ctx.accounts.funder_token_account,
+        &ctx.accounts.reward_token_vault,
+        Some(ctx.accounts.reward_token_mint.clone()),
+        &ctx.accounts.reward_token_program.to_account_info(),
+        Some(ctx.accounts.reward_token_program.to_account_info()),
+        reward_amount_with_transfer_fee,
+    )?;
+
+    Ok(())
+}
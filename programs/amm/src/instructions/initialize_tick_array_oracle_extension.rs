@@ -0,0 +1,86 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializeTickArrayOracleExtension<'info> {
+    /// The pool owner, admin or an operation owner, pays to create the tick array oracle extension
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// load info from the account to judge permission
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: AccountLoader<'info, OperationState>,
+
+    /// The program account of the pool observations, to read the tick cumulative from
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The tick array to track the tick cumulatives and seconds of
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// The tick cumulatives and seconds outside the ticks of the tick array
+    #[account(
+        init,
+        seeds = [
+            TICK_ARRAY_ORACLE_EXTENSION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array.load()?.start_tick_index.to_be_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = TickArrayOracleExtension::LEN
+    )]
+    pub tick_array_oracle_extension: AccountLoader<'info, TickArrayOracleExtension>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_tick_array_oracle_extension(
+    ctx: Context<InitializeTickArrayOracleExtension>,
+) -> Result<()> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    // Swaps through the tick array need the extension from now on
+    require!(
+        ctx.accounts.payer.key() == crate::admin::id()
+            || ctx.accounts.payer.key() == pool_state.owner
+            || ctx
+                .accounts
+                .operation_state
+                .load()?
+                .validate_operation_owner(ctx.accounts.payer.key()),
+        ErrorCode::NotApproved
+    );
+
+    let block_timestamp = oracle::block_timestamp();
+    let (observation_state, observation_extension) =
+        load_observations(ctx.accounts.observation_state.as_ref())?;
+    let tick_cumulative = get_tick_cumulative(
+        &pool_state,
+        &observation_state,
+        &observation_extension,
+        block_timestamp,
+    )?;
+
+    let mut tick_array = ctx.accounts.tick_array.load_mut()?;
+    ctx.accounts
+        .tick_array_oracle_extension
+        .load_init()?
+        .initialize(
+            &tick_array,
+            pool_state.tick_current,
+            tick_cumulative,
+            block_timestamp,
+        );
+    tick_array.has_oracle_extension = true;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitializeTickArrayRewardExtension<'info> {
+    /// The pool owner, admin or an operation owner, pays to create the tick array reward extension
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// load info from the account to judge permission
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: AccountLoader<'info, OperationState>,
+
+    /// The tick array to track the extension rewards of
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// The reward growths outside the ticks of the tick array
+    #[account(
+        init,
+        seeds = [
+            TICK_ARRAY_REWARD_EXTENSION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array.load()?.start_tick_index.to_be_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = TickArrayRewardExtension::LEN
+    )]
+    pub tick_array_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_tick_array_reward_extension(
+    ctx: Context<InitializeTickArrayRewardExtension>,
+) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    require!(
+        ctx.accounts.payer.key() == crate::admin::id()
+            || ctx.accounts.payer.key() == pool_state.owner
+            || ctx
+                .accounts
+                .operation_state
+                .load()?
+                .validate_operation_owner(ctx.accounts.payer.key()),
+        ErrorCode::NotApproved
+    );
+    // Swaps through the tick array need the extension from now on, so only once there is a reward
+    require!(
+        pool_state
+            .extension_reward_growths
+            .iter()
+            .any(|reward_growth| reward_growth.initialized()),
+        ErrorCode::UnInitializedRewardInfo
+    );
+    pool_state.update_reward_infos(Clock::get()?.unix_timestamp as u64)?;
+
+    let mut tick_array = ctx.accounts.tick_array.load_mut()?;
+    ctx.accounts
+        .tick_array_reward_extension
+        .load_init()?
+        .initialize(
+            &tick_array,
+            pool_state.tick_current,
+            &pool_state.extension_reward_growths,
+        );
+    tick_array.has_reward_extension = true;
+
+    Ok(())
+}
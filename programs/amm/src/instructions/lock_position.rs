@@ -0,0 +1,89 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct LockPosition<'info> {
+    /// The position owner
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1 && nft_account.owner == nft_owner.key() @ ErrorCode::NotApproved,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The position to lock
+    #[account(mut)]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// SPL program of the position nft
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn lock_position(
+    ctx: Context<LockPosition>,
+    unlock_timestamp: u64,
+    allow_collect_fee: bool,
+) -> Result<()> {
+    let personal_position = &mut ctx.accounts.personal_position;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    // A running lock can only be extended, with its fee collection flag kept
+    require!(
+        !personal_position.is_locked(current_timestamp),
+        ErrorCode::PositionLocked
+    );
+    require_gt!(
+        unlock_timestamp,
+        current_timestamp,
+        ErrorCode::InvalidUnlockTimestamp
+    );
+    check_lock_duration(unlock_timestamp, current_timestamp)?;
+    personal_position.unlock_timestamp = unlock_timestamp;
+    personal_position.lock_allow_collect_fee = allow_collect_fee;
+
+    emit!(LockPositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        unlock_timestamp,
+        allow_collect_fee,
+    });
+    Ok(())
+}
+
+pub fn extend_lock(ctx: Context<LockPosition>, unlock_timestamp: u64) -> Result<()> {
+    let personal_position = &mut ctx.accounts.personal_position;
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    require!(
+        personal_position.is_locked(current_timestamp),
+        ErrorCode::NotApproved
+    );
+    require_gt!(
+        unlock_timestamp,
+        personal_position.unlock_timestamp,
+        ErrorCode::InvalidUnlockTimestamp
+    );
+    check_lock_duration(unlock_timestamp, current_timestamp)?;
+    personal_position.unlock_timestamp = unlock_timestamp;
+
+    emit!(LockPositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        unlock_timestamp,
+        allow_collect_fee: personal_position.lock_allow_collect_fee,
+    });
+    Ok(())
+}
+
+/// Checks a lock does not run further than `MAX_LOCK_DURATION` from now, so that a mistyped
+/// unlock time, e.g. in milliseconds, cannot lock the position forever
+fn check_lock_duration(unlock_timestamp: u64, current_timestamp: u64) -> Result<()> {
+    require_gte!(
+        current_timestamp + MAX_LOCK_DURATION,
+        unlock_timestamp,
+        ErrorCode::InvalidUnlockTimestamp
+    );
+    Ok(())
+}
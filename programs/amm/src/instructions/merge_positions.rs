@@ -0,0 +1,151 @@
+use super::{burn_liquidity, settle_personal_position};
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct MergePositions<'info> {
+    /// The owner of all the merged positions
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the position the others are merged into
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The position the others are merged into
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// SPL program of the position nft
+    pub token_program: Program<'info, Token>,
+    // remaining accounts
+    // for every merged position: nft_account, personal_position
+}
+
+pub fn merge_positions<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, MergePositions<'info>>,
+) -> Result<()> {
+    let remaining_accounts = ctx.remaining_accounts;
+    require!(
+        !remaining_accounts.is_empty() && remaining_accounts.chunks_exact(2).remainder().is_empty(),
+        ErrorCode::InvalidMergePosition
+    );
+    let personal_position = &mut ctx.accounts.personal_position;
+    let protocol_position = &mut ctx.accounts.protocol_position;
+
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    // The rewards of the reward extension must be accrued over the liquidity before it changes
+    personal_position.check_reward_extension_updated(current_timestamp)?;
+    // Load the merged positions and check they all belong to the signer and share the range
+    let mut merged_positions: Vec<Account<'info, PersonalPositionState>> = Vec::new();
+    for accounts in remaining_accounts.chunks(2) {
+        let nft_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[0])?;
+        let merged_position = Account::<PersonalPositionState>::try_from(&accounts[1])?;
+        require!(
+            accounts[1].is_writable
+                && merged_position.key() != personal_position.key()
+                && merged_positions
+                    .iter()
+                    .all(|position| position.key() != merged_position.key())
+                && merged_position.pool_id == personal_position.pool_id
+                && merged_position.tick_lower_index == personal_position.tick_lower_index
+                && merged_position.tick_upper_index == personal_position.tick_upper_index
+                && *accounts[0].owner == ctx.accounts.token_program.key()
+                && nft_account.mint == merged_position.nft_mint
+                && nft_account.amount == 1
+                && nft_account.owner == ctx.accounts.nft_owner.key(),
+            ErrorCode::InvalidMergePosition
+        );
+        // Merging would release the liquidity of a locked position
+        require!(
+            !merged_position.is_locked(current_timestamp),
+            ErrorCode::PositionLocked
+        );
+        merged_position.check_reward_extension_updated(current_timestamp)?;
+        merged_positions.push(merged_position);
+    }
+
+    // Poke the range once, then settle the fees and rewards of every position against it
+    if protocol_position.liquidity > 0 {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        burn_liquidity(
+            &mut pool_state,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+            protocol_position,
+            None,
+            0,
+        )?;
+    }
+    settle_personal_position(personal_position, protocol_position)?;
+
+    let mut merged_position_nft_mints = Vec::with_capacity(merged_positions.len());
+    for merged_position in merged_positions.iter_mut() {
+        settle_personal_position(merged_position, protocol_position)?;
+
+        personal_position.liquidity = personal_position
+            .liquidity
+            .checked_add(merged_position.liquidity)
+            .unwrap();
+        personal_position.token_fees_owed_0 = personal_position
+            .token_fees_owed_0
+            .checked_add(merged_position.token_fees_owed_0)
+            .unwrap();
+        personal_position.token_fees_owed_1 = personal_position
+            .token_fees_owed_1
+            .checked_add(merged_position.token_fees_owed_1)
+            .unwrap();
+        for i in 0..REWARD_NUM {
+            personal_position.reward_infos[i].reward_amount_owed = personal_position.reward_infos
+                [i]
+                .reward_amount_owed
+                .checked_add(merged_position.reward_infos[i].reward_amount_owed)
+                .unwrap();
+            merged_position.reward_infos[i].reward_amount_owed = 0;
+        }
+
+        // The emptied position is left for its owner to close
+        merged_position.liquidity = 0;
+        merged_position.token_fees_owed_0 = 0;
+        merged_position.token_fees_owed_1 = 0;
+        merged_position.exit(&crate::id())?;
+        merged_position_nft_mints.push(merged_position.nft_mint);
+    }
+
+    emit!(MergePositionsEvent {
+        position_nft_mint: personal_position.nft_mint,
+        merged_position_nft_mints,
+        liquidity: personal_position.liquidity,
+    });
+
+    Ok(())
+}
@@ -13,6 +13,54 @@ pub use increase_liquidity::*;
 pub mod decrease_liquidity;
 pub use decrease_liquidity::*;
 
+pub mod collect;
+pub use collect::*;
+
+pub mod update_position_boost;
+pub use update_position_boost::*;
+
+pub mod compound_position;
+pub use compound_position::*;
+
+pub mod set_compound_keeper;
+pub use set_compound_keeper::*;
+
+pub mod rebalance_position;
+pub use rebalance_position::*;
+
+pub mod zap_in;
+pub use zap_in::*;
+
+pub mod zap_out;
+pub use zap_out::*;
+
+pub mod merge_positions;
+pub use merge_positions::*;
+
+pub mod split_position;
+pub use split_position::*;
+
+pub mod lock_position;
+pub use lock_position::*;
+
+pub mod position_reward_extension;
+pub use position_reward_extension::*;
+
+pub mod collect_extension_rewards;
+pub use collect_extension_rewards::*;
+
+pub mod collect_remaining_extension_rewards;
+pub use collect_remaining_extension_rewards::*;
+
+pub mod place_limit_order;
+pub use place_limit_order::*;
+
+pub mod cancel_limit_order;
+pub use cancel_limit_order::*;
+
+pub mod claim_limit_order;
+pub use claim_limit_order::*;
+
 pub mod swap;
 pub use swap::*;
 
@@ -22,15 +70,51 @@ pub use swap_v2::*;
 pub mod swap_router_base_in;
 pub use swap_router_base_in::*;
 
+pub mod swap_router_base_out;
+pub use swap_router_base_out::*;
+
+pub mod quote_swap;
+pub use quote_swap::*;
+
+pub mod flash;
+pub use flash::*;
+
+pub mod observe;
+pub use observe::*;
+
+pub mod increase_observation_cardinality_next;
+pub use increase_observation_cardinality_next::*;
+
+pub mod snapshot_cumulatives_inside;
+pub use snapshot_cumulatives_inside::*;
+
 pub mod update_reward_info;
 pub use update_reward_info::*;
 
 pub mod initialize_reward;
 pub use initialize_reward::*;
 
+pub mod initialize_extension_reward;
+pub use initialize_extension_reward::*;
+
+pub mod initialize_tick_array_reward_extension;
+pub use initialize_tick_array_reward_extension::*;
+
+pub mod initialize_tick_array_oracle_extension;
+pub use initialize_tick_array_oracle_extension::*;
+
 pub mod set_reward_params;
 pub use set_reward_params::*;
 
+pub mod reward_schedule;
+pub use reward_schedule::*;
+
+pub mod fund_reward;
+pub use fund_reward::*;
+
+pub mod cancel_reward;
+pub use cancel_reward::*;
+
 pub mod collect_remaining_rewards;
 pub use collect_remaining_rewards::*;
 
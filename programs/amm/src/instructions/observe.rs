@@ -0,0 +1,74 @@
+use crate::libraries::tick_math;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct Observe<'info> {
+    /// The pool whose oracle is read
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle observations of the pool
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+/// Result of `observe`, set as return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ObserveResult {
+    /// The tick cumulative as of each `seconds_ago`
+    pub tick_cumulatives: Vec<i64>,
+    /// The seconds per unit of in range liquidity cumulative as of each `seconds_ago`, as a Q64.64
+    pub seconds_per_liquidity_cumulatives_x64: Vec<u128>,
+    /// The time weighted average tick from each `seconds_ago` to now, the current tick for zero
+    pub twap_ticks: Vec<i32>,
+    /// The sqrt price at each time weighted average tick, as a Q64.64
+    pub twap_sqrt_prices_x64: Vec<u128>,
+}
+
+pub fn observe(ctx: Context<Observe>, seconds_agos: Vec<u32>) -> Result<ObserveResult> {
+    let block_timestamp = oracle::block_timestamp();
+    let (tick_current, seconds_per_liquidity_global_x64) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        (
+            pool_state.tick_current,
+            pool_state.get_seconds_per_liquidity_global(u64::from(block_timestamp)),
+        )
+    };
+    let (observation_state, observation_extension) =
+        load_observations(ctx.accounts.observation_state.as_ref())?;
+
+    let cumulatives = observation_state.observe(
+        &observation_extension,
+        block_timestamp,
+        tick_current,
+        seconds_per_liquidity_global_x64,
+        &seconds_agos,
+    )?;
+    let (tick_cumulative_now, _) = observation_state.observe_single(
+        &observation_extension,
+        block_timestamp,
+        block_timestamp,
+        tick_current,
+        seconds_per_liquidity_global_x64,
+    )?;
+
+    let mut result = ObserveResult::default();
+    for (seconds_ago, (tick_cumulative, seconds_per_liquidity_cumulative_x64)) in
+        seconds_agos.iter().zip(cumulatives)
+    {
+        let twap_tick = if *seconds_ago == 0 {
+            tick_current
+        } else {
+            get_twap_tick(tick_cumulative, tick_cumulative_now, *seconds_ago)
+        };
+        result.tick_cumulatives.push(tick_cumulative);
+        result
+            .seconds_per_liquidity_cumulatives_x64
+            .push(seconds_per_liquidity_cumulative_x64);
+        result.twap_ticks.push(twap_tick);
+        result
+            .twap_sqrt_prices_x64
+            .push(tick_math::get_sqrt_price_at_tick(twap_tick)?);
+    }
+    Ok(result)
+}
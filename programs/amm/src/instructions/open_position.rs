@@ -683,81 +683,18 @@ pub fn add_liquidity<'b, 'c: 'info, 'info>(
     // Store the pool's liquidity before the operation
     let liquidity_before = pool_state.liquidity;
 
-    // Ensure the tick arrays are associated with the correct pool
-    require_keys_eq!(tick_array_lower_loader.load()?.pool_id, pool_state.key());
-    require_keys_eq!(tick_array_upper_loader.load()?.pool_id, pool_state.key());
-
-    // get tick_state
-    // Load and get mutable references to the tick states for the lower and upper ticks
-    let mut tick_lower_state = *tick_array_lower_loader
-        .load_mut()?
-        .get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
-    let mut tick_upper_state = *tick_array_upper_loader
-        .load_mut()?
-        .get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
-
-    // Initialize tick states if they are not already set
-    if tick_lower_state.tick == 0 {
-        tick_lower_state.tick = tick_lower_index;
-    }
-    if tick_upper_state.tick == 0 {
-        tick_upper_state.tick = tick_upper_index;
-    }
-
-    // Get the current timestamp
-    let clock = Clock::get()?;
-
-    // Modify the position and get the amounts and flip status of the ticks
-    let (amount_0, amount_1, flip_tick_lower, flip_tick_upper) = modify_position(
-        i128::try_from(*liquidity).unwrap(),
+    // Add the liquidity to the position and its ticks
+    let (amount_0, amount_1) = mint_liquidity(
         pool_state,
+        tick_array_lower_loader,
+        tick_array_upper_loader,
         protocol_position,
-        &mut tick_lower_state,
-        &mut tick_upper_state,
-        clock.unix_timestamp as u64,
-    )?;
-
-    // update tick_state
-    // Update the tick states in the tick array loaders
-    tick_array_lower_loader.load_mut()?.update_tick_state(
+        tick_array_bitmap_extension,
+        *liquidity,
         tick_lower_index,
-        pool_state.tick_spacing,
-        tick_lower_state,
-    )?;
-    tick_array_upper_loader.load_mut()?.update_tick_state(
         tick_upper_index,
-        pool_state.tick_spacing,
-        tick_upper_state,
     )?;
 
-    // Handle flipping of the lower tick
-    if flip_tick_lower {
-        let mut tick_array_lower = tick_array_lower_loader.load_mut()?;
-        let before_init_tick_count = tick_array_lower.initialized_tick_count;
-        tick_array_lower.update_initialized_tick_count(true)?;
-
-        if before_init_tick_count == 0 {
-            pool_state.flip_tick_array_bit(
-                tick_array_bitmap_extension,
-                tick_array_lower.start_tick_index,
-            )?;
-        }
-    }
-
-    // Handle flipping of the upper tick
-    if flip_tick_upper {
-        let mut tick_array_upper = tick_array_upper_loader.load_mut()?;
-        let before_init_tick_count = tick_array_upper.initialized_tick_count;
-        tick_array_upper.update_initialized_tick_count(true)?;
-
-        if before_init_tick_count == 0 {
-            pool_state.flip_tick_array_bit(
-                tick_array_bitmap_extension,
-                tick_array_upper.start_tick_index,
-            )?;
-        }
-    }
-
     // Ensure that at least one of the amounts is greater than zero
     require!(
         amount_0 > 0 || amount_1 > 0,
@@ -857,6 +794,96 @@ pub fn add_liquidity<'b, 'c: 'info, 'info>(
     ))
 }
 
+/// Adds liquidity to a protocol position, initializing its ticks and flipping the tick array
+/// bits if needed. Returns the amounts of token_0 and token_1 owed to the pool, no token is moved.
+pub fn mint_liquidity<'b, 'c: 'info, 'info>(
+    pool_state: &mut RefMut<PoolState>,
+    tick_array_lower_loader: &'b AccountLoad<'info, TickArrayState>,
+    tick_array_upper_loader: &'b AccountLoad<'info, TickArrayState>,
+    protocol_position: &mut ProtocolPositionState,
+    tick_array_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    liquidity: u128,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+) -> Result<(u64, u64)> {
+    // Ensure the tick arrays are associated with the correct pool
+    require_keys_eq!(tick_array_lower_loader.load()?.pool_id, pool_state.key());
+    require_keys_eq!(tick_array_upper_loader.load()?.pool_id, pool_state.key());
+
+    // get tick_state
+    // Load and get mutable references to the tick states for the lower and upper ticks
+    let mut tick_lower_state = *tick_array_lower_loader
+        .load_mut()?
+        .get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
+    let mut tick_upper_state = *tick_array_upper_loader
+        .load_mut()?
+        .get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
+
+    // Initialize tick states if they are not already set
+    if tick_lower_state.tick == 0 {
+        tick_lower_state.tick = tick_lower_index;
+    }
+    if tick_upper_state.tick == 0 {
+        tick_upper_state.tick = tick_upper_index;
+    }
+
+    // Get the current timestamp
+    let clock = Clock::get()?;
+
+    // Modify the position and get the amounts and flip status of the ticks
+    let (amount_0, amount_1, flip_tick_lower, flip_tick_upper) = modify_position(
+        i128::try_from(liquidity).unwrap(),
+        pool_state,
+        protocol_position,
+        &mut tick_lower_state,
+        &mut tick_upper_state,
+        clock.unix_timestamp as u64,
+    )?;
+
+    // update tick_state
+    // Update the tick states in the tick array loaders
+    tick_array_lower_loader.load_mut()?.update_tick_state(
+        tick_lower_index,
+        pool_state.tick_spacing,
+        tick_lower_state,
+    )?;
+    tick_array_upper_loader.load_mut()?.update_tick_state(
+        tick_upper_index,
+        pool_state.tick_spacing,
+        tick_upper_state,
+    )?;
+
+    // Handle flipping of the lower tick
+    if flip_tick_lower {
+        let mut tick_array_lower = tick_array_lower_loader.load_mut()?;
+        let before_init_tick_count = tick_array_lower.initialized_tick_count;
+        tick_array_lower.update_initialized_tick_count(true)?;
+
+        if before_init_tick_count == 0 {
+            pool_state.flip_tick_array_bit(
+                tick_array_bitmap_extension,
+                tick_array_lower.start_tick_index,
+            )?;
+        }
+    }
+
+    // Handle flipping of the upper tick
+    if flip_tick_upper {
+        let mut tick_array_upper = tick_array_upper_loader.load_mut()?;
+        let before_init_tick_count = tick_array_upper.initialized_tick_count;
+        tick_array_upper.update_initialized_tick_count(true)?;
+
+        if before_init_tick_count == 0 {
+            pool_state.flip_tick_array_bit(
+                tick_array_bitmap_extension,
+                tick_array_upper.start_tick_index,
+            )?;
+        }
+    }
+
+    Ok((amount_0, amount_1))
+}
+
 pub fn modify_position(
     liquidity_delta: i128,
     pool_state: &mut RefMut<PoolState>,
@@ -926,6 +953,7 @@ pub fn update_position(
             liquidity_delta,
             pool_state.fee_growth_global_0_x64,
             pool_state.fee_growth_global_1_x64,
+            pool_state.seconds_per_liquidity_global_x64,
             false,
             &updated_reward_infos,
         )?;
@@ -935,6 +963,7 @@ pub fn update_position(
             liquidity_delta,
             pool_state.fee_growth_global_0_x64,
             pool_state.fee_growth_global_1_x64,
+            pool_state.seconds_per_liquidity_global_x64,
             true,
             &updated_reward_infos,
         )?;
@@ -963,6 +992,24 @@ pub fn update_position(
         &updated_reward_infos,
     );
 
+    // Reprice the boosted liquidity of the position at the current boost, even when its
+    // liquidity is unchanged so that a poke applies a new boost to an existing position
+    let boost_rate = pool_state
+        .reward_boost
+        .get_boost_rate(tick_lower_state.tick, tick_upper_state.tick);
+    let boosted_liquidity_delta =
+        protocol_position_state.get_boosted_liquidity_delta(liquidity_delta, boost_rate)?;
+    if boosted_liquidity_delta != 0 {
+        tick_lower_state.update_boosted_liquidity_net(boosted_liquidity_delta, false);
+        tick_upper_state.update_boosted_liquidity_net(boosted_liquidity_delta, true);
+        if pool_state.tick_current >= tick_lower_state.tick
+            && pool_state.tick_current < tick_upper_state.tick
+        {
+            pool_state.boosted_liquidity =
+                liquidity_math::add_delta(pool_state.boosted_liquidity, boosted_liquidity_delta)?;
+        }
+    }
+
     // Update the protocol position state with the new values
     protocol_position_state.update(
         tick_lower_state.tick,
@@ -971,6 +1018,8 @@ pub fn update_position(
         fee_growth_inside_0_x64,
         fee_growth_inside_1_x64,
         reward_growths_inside,
+        pool_state.reward_boost.boosted_reward_flags,
+        boost_rate,
     )?;
 
     // Clear the tick states if liquidity is decreased and the ticks are flipped
@@ -990,7 +1039,7 @@ pub fn update_position(
 const METADATA_URI: &str =
     "https://cloudflare-ipfs.com/ipfs/QmbzJafuKY3B4t25eq9zdKZMgXiMeW4jHLzf6KE6ZmHWn1/02.json";
 
-fn create_nft_with_metadata<'info>(
+pub fn create_nft_with_metadata<'info>(
     payer: &Signer<'info>,
     pool_state_loader: &AccountLoader<'info, PoolState>,
     position_nft_mint: &Box<InterfaceAccount<'info, Mint>>,
@@ -0,0 +1,296 @@
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[derive(Accounts)]
+#[instruction(tick_lower_index: i32)]
+pub struct PlaceLimitOrder<'info> {
+    /// The owner of the order, pays for the order account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Add the order liquidity to this pool
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The order account, added to when the owner places more at the same range
+    #[account(
+        init_if_needed,
+        seeds = [
+            LIMIT_ORDER_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+        ],
+        bump,
+        payer = owner,
+        space = LimitOrderState::LEN
+    )]
+    pub limit_order: Box<Account<'info, LimitOrderState>>,
+
+    /// Stores the lower and upper tick of the order range
+    #[account(mut, constraint = tick_array.load()?.pool_id == pool_state.key())]
+    pub tick_array: AccountLoader<'info, TickArrayState>,
+
+    /// The token account paying the order input
+    #[account(
+        mut,
+        token::mint = input_vault.mint
+    )]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault of the token sold by the order
+    #[account(mut)]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the input vault
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// Program to create the order account
+    pub system_program: Program<'info, System>,
+    // remaining account
+    // #[account(
+    //     seeds = [
+    //         POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
+    //         pool_state.key().as_ref(),
+    //     ],
+    //     bump
+    // )]
+    // pub tick_array_bitmap: AccountLoader<'info, TickArrayBitmapExtension>,
+}
+
+pub fn place_limit_order<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, PlaceLimitOrder<'info>>,
+    tick_lower_index: i32,
+    zero_for_one: bool,
+    amount_in: u64,
+) -> Result<()> {
+    let limit_order = &mut ctx.accounts.limit_order;
+    let (liquidity, amount) = {
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
+            return err!(ErrorCode::NotApproved);
+        }
+        // The order input goes to the vault of the token it sells
+        require_keys_eq!(
+            ctx.accounts.input_vault.key(),
+            if zero_for_one {
+                pool_state.token_vault_0
+            } else {
+                pool_state.token_vault_1
+            }
+        );
+        check_limit_order_range(
+            tick_lower_index,
+            pool_state.tick_spacing,
+            pool_state.tick_current,
+            zero_for_one,
+        )?;
+        let tick_upper_index = tick_lower_index + i32::from(pool_state.tick_spacing);
+
+        // The order liquidity is computed from the amount received by the vault
+        let transfer_fee = get_transfer_fee(ctx.accounts.input_vault_mint.clone(), amount_in)?;
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower_index)?;
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper_index)?;
+        let liquidity = if zero_for_one {
+            liquidity_math::get_liquidity_from_amount_0(
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in.checked_sub(transfer_fee).unwrap(),
+            )
+        } else {
+            liquidity_math::get_liquidity_from_amount_1(
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in.checked_sub(transfer_fee).unwrap(),
+            )
+        };
+        require_gt!(liquidity, 0, ErrorCode::InvaildLiquidity);
+
+        let tick_array = &mut ctx.accounts.tick_array.load_mut()?;
+        let mut tick_lower_state =
+            *tick_array.get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?;
+        let mut tick_upper_state =
+            *tick_array.get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?;
+        if tick_lower_state.tick == 0 {
+            tick_lower_state.tick = tick_lower_index;
+        }
+        if tick_upper_state.tick == 0 {
+            tick_upper_state.tick = tick_upper_index;
+        }
+
+        // A new order starts at the current epoch of its fill tick, an existing one must still be pending
+        {
+            let fill_tick_state = if zero_for_one {
+                &tick_upper_state
+            } else {
+                &tick_lower_state
+            };
+            if limit_order.liquidity == 0 {
+                limit_order.initialize(
+                    ctx.bumps.limit_order,
+                    ctx.accounts.pool_state.key(),
+                    ctx.accounts.owner.key(),
+                    tick_lower_index,
+                    zero_for_one,
+                )?;
+                limit_order.order_epoch = fill_tick_state.order_epoch;
+            } else {
+                require!(
+                    limit_order.zero_for_one == zero_for_one
+                        && !limit_order.is_filled(fill_tick_state),
+                    ErrorCode::LimitOrderFilled
+                );
+            }
+        }
+
+        let updated_reward_infos =
+            pool_state.update_reward_infos(Clock::get()?.unix_timestamp as u64)?;
+        let liquidity_delta = i128::try_from(liquidity).unwrap();
+        // The order liquidity is kept out of `liquidity_net` so it never adds to the pool liquidity
+        // the fees and rewards are shared over, swaps pick it up from the fill tick instead
+        let flip_tick_lower = tick_lower_state.update_liquidity_gross(
+            pool_state.tick_current,
+            liquidity_delta,
+            pool_state.fee_growth_global_0_x64,
+            pool_state.fee_growth_global_1_x64,
+            pool_state.seconds_per_liquidity_global_x64,
+            &updated_reward_infos,
+        )?;
+        let flip_tick_upper = tick_upper_state.update_liquidity_gross(
+            pool_state.tick_current,
+            liquidity_delta,
+            pool_state.fee_growth_global_0_x64,
+            pool_state.fee_growth_global_1_x64,
+            pool_state.seconds_per_liquidity_global_x64,
+            &updated_reward_infos,
+        )?;
+        if zero_for_one {
+            tick_upper_state.update_order_liquidity(liquidity_delta)?;
+        } else {
+            tick_lower_state.update_order_liquidity(liquidity_delta)?;
+        }
+        tick_array.update_tick_state(
+            tick_lower_index,
+            pool_state.tick_spacing,
+            tick_lower_state,
+        )?;
+        tick_array.update_tick_state(
+            tick_upper_index,
+            pool_state.tick_spacing,
+            tick_upper_state,
+        )?;
+
+        let tickarray_bitmap_extension = get_limit_order_tickarray_bitmap_extension(
+            pool_state,
+            tick_array.start_tick_index,
+            ctx.remaining_accounts,
+        )?;
+        update_limit_order_tick_array(
+            pool_state,
+            tick_array,
+            tickarray_bitmap_extension,
+            u8::from(flip_tick_lower) + u8::from(flip_tick_upper),
+            true,
+        )?;
+
+        // The range is out of the current price, so the order only holds the token it sells
+        let (amount_0, amount_1) = liquidity_math::get_delta_amounts_signed(
+            pool_state.tick_current,
+            pool_state.sqrt_price_x64,
+            tick_lower_index,
+            tick_upper_index,
+            liquidity_delta,
+        )?;
+        (liquidity, if zero_for_one { amount_0 } else { amount_1 })
+    };
+
+    limit_order.liquidity = limit_order.liquidity.checked_add(liquidity).unwrap();
+    limit_order.recent_epoch = get_recent_epoch()?;
+
+    let amount_transfer_fee =
+        get_transfer_inverse_fee(ctx.accounts.input_vault_mint.clone(), amount)?;
+    require_gte!(
+        amount_in,
+        amount + amount_transfer_fee,
+        ErrorCode::PriceSlippageCheck
+    );
+
+    transfer_from_user_to_pool_vault(
+        &ctx.accounts.owner,
+        &ctx.accounts.input_token_account,
+        &ctx.accounts.input_vault,
+        Some(ctx.accounts.input_vault_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount + amount_transfer_fee,
+    )?;
+
+    emit!(PlaceLimitOrderEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        owner: ctx.accounts.owner.key(),
+        tick_lower_index,
+        zero_for_one,
+        liquidity,
+        amount_in: amount,
+        amount_in_transfer_fee: amount_transfer_fee,
+    });
+
+    Ok(())
+}
+
+/// Find the tick array bitmap extension in the remaining accounts, it is required when the
+/// order tick array is out of the default bitmap
+pub fn get_limit_order_tickarray_bitmap_extension<'c: 'info, 'info>(
+    pool_state: &PoolState,
+    tick_array_start_index: i32,
+    remaining_accounts: &'c [AccountInfo<'info>],
+) -> Result<Option<&'c AccountInfo<'info>>> {
+    let tickarray_bitmap_extension = remaining_accounts.iter().find(|account_info| {
+        account_info
+            .key()
+            .eq(&TickArrayBitmapExtension::key(pool_state.key()))
+    });
+    if pool_state.is_overflow_default_tickarray_bitmap(vec![tick_array_start_index]) {
+        require!(
+            tickarray_bitmap_extension.is_some(),
+            ErrorCode::MissingTickArrayBitmapExtensionAccount
+        );
+    }
+    Ok(tickarray_bitmap_extension)
+}
+
+/// Update the initialized tick count of the order tick array after `flipped_count` of its ticks
+/// flipped, and flip the tick array bit when the array itself becomes (un)initialized
+pub fn update_limit_order_tick_array<'c: 'info, 'info>(
+    pool_state: &mut PoolState,
+    tick_array: &mut TickArrayState,
+    tickarray_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    flipped_count: u8,
+    add: bool,
+) -> Result<()> {
+    if flipped_count == 0 {
+        return Ok(());
+    }
+    let before_init_tick_count = tick_array.initialized_tick_count;
+    for _ in 0..flipped_count {
+        tick_array.update_initialized_tick_count(add)?;
+    }
+    if (add && before_init_tick_count == 0) || (!add && tick_array.initialized_tick_count == 0) {
+        pool_state.flip_tick_array_bit(tickarray_bitmap_extension, tick_array.start_tick_index)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct InitializePositionRewardExtension<'info> {
+    /// The position owner, pays to create the position reward extension
+    #[account(mut)]
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The position to earn the extension rewards
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The reward extension of the tick array of the lower tick
+    #[account(constraint = tick_array_lower_reward_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    /// The reward extension of the tick array of the upper tick
+    #[account(constraint = tick_array_upper_reward_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    /// The extension rewards owed to the position
+    #[account(
+        init,
+        seeds = [
+            POSITION_REWARD_EXTENSION_SEED.as_bytes(),
+            personal_position.key().as_ref(),
+        ],
+        bump,
+        payer = nft_owner,
+        space = PositionRewardExtension::LEN
+    )]
+    pub position_reward_extension: Box<Account<'info, PositionRewardExtension>>,
+
+    /// SPL program of the position nft
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePositionRewardExtension<'info> {
+    /// The position earning the extension rewards
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The reward extension of the tick array of the lower tick
+    #[account(constraint = tick_array_lower_reward_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    /// The reward extension of the tick array of the upper tick
+    #[account(constraint = tick_array_upper_reward_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper_reward_extension: AccountLoader<'info, TickArrayRewardExtension>,
+
+    /// The extension rewards owed to the position
+    #[account(
+        mut,
+        seeds = [
+            POSITION_REWARD_EXTENSION_SEED.as_bytes(),
+            personal_position.key().as_ref(),
+        ],
+        bump = position_reward_extension.bump,
+    )]
+    pub position_reward_extension: Box<Account<'info, PositionRewardExtension>>,
+}
+
+pub fn initialize_position_reward_extension(
+    ctx: Context<InitializePositionRewardExtension>,
+) -> Result<()> {
+    let personal_position = &mut ctx.accounts.personal_position;
+    let position_reward_extension = &mut ctx.accounts.position_reward_extension;
+    position_reward_extension.bump = ctx.bumps.position_reward_extension;
+    position_reward_extension.personal_position = personal_position.key();
+    // No position has this range, so the first update only takes the reward growths inside
+    position_reward_extension.tick_lower_index = i32::MIN;
+    position_reward_extension.tick_upper_index = i32::MIN;
+
+    update_position_reward_extension_rewards(
+        &ctx.accounts.pool_state,
+        personal_position,
+        position_reward_extension,
+        &ctx.accounts.tick_array_lower_reward_extension,
+        &ctx.accounts.tick_array_upper_reward_extension,
+    )
+}
+
+pub fn update_position_reward_extension(ctx: Context<UpdatePositionRewardExtension>) -> Result<()> {
+    update_position_reward_extension_rewards(
+        &ctx.accounts.pool_state,
+        &mut ctx.accounts.personal_position,
+        &mut ctx.accounts.position_reward_extension,
+        &ctx.accounts.tick_array_lower_reward_extension,
+        &ctx.accounts.tick_array_upper_reward_extension,
+    )
+}
+
+/// Accrues the extension rewards of a position up to the current timestamp, which allows its
+/// liquidity to change in the same transaction. A position moved to another range since the last
+/// update only takes the reward growths inside the new one, nothing is accrued over the move.
+pub fn update_position_reward_extension_rewards(
+    pool_state_loader: &AccountLoader<PoolState>,
+    personal_position: &mut PersonalPositionState,
+    position_reward_extension: &mut PositionRewardExtension,
+    tick_array_lower_reward_extension: &AccountLoader<TickArrayRewardExtension>,
+    tick_array_upper_reward_extension: &AccountLoader<TickArrayRewardExtension>,
+) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp as u64;
+    let mut pool_state = pool_state_loader.load_mut()?;
+    pool_state.update_reward_infos(current_timestamp)?;
+
+    let tick_lower_index = personal_position.tick_lower_index;
+    let tick_upper_index = personal_position.tick_upper_index;
+    let reward_growths_inside = get_extension_reward_growths_inside(
+        tick_lower_index,
+        tick_array_lower_reward_extension
+            .load()?
+            .get_reward_growths_outside(tick_lower_index, pool_state.tick_spacing)?,
+        tick_upper_index,
+        tick_array_upper_reward_extension
+            .load()?
+            .get_reward_growths_outside(tick_upper_index, pool_state.tick_spacing)?,
+        pool_state.tick_current,
+        &pool_state.extension_reward_growths,
+    );
+    position_reward_extension.update_rewards(
+        reward_growths_inside,
+        personal_position.liquidity,
+        tick_lower_index,
+        tick_upper_index,
+    );
+    personal_position.reward_extension_update_time = current_timestamp;
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use crate::libraries::tick_math;
+use crate::states::*;
+use crate::swap::{swap_internal_with_mode, SwapResult};
+use crate::util::*;
+use anchor_lang::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool to quote against
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // tick_array_account_1
+    // tick_array_account_2
+    // tick_array_account_...
+}
+
+/// Result of `quote_swap`, set as return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuoteSwapResult {
+    /// The report the swap would produce if it was executed now
+    pub swap_result: SwapResult,
+    /// The start index of every tick array the swap needs, in swap order
+    pub tick_array_start_indexes: Vec<i32>,
+}
+
+pub fn quote_swap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, QuoteSwap<'info>>,
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+) -> Result<QuoteSwapResult> {
+    // Get the current block timestamp
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+
+    // The swap loop updates the pool in place, so it runs against a copy
+    let pool_state = Box::new(RefCell::new(*ctx.accounts.pool_state.load()?));
+
+    // Ensure the swap is allowed based on the pool's open time
+    require_gt!(block_timestamp, pool_state.borrow().open_time);
+
+    // Initialize the tick array states and bitmap extension, all of them are only read
+    let mut tickarray_bitmap_extension = None;
+    let tick_array_states = &mut VecDeque::new();
+
+    // Load the tick array bitmap extension if it exists
+    let tick_array_bitmap_extension_key =
+        TickArrayBitmapExtension::key(ctx.accounts.pool_state.key());
+    for account_info in ctx.remaining_accounts.into_iter() {
+        if account_info.key().eq(&tick_array_bitmap_extension_key) {
+            tickarray_bitmap_extension = Some(
+                *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                    .load()?
+                    .deref()),
+            );
+            continue;
+        }
+        tick_array_states.push_back(AccountLoad::load_data_mut_unchecked(account_info)?);
+    }
+
+    let (mut observation_state, mut observation_extension) =
+        load_observations_mut(ctx.accounts.observation_state.as_ref())?;
+    let (amount_0, amount_1, swap_state) = swap_internal_with_mode(
+        &ctx.accounts.amm_config,
+        &mut pool_state.borrow_mut(),
+        tick_array_states,
+        &mut observation_state,
+        &mut observation_extension,
+        &tickarray_bitmap_extension,
+        amount_specified,
+        if sqrt_price_limit_x64 == 0 {
+            if zero_for_one {
+                tick_math::MIN_SQRT_PRICE_X64 + 1
+            } else {
+                tick_math::MAX_SQRT_PRICE_X64 - 1
+            }
+        } else {
+            sqrt_price_limit_x64
+        },
+        zero_for_one,
+        is_base_input,
+        oracle::block_timestamp(),
+        0,
+        true,
+    )?;
+
+    Ok(QuoteSwapResult {
+        swap_result: SwapResult::new(zero_for_one, amount_0, amount_1, &swap_state),
+        tick_array_start_indexes: swap_state.tick_array_start_indexes,
+    })
+}
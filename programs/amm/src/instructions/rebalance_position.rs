@@ -0,0 +1,420 @@
+use super::{
+    check_unclaimed_fees_and_vault, decrease_liquidity_and_update_position, mint_liquidity,
+    swap_within_pool,
+};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::ops::DerefMut;
+
+#[derive(Accounts)]
+#[instruction(tick_lower_index: i32, tick_upper_index: i32,tick_array_lower_start_index:i32,tick_array_upper_start_index:i32)]
+pub struct RebalancePosition<'info> {
+    /// The position owner, pays for the new protocol position and tick arrays if needed
+    #[account(mut)]
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The position moved to the new range
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The amm config of the pool, read by the swap
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle observations of the pool, written by the swap
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The protocol position of the current range
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores init state for the lower tick of the current range
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick of the current range
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The protocol position of the new range
+    #[account(
+        init_if_needed,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        payer = nft_owner,
+        space = ProtocolPositionState::LEN
+    )]
+    pub new_protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// CHECK: Account to mark the lower tick of the new range as initialized
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub new_tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK: Account to mark the upper tick of the new range as initialized
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub new_tick_array_upper: UncheckedAccount<'info>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the token_0 left over from the new range
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for the token_1 left over from the new range
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Program to create the new protocol position and tick arrays
+    pub system_program: Program<'info, System>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // tick_array_reward_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // tick_array_oracle_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // the swap hook program of the pool and its accounts, if the pool has one and a swap is made
+    // the tick arrays crossed by the swap, in the swap direction
+}
+
+pub fn rebalance_position<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, RebalancePosition<'info>>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    swap_amount: u64,
+    zero_for_one: bool,
+    sqrt_price_limit_x64: u128,
+    liquidity_min: u128,
+) -> Result<()> {
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let personal_position = &mut ctx.accounts.personal_position;
+    let tick_lower_index_before = personal_position.tick_lower_index;
+    let tick_upper_index_before = personal_position.tick_upper_index;
+    require!(
+        tick_lower_index != tick_lower_index_before || tick_upper_index != tick_upper_index_before,
+        ErrorCode::RebalanceToSameRange
+    );
+
+    // The swap hook is only looked for when the position is swapped
+    let (swap_hook, remaining_accounts) = if swap_amount > 0 {
+        split_swap_hook(
+            pool_state_loader.load()?.hook_program,
+            ctx.remaining_accounts,
+        )?
+    } else {
+        (None, ctx.remaining_accounts.iter().collect())
+    };
+    let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state_loader.key());
+    let tick_array_bitmap_extension = remaining_accounts
+        .iter()
+        .find(|account_info| account_info.key() == tick_array_bitmap_extension_key)
+        .copied();
+    let swap_tick_array_accounts: Vec<&AccountInfo> = remaining_accounts
+        .into_iter()
+        .filter(|account_info| account_info.key() != tick_array_bitmap_extension_key)
+        .collect();
+
+    {
+        let pool_state = pool_state_loader.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        check_ticks_order(tick_lower_index, tick_upper_index)?;
+        check_tick_array_start_index(
+            tick_array_lower_start_index,
+            tick_lower_index,
+            pool_state.tick_spacing,
+        )?;
+        check_tick_array_start_index(
+            tick_array_upper_start_index,
+            tick_upper_index,
+            pool_state.tick_spacing,
+        )?;
+
+        // Ensure the tick array bitmap extension is provided if either range needs it
+        if pool_state.is_overflow_default_tickarray_bitmap(vec![
+            ctx.accounts.tick_array_lower.load()?.start_tick_index,
+            ctx.accounts.tick_array_upper.load()?.start_tick_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+        ]) {
+            require!(
+                tick_array_bitmap_extension.is_some(),
+                ErrorCode::MissingTickArrayBitmapExtensionAccount
+            );
+        }
+    }
+
+    // Remove all the liquidity of the position and take its fees, the tokens stay in the vaults
+    let liquidity_before = personal_position.liquidity;
+    let (decrease_amount_0, fee_amount_0, decrease_amount_1, fee_amount_1) =
+        decrease_liquidity_and_update_position(
+            pool_state_loader,
+            &mut ctx.accounts.protocol_position,
+            personal_position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+            tick_array_bitmap_extension,
+            liquidity_before,
+        )?;
+    let mut available_0 = decrease_amount_0 + fee_amount_0;
+    let mut available_1 = decrease_amount_1 + fee_amount_1;
+
+    // Swap part of the tokens in the pool toward the ratio of the new range
+    let (mut swap_amount_in, mut swap_amount_out) = (0, 0);
+    if swap_amount > 0 {
+        require_gte!(
+            if zero_for_one {
+                available_0
+            } else {
+                available_1
+            },
+            swap_amount,
+            ErrorCode::RebalanceSwapAmountTooLarge
+        );
+        (swap_amount_in, swap_amount_out) = swap_within_pool(
+            pool_state_loader,
+            &ctx.accounts.amm_config,
+            &ctx.accounts.observation_state,
+            &ctx.accounts.nft_owner,
+            &ctx.accounts.recipient_token_account_0,
+            &ctx.accounts.recipient_token_account_1,
+            &swap_hook,
+            tick_array_bitmap_extension,
+            swap_tick_array_accounts,
+            swap_amount,
+            sqrt_price_limit_x64,
+            zero_for_one,
+        )?;
+        if zero_for_one {
+            available_0 -= swap_amount_in;
+            available_1 += swap_amount_out;
+        } else {
+            available_1 -= swap_amount_in;
+            available_0 += swap_amount_out;
+        }
+    }
+
+    // Open the new range with as much of the tokens as fits its ratio at the current price
+    let (liquidity, amount_0, amount_1);
+    {
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        let tick_array_lower_loader = TickArrayState::get_or_create_tick_array(
+            ctx.accounts.nft_owner.to_account_info(),
+            ctx.accounts.new_tick_array_lower.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            pool_state_loader,
+            tick_array_lower_start_index,
+            pool_state.tick_spacing,
+        )?;
+        let tick_array_upper_loader =
+            if tick_array_lower_start_index == tick_array_upper_start_index {
+                AccountLoad::<TickArrayState>::try_from(
+                    &ctx.accounts.new_tick_array_upper.to_account_info(),
+                )?
+            } else {
+                TickArrayState::get_or_create_tick_array(
+                    ctx.accounts.nft_owner.to_account_info(),
+                    ctx.accounts.new_tick_array_upper.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    pool_state_loader,
+                    tick_array_upper_start_index,
+                    pool_state.tick_spacing,
+                )?
+            };
+
+        let protocol_position = ctx.accounts.new_protocol_position.deref_mut();
+        if protocol_position.pool_id == Pubkey::default() {
+            protocol_position.bump = ctx.bumps.new_protocol_position;
+            protocol_position.pool_id = pool_state_loader.key();
+            protocol_position.tick_lower_index = tick_lower_index;
+            protocol_position.tick_upper_index = tick_upper_index;
+            tick_array_lower_loader
+                .load_mut()?
+                .get_tick_state_mut(tick_lower_index, pool_state.tick_spacing)?
+                .tick = tick_lower_index;
+            tick_array_upper_loader
+                .load_mut()?
+                .get_tick_state_mut(tick_upper_index, pool_state.tick_spacing)?
+                .tick = tick_upper_index;
+        }
+
+        // Keep one unit of each token as a margin for the amounts being rounded up
+        liquidity = liquidity_math::get_liquidity_from_amounts(
+            pool_state.sqrt_price_x64,
+            tick_math::get_sqrt_price_at_tick(tick_lower_index)?,
+            tick_math::get_sqrt_price_at_tick(tick_upper_index)?,
+            available_0.saturating_sub(1),
+            available_1.saturating_sub(1),
+        );
+        require_gte!(liquidity, liquidity_min, ErrorCode::PriceSlippageCheck);
+
+        (amount_0, amount_1) = if liquidity > 0 {
+            let pool_liquidity_before = pool_state.liquidity;
+            let (amount_0, amount_1) = mint_liquidity(
+                pool_state,
+                &tick_array_lower_loader,
+                &tick_array_upper_loader,
+                protocol_position,
+                tick_array_bitmap_extension,
+                liquidity,
+                tick_lower_index,
+                tick_upper_index,
+            )?;
+            require_gte!(available_0, amount_0, ErrorCode::PriceSlippageCheck);
+            require_gte!(available_1, amount_1, ErrorCode::PriceSlippageCheck);
+
+            emit!(LiquidityChangeEvent {
+                pool_state: pool_state.key(),
+                tick: pool_state.tick_current,
+                tick_lower: tick_lower_index,
+                tick_upper: tick_upper_index,
+                liquidity_before: pool_liquidity_before,
+                liquidity_after: pool_state.liquidity,
+            });
+            (amount_0, amount_1)
+        } else {
+            (0, 0)
+        };
+
+        personal_position.tick_lower_index = tick_lower_index;
+        personal_position.tick_upper_index = tick_upper_index;
+        personal_position.fee_growth_inside_0_last_x64 =
+            protocol_position.fee_growth_inside_0_last_x64;
+        personal_position.fee_growth_inside_1_last_x64 =
+            protocol_position.fee_growth_inside_1_last_x64;
+        // update rewards, must update before update liquidity
+        personal_position.update_rewards(protocol_position.reward_growth_inside, false)?;
+        personal_position.liquidity = liquidity;
+    }
+
+    // Send back what does not fit the new range
+    let refund_amount_0 = available_0 - amount_0;
+    let refund_amount_1 = available_1 - amount_1;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.recipient_token_account_0,
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        refund_amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.recipient_token_account_1,
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        refund_amount_1,
+    )?;
+    check_unclaimed_fees_and_vault(
+        pool_state_loader,
+        ctx.accounts.token_vault_0.deref_mut(),
+        ctx.accounts.token_vault_1.deref_mut(),
+    )?;
+
+    emit!(RebalancePositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        tick_lower_index_before,
+        tick_upper_index_before,
+        liquidity_before,
+        tick_lower_index,
+        tick_upper_index,
+        liquidity,
+        fee_amount_0,
+        fee_amount_1,
+        swap_amount_in,
+        swap_amount_out,
+        refund_amount_0,
+        refund_amount_1,
+    });
+
+    Ok(())
+}
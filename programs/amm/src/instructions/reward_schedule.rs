@@ -0,0 +1,173 @@
+use super::InitializeRewardParam;
+use crate::error::ErrorCode;
+use crate::states::pool::reward_period_limit;
+use crate::util::transfer_from_user_to_pool_vault;
+use crate::{states::*, util};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(reward_index: u8)]
+pub struct QueueRewardEpoch<'info> {
+    /// The pool owner or an operation owner, funds the epoch
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The authority's reward token account
+    #[account(
+        mut,
+        token::mint = reward_vault_mint
+    )]
+    pub authority_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// load info from the account to judge reward permission
+    #[account(
+        seeds = [
+            OPERATION_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub operation_state: AccountLoader<'info, OperationState>,
+
+    /// The queued epochs of the reward, created with the first one
+    #[account(
+        init_if_needed,
+        seeds = [
+            REWARD_SCHEDULE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &[reward_index],
+        ],
+        bump,
+        payer = authority,
+        space = RewardScheduleState::LEN
+    )]
+    pub reward_schedule: AccountLoader<'info, RewardScheduleState>,
+
+    /// The reward vault the epoch is funded into
+    #[account(mut)]
+    pub reward_token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of reward token vault
+    #[account(address = reward_token_vault.mint)]
+    pub reward_vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdvanceRewardSchedule<'info> {
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The queued epochs of the reward
+    #[account(mut, constraint = reward_schedule.load()?.pool_id == pool_state.key())]
+    pub reward_schedule: AccountLoader<'info, RewardScheduleState>,
+}
+
+pub fn queue_reward_epoch(
+    ctx: Context<QueueRewardEpoch>,
+    reward_index: u8,
+    param: InitializeRewardParam,
+) -> Result<()> {
+    let index = usize::from(reward_index);
+    require_gt!(REWARD_NUM, index);
+
+    let current_timestamp = u64::try_from(Clock::get()?.unix_timestamp).unwrap();
+    param.check(current_timestamp)?;
+
+    let admin_operator = ctx
+        .accounts
+        .operation_state
+        .load()?
+        .validate_operation_owner(ctx.accounts.authority.key());
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    if !admin_operator {
+        require_keys_eq!(ctx.accounts.authority.key(), pool_state.owner);
+        let period = param.end_time - param.open_time;
+        if period < reward_period_limit::MIN_REWARD_PERIOD
+            || period > reward_period_limit::MAX_REWARD_PERIOD
+        {
+            return err!(ErrorCode::InvalidRewardPeriod);
+        }
+    }
+
+    let reward_info = pool_state.reward_infos[index];
+    if !reward_info.initialized() {
+        return err!(ErrorCode::UnInitializedRewardInfo);
+    }
+    require_keys_eq!(
+        ctx.accounts.reward_token_vault.key(),
+        reward_info.token_vault
+    );
+
+    // The schedule is created with its first epoch
+    let mut reward_schedule = match ctx.accounts.reward_schedule.load_mut() {
+        Ok(reward_schedule) => reward_schedule,
+        Err(_) => {
+            let mut reward_schedule = ctx.accounts.reward_schedule.load_init()?;
+            reward_schedule.bump = ctx.bumps.reward_schedule;
+            reward_schedule.pool_id = ctx.accounts.pool_state.key();
+            reward_schedule.reward_index = reward_index;
+            reward_schedule
+        }
+    };
+
+    pool_state.update_reward_infos(current_timestamp)?;
+    // Epochs are queued in time order and never overlap
+    require_gte!(
+        param.open_time,
+        reward_schedule.get_last_end_time(&pool_state),
+        ErrorCode::InvalidRewardPeriod
+    );
+    let epoch = RewardEpoch {
+        open_time: param.open_time,
+        end_time: param.end_time,
+        emissions_per_second_x64: param.emissions_per_second_x64,
+    };
+    reward_schedule.push(epoch, ctx.accounts.authority.key())?;
+    reward_schedule.promote(&mut pool_state);
+
+    let reward_amount = epoch.get_reward_amount();
+    pool_state.reward_scheduled_amounts[index] = pool_state.reward_scheduled_amounts[index]
+        .checked_add(reward_amount)
+        .unwrap();
+    let reward_amount_with_transfer_fee = reward_amount
+        .checked_add(
+            util::get_transfer_inverse_fee(ctx.accounts.reward_vault_mint.clone(), reward_amount)
+                .unwrap(),
+        )
+        .unwrap();
+
+    emit!(RewardEpochQueuedEvent {
+        pool_state: ctx.accounts.pool_state.key(),
+        reward_index,
+        open_time: param.open_time,
+        end_time: param.end_time,
+        emissions_per_second_x64: param.emissions_per_second_x64,
+        reward_amount,
+    });
+
+    transfer_from_user_to_pool_vault(
+        &ctx.accounts.authority,
+        &ctx.accounts.authority_token_account,
+        &ctx.accounts.reward_token_vault,
+        Some(ctx.accounts.reward_vault_mint.clone()),
+        &ctx.accounts.reward_token_program.to_account_info(),
+        Some(ctx.accounts.reward_token_program.to_account_info()),
+        reward_amount_with_transfer_fee,
+    )
+}
+
+pub fn advance_reward_schedule(ctx: Context<AdvanceRewardSchedule>) -> Result<()> {
+    let current_timestamp = u64::try_from(Clock::get()?.unix_timestamp).unwrap();
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    // Roll into every epoch due by now, then refill the pending epoch from the schedule
+    ctx.accounts
+        .reward_schedule
+        .load_mut()?
+        .advance(&mut pool_state, current_timestamp)
+}
@@ -0,0 +1,65 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct SetCompoundKeeper<'info> {
+    /// The position owner, pays to create the compound keeper state
+    #[account(mut)]
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        constraint = nft_account.amount == 1 && nft_account.owner == nft_owner.key() @ ErrorCode::NotApproved,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The position allowing the keeper to compound its fees
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The keeper of the position
+    #[account(
+        init_if_needed,
+        seeds = [
+            COMPOUND_KEEPER_SEED.as_bytes(),
+            personal_position.nft_mint.as_ref(),
+        ],
+        bump,
+        payer = nft_owner,
+        space = CompoundKeeperState::LEN
+    )]
+    pub compound_keeper_state: Box<Account<'info, CompoundKeeperState>>,
+
+    /// SPL program of the position nft
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_compound_keeper(
+    ctx: Context<SetCompoundKeeper>,
+    keeper: Pubkey,
+    tip_rate: u32,
+) -> Result<()> {
+    require_gte!(
+        MAX_COMPOUND_TIP_RATE,
+        tip_rate,
+        ErrorCode::InvalidCompoundTipRate
+    );
+    let compound_keeper_state = &mut ctx.accounts.compound_keeper_state;
+    compound_keeper_state.bump = ctx.bumps.compound_keeper_state;
+    compound_keeper_state.nft_mint = ctx.accounts.personal_position.nft_mint;
+    // A new holder of the NFT does not inherit the keeper
+    compound_keeper_state.owner = ctx.accounts.nft_owner.key();
+    compound_keeper_state.keeper = keeper;
+    compound_keeper_state.tip_rate = if keeper == Pubkey::default() {
+        0
+    } else {
+        tip_rate
+    };
+    Ok(())
+}
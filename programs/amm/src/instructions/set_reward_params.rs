@@ -109,6 +109,16 @@ pub fn set_reward_params<'a, 'b, 'c: 'info, 'info>(
         .unwrap()
     };
 
+    // The cycle must end before the queued epoch of the reward opens
+    let pending_epoch = pool_state.reward_pending_epochs[reward_index as usize];
+    if pending_epoch.initialized() {
+        require_gte!(
+            pending_epoch.open_time,
+            reward_info.end_time,
+            ErrorCode::InvalidRewardPeriod
+        );
+    }
+
     // Update the reward information in the pool state
     pool_state.reward_infos[reward_index as usize] = reward_info;
 
@@ -0,0 +1,105 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SnapshotCumulativesInside<'info> {
+    /// The pool of the range
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Stores the lower tick of the range
+    #[account(constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores the upper tick of the range
+    #[account(constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The program account of the pool observations, to read the tick cumulative from
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The oracle extension of the tick array of the lower tick
+    #[account(constraint = tick_array_lower_oracle_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower_oracle_extension: AccountLoader<'info, TickArrayOracleExtension>,
+
+    /// The oracle extension of the tick array of the upper tick
+    #[account(constraint = tick_array_upper_oracle_extension.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper_oracle_extension: AccountLoader<'info, TickArrayOracleExtension>,
+}
+
+/// The cumulatives of a range as of a snapshot, like the ones of the Uniswap v3 oracle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CumulativesInside {
+    /// The tick cumulative inside the range
+    pub tick_cumulative_inside: i64,
+    /// The seconds per liquidity inside the range, as a Q64.64
+    pub seconds_per_liquidity_inside_x64: u128,
+    /// The seconds spent inside the range
+    pub seconds_inside: u32,
+}
+
+/// Returns the cumulatives inside the range as of now. They are only meaningful compared to
+/// another snapshot of the same range, taken while the ticks of the range stayed initialized.
+pub fn snapshot_cumulatives_inside(
+    ctx: Context<SnapshotCumulativesInside>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+) -> Result<CumulativesInside> {
+    check_ticks_order(tick_lower_index, tick_upper_index)?;
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let tick_array_lower = ctx.accounts.tick_array_lower.load()?;
+    let tick_array_upper = ctx.accounts.tick_array_upper.load()?;
+    check_tick_array_start_index(
+        tick_array_lower.start_tick_index,
+        tick_lower_index,
+        pool_state.tick_spacing,
+    )?;
+    check_tick_array_start_index(
+        tick_array_upper.start_tick_index,
+        tick_upper_index,
+        pool_state.tick_spacing,
+    )?;
+
+    let tick_lower = tick_array_lower.get_tick_state(tick_lower_index, pool_state.tick_spacing)?;
+    let tick_upper = tick_array_upper.get_tick_state(tick_upper_index, pool_state.tick_spacing)?;
+    require!(
+        tick_lower.is_initialized() && tick_upper.is_initialized(),
+        ErrorCode::TickNotInitialized
+    );
+
+    let block_timestamp = oracle::block_timestamp();
+    let (observation_state, observation_extension) =
+        load_observations(ctx.accounts.observation_state.as_ref())?;
+    let (tick_cumulative_inside, seconds_inside) = get_cumulatives_inside(
+        tick_lower_index,
+        ctx.accounts
+            .tick_array_lower_oracle_extension
+            .load()?
+            .get_cumulatives_outside(tick_lower_index, pool_state.tick_spacing)?,
+        tick_upper_index,
+        ctx.accounts
+            .tick_array_upper_oracle_extension
+            .load()?
+            .get_cumulatives_outside(tick_upper_index, pool_state.tick_spacing)?,
+        pool_state.tick_current,
+        get_tick_cumulative(
+            &pool_state,
+            &observation_state,
+            &observation_extension,
+            block_timestamp,
+        )?,
+        block_timestamp,
+    );
+
+    Ok(CumulativesInside {
+        tick_cumulative_inside,
+        seconds_per_liquidity_inside_x64: get_seconds_per_liquidity_inside(
+            tick_lower,
+            tick_upper,
+            pool_state.tick_current,
+            pool_state.get_seconds_per_liquidity_global(Clock::get()?.unix_timestamp as u64),
+        ),
+        seconds_inside,
+    })
+}
@@ -0,0 +1,173 @@
+use super::{create_nft_with_metadata, poke_personal_position};
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct SplitPosition<'info> {
+    /// The position owner, pays to mint the new position
+    #[account(mut)]
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the position being split
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Split liquidity out of this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// CHECK: Receives the new position NFT
+    pub position_nft_owner: UncheckedAccount<'info>,
+
+    /// Unique token mint address of the new position
+    #[account(
+        init,
+        mint::decimals = 0,
+        mint::authority = pool_state.key(),
+        payer = nft_owner,
+        mint::token_program = token_program,
+    )]
+    pub position_nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token account where the new position NFT will be minted
+    #[account(
+        init,
+        associated_token::mint = position_nft_mint,
+        associated_token::authority = position_nft_owner,
+        payer = nft_owner,
+        token::token_program = token_program,
+    )]
+    pub position_nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// To store metaplex metadata
+    /// CHECK: Safety check performed inside function body
+    #[account(mut)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// The new personal position state
+    #[account(
+        init,
+        seeds = [POSITION_SEED.as_bytes(), position_nft_mint.key().as_ref()],
+        bump,
+        payer = nft_owner,
+        space = PersonalPositionState::LEN
+    )]
+    pub new_personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// Sysvar for token mint and ATA creation
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Program to create the position manager state account
+    pub system_program: Program<'info, System>,
+
+    /// Program to create mint account and mint tokens
+    pub token_program: Program<'info, Token>,
+    /// Program to create an ATA for receiving position NFT
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Program to create NFT metadata
+    /// CHECK: Metadata program address constraint applied
+    pub metadata_program: Program<'info, Metadata>,
+}
+
+pub fn split_position<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SplitPosition<'info>>,
+    liquidity: u128,
+    with_matedata: bool,
+) -> Result<()> {
+    let personal_position = &mut ctx.accounts.personal_position;
+    require!(
+        liquidity > 0 && liquidity <= personal_position.liquidity,
+        ErrorCode::InvaildLiquidity
+    );
+
+    // The rewards of the reward extension must be accrued over the liquidity before it changes
+    personal_position.check_reward_extension_updated(Clock::get()?.unix_timestamp as u64)?;
+
+    {
+        let protocol_position = &mut ctx.accounts.protocol_position;
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        // Settle the position first, the fees and rewards owed so far stay with it
+        poke_personal_position(
+            &mut pool_state,
+            personal_position,
+            protocol_position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+        )?;
+
+        let new_personal_position = &mut ctx.accounts.new_personal_position;
+        new_personal_position.bump = ctx.bumps.new_personal_position;
+        new_personal_position.nft_mint = ctx.accounts.position_nft_mint.key();
+        new_personal_position.pool_id = ctx.accounts.pool_state.key();
+        new_personal_position.tick_lower_index = personal_position.tick_lower_index;
+        new_personal_position.tick_upper_index = personal_position.tick_upper_index;
+
+        new_personal_position.fee_growth_inside_0_last_x64 =
+            protocol_position.fee_growth_inside_0_last_x64;
+        new_personal_position.fee_growth_inside_1_last_x64 =
+            protocol_position.fee_growth_inside_1_last_x64;
+
+        // update rewards, must update before update liquidity
+        new_personal_position.update_rewards(protocol_position.reward_growth_inside, false)?;
+        new_personal_position.liquidity = liquidity;
+        // The split liquidity stays under the lock of the position
+        new_personal_position.unlock_timestamp = personal_position.unlock_timestamp;
+        new_personal_position.lock_allow_collect_fee = personal_position.lock_allow_collect_fee;
+
+        personal_position.liquidity = personal_position.liquidity.checked_sub(liquidity).unwrap();
+    }
+
+    create_nft_with_metadata(
+        &ctx.accounts.nft_owner,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.position_nft_mint,
+        &ctx.accounts.position_nft_account,
+        &ctx.accounts.metadata_account,
+        &ctx.accounts.metadata_program,
+        &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+        &ctx.accounts.rent,
+        with_matedata,
+    )?;
+
+    emit!(SplitPositionEvent {
+        position_nft_mint: personal_position.nft_mint,
+        new_position_nft_mint: ctx.accounts.position_nft_mint.key(),
+        liquidity,
+    });
+
+    Ok(())
+}
@@ -116,8 +116,78 @@ pub struct SwapState {
     pub protocol_fee: u64,
     // amount of input token paid as fund fee
     pub fund_fee: u64,
+    // amount of input token paid to the referrer
+    pub referral_fee: u64,
     // the current liquidity in range
     pub liquidity: u128,
+    // the liquidity of the pending limit orders whose range contains the current price,
+    // swapped against but not part of `liquidity`
+    pub order_liquidity: u128,
+    // the current boosted liquidity in range
+    pub boosted_liquidity: u128,
+    // the trade fee rate charged on the input amount
+    pub trade_fee_rate: u32,
+    // the number of initialized ticks crossed
+    pub ticks_crossed: u32,
+    // the start index of every tick array the swap went through, in swap order
+    pub tick_array_start_indexes: Vec<i32>,
+    // the initialized ticks crossed in tick arrays with a reward extension, flipped once the swap is done
+    pub reward_extension_crossed_ticks: Vec<i32>,
+    // the initialized ticks crossed in tick arrays with an oracle extension, flipped once the swap is done
+    pub oracle_extension_crossed_ticks: Vec<i32>,
+}
+
+/// Current layout version of `SwapResult`
+pub const SWAP_RESULT_VERSION: u8 = 3;
+
+/// Execution report of a swap, set as return data by the swap instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapResult {
+    /// Layout version of the report, bumped whenever a field is added
+    pub version: u8,
+    /// The amount of input token swapped into the pool, fees included
+    pub amount_in: u64,
+    /// The amount of output token swapped out of the pool
+    pub amount_out: u64,
+    /// The input token fee left to liquidity providers
+    pub lp_fee: u64,
+    /// The input token fee accrued to the protocol
+    pub protocol_fee: u64,
+    /// The input token fee accrued to the fund
+    pub fund_fee: u64,
+    /// The sqrt(price) of the pool after the swap
+    pub sqrt_price_x64: u128,
+    /// The tick of the pool after the swap
+    pub tick: i32,
+    /// The number of initialized ticks crossed by the swap
+    pub ticks_crossed: u32,
+    /// The trade fee rate charged by the swap, added in version 2
+    pub trade_fee_rate: u32,
+    /// The input token fee paid to the referrer, added in version 3
+    pub referral_fee: u64,
+}
+
+impl SwapResult {
+    pub fn new(zero_for_one: bool, amount_0: u64, amount_1: u64, state: &SwapState) -> Self {
+        let (amount_in, amount_out) = if zero_for_one {
+            (amount_0, amount_1)
+        } else {
+            (amount_1, amount_0)
+        };
+        Self {
+            version: SWAP_RESULT_VERSION,
+            amount_in,
+            amount_out,
+            lp_fee: state.fee_amount,
+            protocol_fee: state.protocol_fee,
+            fund_fee: state.fund_fee,
+            sqrt_price_x64: state.sqrt_price_x64,
+            tick: state.tick,
+            ticks_crossed: state.ticks_crossed,
+            trade_fee_rate: state.trade_fee_rate,
+            referral_fee: state.referral_fee,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -143,13 +213,51 @@ pub fn swap_internal<'b, 'info>(
     pool_state: &mut RefMut<PoolState>,
     tick_array_states: &mut VecDeque<RefMut<TickArrayState>>,
     observation_state: &mut RefMut<ObservationState>,
+    observation_extension: &mut [Observation],
+    tickarray_bitmap_extension: &Option<TickArrayBitmapExtension>,
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+    is_base_input: bool,
+    block_timestamp: u32,
+) -> Result<(u64, u64, SwapState)> {
+    swap_internal_with_mode(
+        amm_config,
+        pool_state,
+        tick_array_states,
+        observation_state,
+        observation_extension,
+        tickarray_bitmap_extension,
+        amount_specified,
+        sqrt_price_limit_x64,
+        zero_for_one,
+        is_base_input,
+        block_timestamp,
+        0,
+        false,
+    )
+}
+
+/// Runs the swap loop. `referral_fee_rate` is the share of the trade fee carved out of the
+/// LP fee for a referrer, zero when the swap has none.
+/// When `dry_run` is set, the crossed ticks are not written back to the
+/// tick arrays and the observation is left untouched, so both may be borrowed from read-only accounts.
+/// The pool state is still updated and must then be a copy.
+pub fn swap_internal_with_mode<'b, 'info>(
+    amm_config: &AmmConfig,
+    pool_state: &mut RefMut<PoolState>,
+    tick_array_states: &mut VecDeque<RefMut<TickArrayState>>,
+    observation_state: &mut RefMut<ObservationState>,
+    observation_extension: &mut [Observation],
     tickarray_bitmap_extension: &Option<TickArrayBitmapExtension>,
     amount_specified: u64,
     sqrt_price_limit_x64: u128,
     zero_for_one: bool,
     is_base_input: bool,
     block_timestamp: u32,
-) -> Result<(u64, u64)> {
+    referral_fee_rate: u32,
+    dry_run: bool,
+) -> Result<(u64, u64, SwapState)> {
     // Ensure the specified amount is not zero
     require!(amount_specified != 0, ErrorCode::ZeroAmountSpecified);
 
@@ -176,6 +284,21 @@ pub fn swap_internal<'b, 'info>(
     // Update reward information based on the current timestamp
     let updated_reward_infos = pool_state.update_reward_infos(block_timestamp as u64)?;
 
+    // check observation account is owned by the pool
+    // Ensure the observation account is owned by the pool
+    require_keys_eq!(observation_state.pool_id, pool_state.key());
+
+    // The pool may override the fee rates of its amm config
+    let (base_trade_fee_rate, protocol_fee_rate, fund_fee_rate) =
+        pool_state.get_fee_rates(amm_config);
+    // The fee rate is fixed for the whole swap, from the pool state before the swap
+    let trade_fee_rate = pool_state.get_trade_fee_rate(
+        base_trade_fee_rate,
+        observation_state,
+        observation_extension,
+        block_timestamp,
+    );
+
     // Initialize the swap state
     let mut state = SwapState {
         amount_specified_remaining: amount_specified,
@@ -190,13 +313,17 @@ pub fn swap_internal<'b, 'info>(
         fee_amount: 0,
         protocol_fee: 0,
         fund_fee: 0,
+        referral_fee: 0,
         liquidity: liquidity_start,
+        order_liquidity: 0,
+        boosted_liquidity: pool_state.boosted_liquidity,
+        trade_fee_rate,
+        ticks_crossed: 0,
+        tick_array_start_indexes: Vec::new(),
+        reward_extension_crossed_ticks: Vec::new(),
+        oracle_extension_crossed_ticks: Vec::new(),
     };
 
-    // check observation account is owned by the pool
-    // Ensure the observation account is owned by the pool
-    require_keys_eq!(observation_state.pool_id, pool_state.key());
-
     // Get the first initialized tick array
     let (mut is_match_pool_current_tick_array, first_vaild_tick_array_start_index) =
         pool_state.get_first_initialized_tick_array(&tickarray_bitmap_extension, zero_for_one)?;
@@ -221,6 +348,22 @@ pub fn swap_internal<'b, 'info>(
         current_vaild_tick_array_start_index,
         ErrorCode::InvalidFirstTickArrayAccount
     );
+    state
+        .tick_array_start_indexes
+        .push(current_vaild_tick_array_start_index);
+    // A limit order range is one tick spacing wide within a tick array, the pending orders of the
+    // current range are on its bounds
+    if is_match_pool_current_tick_array {
+        let tick_spacing = i32::from(pool_state.tick_spacing);
+        let tick_lower_index = state.tick - state.tick.rem_euclid(tick_spacing);
+        state.order_liquidity = tick_array_current
+            .get_order_liquidity(tick_lower_index, pool_state.tick_spacing)
+            .checked_add(
+                tick_array_current
+                    .get_order_liquidity(tick_lower_index + tick_spacing, pool_state.tick_spacing),
+            )
+            .unwrap();
+    }
 
     // continue swapping as long as we haven't used the entire input/output and haven't
     // reached the price limit
@@ -234,7 +377,7 @@ pub fn swap_internal<'b, 'info>(
             state.tick,
             state.liquidity,
             state.protocol_fee,
-            amm_config.protocol_fee_rate
+            protocol_fee_rate
         );
         // Save these three pieces of information for PriceChangeEvent
         // let tick_before = state.tick;
@@ -285,6 +428,9 @@ pub fn swap_internal<'b, 'info>(
                 require_keys_eq!(tick_array_current.pool_id, pool_state.key());
             }
             current_vaild_tick_array_start_index = next_initialized_tickarray_index.unwrap();
+            state
+                .tick_array_start_indexes
+                .push(current_vaild_tick_array_start_index);
 
             let first_initialized_tick = tick_array_current.first_initialized_tick(zero_for_one)?;
             next_initialized_tick = Box::new(*first_initialized_tick);
@@ -332,9 +478,12 @@ pub fn swap_internal<'b, 'info>(
         let swap_step = swap_math::compute_swap_step(
             step.sqrt_price_start_x64,
             target_price,
-            state.liquidity,
+            state
+                .liquidity
+                .checked_add(state.order_liquidity)
+                .ok_or(ErrorCode::CalculateOverflow)?,
             state.amount_specified_remaining,
-            amm_config.trade_fee_rate,
+            state.trade_fee_rate,
             is_base_input,
             zero_for_one,
             block_timestamp,
@@ -383,9 +532,9 @@ pub fn swap_internal<'b, 'info>(
         let step_fee_amount = step.fee_amount;
         // if the protocol fee is on, calculate how much is owed, decrement fee_amount, and increment protocol_fee
         // Calculate and update protocol fee
-        if amm_config.protocol_fee_rate > 0 {
+        if protocol_fee_rate > 0 {
             let delta = U128::from(step_fee_amount)
-                .checked_mul(amm_config.protocol_fee_rate.into())
+                .checked_mul(protocol_fee_rate.into())
                 .unwrap()
                 .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
                 .unwrap()
@@ -395,9 +544,9 @@ pub fn swap_internal<'b, 'info>(
         }
         // if the fund fee is on, calculate how much is owed, decrement fee_amount, and increment fund_fee
         // Calculate and update fund fee
-        if amm_config.fund_fee_rate > 0 {
+        if fund_fee_rate > 0 {
             let delta = U128::from(step_fee_amount)
-                .checked_mul(amm_config.fund_fee_rate.into())
+                .checked_mul(fund_fee_rate.into())
                 .unwrap()
                 .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
                 .unwrap()
@@ -405,6 +554,18 @@ pub fn swap_internal<'b, 'info>(
             step.fee_amount = step.fee_amount.checked_sub(delta).unwrap();
             state.fund_fee = state.fund_fee.checked_add(delta).unwrap();
         }
+        // if there is a referrer, carve its share out of what is left for liquidity providers
+        if referral_fee_rate > 0 {
+            let delta = U128::from(step_fee_amount)
+                .checked_mul(referral_fee_rate.into())
+                .unwrap()
+                .checked_div(FEE_RATE_DENOMINATOR_VALUE.into())
+                .unwrap()
+                .as_u64()
+                .min(step.fee_amount);
+            step.fee_amount = step.fee_amount.checked_sub(delta).unwrap();
+            state.referral_fee = state.referral_fee.checked_add(delta).unwrap();
+        }
 
         // update global fee tracker
         if state.liquidity > 0 {
@@ -424,6 +585,9 @@ pub fn swap_internal<'b, 'info>(
                 fee_growth_global_x64_delta,
                 state.fee_growth_global_x64, state.liquidity, step.fee_amount, state.fee_amount
             );
+        } else if step.fee_amount > 0 {
+            // only limit orders are in range, they do not earn fees
+            state.protocol_fee = state.protocol_fee.checked_add(step.fee_amount).unwrap();
         }
         // shift tick if we reached the next price
         if state.sqrt_price_x64 == step.sqrt_price_next_x64 {
@@ -443,19 +607,53 @@ pub fn swap_internal<'b, 'info>(
                     } else {
                         state.fee_growth_global_x64
                     },
+                    pool_state.seconds_per_liquidity_global_x64,
                     &updated_reward_infos,
                 );
                 // update tick_state to tick_array account
-                tick_array_current.update_tick_state(
-                    next_initialized_tick.tick,
-                    pool_state.tick_spacing.into(),
-                    *next_initialized_tick,
-                )?;
+                if !dry_run {
+                    // Fill the limit orders of this tick. Orders selling token_0 fill on the upper
+                    // tick of their range while the price moves up, orders selling token_1 fill on the
+                    // lower tick while it moves down.
+                    next_initialized_tick.fill_limit_orders();
+                    tick_array_current.update_tick_state(
+                        next_initialized_tick.tick,
+                        pool_state.tick_spacing.into(),
+                        *next_initialized_tick,
+                    )?;
+                    if tick_array_current.has_reward_extension {
+                        state
+                            .reward_extension_crossed_ticks
+                            .push(next_initialized_tick.tick);
+                    }
+                    if tick_array_current.has_oracle_extension {
+                        state
+                            .oracle_extension_crossed_ticks
+                            .push(next_initialized_tick.tick);
+                    }
+                }
 
+                // The orders of the range entered are pending on its far bound, the ones on the
+                // tick crossed have just been filled or belong to the range left
+                let tick_spacing = i32::from(pool_state.tick_spacing);
+                state.order_liquidity = tick_array_current.get_order_liquidity(
+                    if zero_for_one {
+                        next_initialized_tick.tick - tick_spacing
+                    } else {
+                        next_initialized_tick.tick + tick_spacing
+                    },
+                    pool_state.tick_spacing,
+                );
+
+                let mut boosted_liquidity_net = next_initialized_tick.boosted_liquidity_net;
                 if zero_for_one {
                     liquidity_net = liquidity_net.neg();
+                    boosted_liquidity_net = boosted_liquidity_net.neg();
                 }
                 state.liquidity = liquidity_math::add_delta(state.liquidity, liquidity_net)?;
+                state.boosted_liquidity =
+                    liquidity_math::add_delta(state.boosted_liquidity, boosted_liquidity_net)?;
+                state.ticks_crossed = state.ticks_crossed.checked_add(1).unwrap();
             }
 
             state.tick = if zero_for_one {
@@ -483,9 +681,9 @@ pub fn swap_internal<'b, 'info>(
             state.tick,
             state.liquidity,
             state.protocol_fee,
-            amm_config.protocol_fee_rate,
+            protocol_fee_rate,
             state.fund_fee,
-            amm_config.fund_fee_rate,
+            fund_fee_rate,
         );
         // emit!(PriceChangeEvent {
         //     pool_state: pool_state.key(),
@@ -504,13 +702,21 @@ pub fn swap_internal<'b, 'info>(
         pool_state.tick_current = state.tick;
     }
     // update the previous price to the observation
-    observation_state.update(block_timestamp, pool_state.tick_current);
+    if !dry_run {
+        observation_state.update(
+            observation_extension,
+            block_timestamp,
+            pool_state.tick_current,
+            pool_state.seconds_per_liquidity_global_x64,
+        );
+    }
 
     pool_state.sqrt_price_x64 = state.sqrt_price_x64;
 
     if liquidity_start != state.liquidity {
         pool_state.liquidity = state.liquidity;
     }
+    pool_state.boosted_liquidity = state.boosted_liquidity;
 
     // Calculate the final amounts for token 0 and token 1
     let (amount_0, amount_1) = if zero_for_one == is_base_input {
@@ -586,7 +792,7 @@ pub fn swap_internal<'b, 'info>(
             .unwrap();
     }
 
-    Ok((amount_0, amount_1))
+    Ok((amount_0, amount_1, state))
 }
 
 /// Performs a single exact input/output swap
@@ -597,12 +803,13 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
     amount_specified: u64,
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
-) -> Result<u64> {
+) -> Result<(u64, SwapResult)> {
     // Get the current block timestamp
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
 
     let amount_0;
     let amount_1;
+    let swap_state;
     let zero_for_one;
     let swap_price_before;
 
@@ -610,6 +817,24 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
     let input_balance_before = ctx.input_vault.amount;
     let output_balance_before = ctx.output_vault.amount;
 
+    // Let the hook of the pool reject the swap before it runs
+    let (swap_hook, remaining_accounts) =
+        split_swap_hook(ctx.pool_state.load()?.hook_program, remaining_accounts)?;
+    let (tick_array_reward_extensions, remaining_accounts) =
+        split_tick_array_reward_extensions(remaining_accounts);
+    let (tick_array_oracle_extensions, remaining_accounts) =
+        split_tick_array_oracle_extensions(remaining_accounts);
+    if let Some(swap_hook) = &swap_hook {
+        swap_hook.before_swap(BeforeSwapArgs {
+            pool_state: ctx.pool_state.key(),
+            sender: ctx.signer.key(),
+            zero_for_one: ctx.input_vault.mint == ctx.pool_state.load()?.token_mint_0,
+            is_base_input,
+            amount_specified,
+            sqrt_price_limit_x64,
+        })?;
+    }
+
     {
         // Load the pool state and store the initial swap price
         swap_price_before = ctx.pool_state.load()?.sqrt_price_x64;
@@ -638,7 +863,7 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
 
         // Load the tick array bitmap extension if it exists
         let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
-        for account_info in remaining_accounts.into_iter() {
+        for account_info in remaining_accounts {
             if account_info.key().eq(&tick_array_bitmap_extension_key) {
                 tickarray_bitmap_extension = Some(
                     *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
@@ -650,12 +875,15 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
             tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
         }
 
+        let (mut observation_state, mut observation_extension) =
+            load_observations_mut(ctx.observation_state.as_ref())?;
         // Perform the swap using the swap_internal function
-        (amount_0, amount_1) = swap_internal(
+        (amount_0, amount_1, swap_state) = swap_internal(
             &ctx.amm_config,
             pool_state,
             tick_array_states,
-            &mut ctx.observation_state.load_mut()?,
+            &mut observation_state,
+            &mut observation_extension,
             &tickarray_bitmap_extension,
             amount_specified,
             if sqrt_price_limit_x64 == 0 {
@@ -671,6 +899,22 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
             is_base_input,
             oracle::block_timestamp(),
         )?;
+        cross_tick_array_reward_extensions(
+            ctx.pool_state.key(),
+            pool_state.tick_spacing,
+            &pool_state.extension_reward_growths,
+            &tick_array_reward_extensions,
+            &swap_state.reward_extension_crossed_ticks,
+        )?;
+        cross_tick_array_oracle_extensions(
+            ctx.pool_state.key(),
+            pool_state,
+            &observation_state,
+            &observation_extension,
+            oracle::block_timestamp(),
+            &tick_array_oracle_extensions,
+            &swap_state.oracle_extension_crossed_ticks,
+        )?;
 
         #[cfg(feature = "enable-log")]
         msg!(
@@ -687,6 +931,20 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
         );
     }
 
+    // Let the hook of the pool reject the swap from its result
+    if let Some(swap_hook) = &swap_hook {
+        let pool_state = ctx.pool_state.load()?;
+        swap_hook.after_swap(AfterSwapArgs {
+            pool_state: ctx.pool_state.key(),
+            sender: ctx.signer.key(),
+            zero_for_one,
+            amount_0,
+            amount_1,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+            tick: pool_state.tick_current,
+        })?;
+    }
+
     // Determine the token accounts and vaults based on the swap direction
     let (token_account_0, token_account_1, vault_0, vault_1) = if zero_for_one {
         (
@@ -781,7 +1039,17 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
         zero_for_one,
         sqrt_price_x64: pool_state.sqrt_price_x64,
         liquidity: pool_state.liquidity,
-        tick: pool_state.tick_current
+        tick: pool_state.tick_current,
+        trade_fee_rate: swap_state.trade_fee_rate,
+        fee_amount: swap_state
+            .fee_amount
+            .checked_add(swap_state.protocol_fee)
+            .unwrap()
+            .checked_add(swap_state.fund_fee)
+            .unwrap()
+            .checked_add(swap_state.referral_fee)
+            .unwrap(),
+        referral_fee: swap_state.referral_fee,
     });
 
     // Ensure the swap price has changed as expected
@@ -809,17 +1077,23 @@ pub fn exact_internal<'b, 'c: 'info, 'info>(
         }
     }
 
+    let swap_result = SwapResult::new(zero_for_one, amount_0, amount_1, &swap_state);
     // Return the amount of tokens received by the user
     if is_base_input {
-        Ok(output_balance_before
-            .checked_sub(ctx.output_vault.amount)
-            .unwrap())
+        Ok((
+            output_balance_before
+                .checked_sub(ctx.output_vault.amount)
+                .unwrap(),
+            swap_result,
+        ))
     } else {
-        Ok(ctx
-            .input_vault
-            .amount
-            .checked_sub(input_balance_before)
-            .unwrap())
+        Ok((
+            ctx.input_vault
+                .amount
+                .checked_sub(input_balance_before)
+                .unwrap(),
+            swap_result,
+        ))
     }
 }
 
@@ -829,8 +1103,8 @@ pub fn swap<'a, 'b, 'c: 'info, 'info>(
     other_amount_threshold: u64,
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
-) -> Result<()> {
-    let amount = exact_internal(
+) -> Result<SwapResult> {
+    let (amount, swap_result) = exact_internal(
         &mut SwapAccounts {
             signer: ctx.accounts.payer.clone(),
             amm_config: &ctx.accounts.amm_config,
@@ -860,7 +1134,7 @@ pub fn swap<'a, 'b, 'c: 'info, 'info>(
         );
     }
 
-    Ok(())
+    Ok(swap_result)
 }
 
 #[cfg(test)]
@@ -1026,6 +1300,7 @@ mod swap_test {
                             i128::try_from(liquidity).unwrap(),
                             0,
                             0,
+                            0,
                             false,
                             &[RewardInfo::default(); 3],
                         )
@@ -1047,6 +1322,7 @@ mod swap_test {
                             i128::try_from(liquidity).unwrap(),
                             0,
                             0,
+                            0,
                             false,
                             &[RewardInfo::default(); 3],
                         )
@@ -1074,6 +1350,7 @@ mod swap_test {
                             i128::try_from(liquidity).unwrap(),
                             0,
                             0,
+                            0,
                             true,
                             &[RewardInfo::default(); 3],
                         )
@@ -1096,6 +1373,7 @@ mod swap_test {
                             i128::try_from(liquidity).unwrap(),
                             0,
                             0,
+                            0,
                             true,
                             &[RewardInfo::default(); 3],
                         )
@@ -1186,11 +1464,12 @@ mod swap_test {
                 );
 
             // just cross the tickarray boundary(-32400), hasn't reached the next tick array initialized tick
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 12188240002,
                 3049500711113990606,
@@ -1217,11 +1496,12 @@ mod swap_test {
             // so we pop the tickarray with start_index -32400
             // in this swap we will cross the tick(-32460), but not reach next tick (-32520)
             tick_array_states.pop_front();
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 121882400020,
                 3049500711113990606,
@@ -1245,11 +1525,12 @@ mod swap_test {
             liquidity = pool_state.borrow().liquidity;
 
             // swap in tickarray with start_index -36000, cross the tick -32520
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 60941200010,
                 3049500711113990606,
@@ -1302,11 +1583,12 @@ mod swap_test {
                 );
 
             // just cross the tickarray boundary(-32400), hasn't reached the next tick array initialized tick
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 477470480,
                 3049500711113990606,
@@ -1333,11 +1615,12 @@ mod swap_test {
             // so we pop the tickarray with start_index -32400
             // in this swap we will cross the tick(-32460), but not reach next tick (-32520)
             tick_array_states.pop_front();
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 4751002622,
                 3049500711113990606,
@@ -1361,11 +1644,12 @@ mod swap_test {
             liquidity = pool_state.borrow().liquidity;
 
             // swap in tickarray with start_index -36000
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 2358130642,
                 3049500711113990606,
@@ -1418,11 +1702,12 @@ mod swap_test {
                 );
 
             // just cross the tickarray boundary(-32460), hasn't reached the next tick array initialized tick
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 887470480,
                 5882283448660210779,
@@ -1448,11 +1733,12 @@ mod swap_test {
             // cross the tickarray boundary(-32460) in last step, but not reached tick -32400, because -32400 is the next tickarray boundary,
             // so the tickarray_current still is the tick array with start_index -36000
             // in this swap we will cross the tick(-32400), but not reach next tick (-29220)
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 3087470480,
                 5882283448660210779,
@@ -1477,11 +1763,12 @@ mod swap_test {
 
             // swap in tickarray with start_index -32400, cross the tick -29220
             tick_array_states.pop_front();
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 200941200010,
                 5882283448660210779,
@@ -1534,11 +1821,12 @@ mod swap_test {
                 );
 
             // just cross the tickarray boundary(-32460), hasn't reached the next tick array initialized tick
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 22796232052,
                 5882283448660210779,
@@ -1564,11 +1852,12 @@ mod swap_test {
             // cross the tickarray boundary(-32460) in last step, but not reached tick -32400, because -32400 is the next tickarray boundary,
             // so the tickarray_current still is the tick array with start_index -36000
             // in this swap we will cross the tick(-32400), but not reach next tick (-29220)
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 79023558189,
                 5882283448660210779,
@@ -1593,11 +1882,12 @@ mod swap_test {
 
             // swap in tickarray with start_index -32400, cross the tick -29220
             tick_array_states.pop_front();
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 4315086194758,
                 5882283448660210779,
@@ -1619,6 +1909,361 @@ mod swap_test {
     }
 
     #[cfg(test)]
+    mod swap_result_test {
+        use super::*;
+        use std::convert::identity;
+
+        #[test]
+        fn swap_result_from_swap_state_test() {
+            let tick_current = -32395;
+            let liquidity = 5124165121219;
+            let sqrt_price_x64 = 3651942632306380802;
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                tick_current,
+                60,
+                sqrt_price_x64,
+                liquidity,
+                vec![
+                    TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    },
+                    TickArrayInfo {
+                        start_tick_index: -36000,
+                        ticks: vec![
+                            build_tick(-32460, 1194569667438, 536061033698).take(),
+                            build_tick(-32520, 790917615645, 790917615645).take(),
+                        ],
+                    },
+                ],
+            );
+
+            // cross the tick -32400 and -32460, but not reach the tick -32520
+            let (amount_0, amount_1, swap_state) = swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &mut [],
+                &None,
+                134070640022,
+                3049500711113990606,
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            assert_eq!(swap_state.ticks_crossed, 2);
+
+            let swap_result = SwapResult::new(true, amount_0, amount_1, &swap_state);
+            assert_eq!(swap_result.version, SWAP_RESULT_VERSION);
+            assert_eq!(swap_result.amount_in, amount_0);
+            assert_eq!(swap_result.amount_out, amount_1);
+            assert_eq!(swap_result.lp_fee, swap_state.fee_amount);
+            assert_eq!(swap_result.protocol_fee, swap_state.protocol_fee);
+            assert_eq!(swap_result.fund_fee, swap_state.fund_fee);
+            assert_eq!(
+                swap_result.lp_fee + swap_result.protocol_fee + swap_result.fund_fee,
+                pool_state.borrow().total_fees_token_0
+                    + pool_state.borrow().protocol_fees_token_0
+                    + pool_state.borrow().fund_fees_token_0
+            );
+            assert_eq!(
+                swap_result.sqrt_price_x64,
+                identity(pool_state.borrow().sqrt_price_x64)
+            );
+            assert_eq!(swap_result.tick, identity(pool_state.borrow().tick_current));
+        }
+
+        #[test]
+        fn referral_fee_carved_out_of_lp_fee_test() {
+            let swap_with_referral_fee_rate = |referral_fee_rate: u32| {
+                let (amm_config, pool_state, tick_array_states, observation_state) =
+                    build_swap_param(
+                        -32395,
+                        60,
+                        3651942632306380802,
+                        5124165121219,
+                        vec![TickArrayInfo {
+                            start_tick_index: -32400,
+                            ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                        }],
+                    );
+                let (amount_0, amount_1, swap_state) = swap_internal_with_mode(
+                    &amm_config,
+                    &mut pool_state.borrow_mut(),
+                    &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                    &mut observation_state.borrow_mut(),
+                    &mut [],
+                    &None,
+                    100_000_000,
+                    3049500711113990606,
+                    true,
+                    true,
+                    oracle::block_timestamp_mock() as u32,
+                    referral_fee_rate,
+                    false,
+                )
+                .unwrap();
+                let total_fees_token_0 = pool_state.borrow().total_fees_token_0;
+                (
+                    SwapResult::new(true, amount_0, amount_1, &swap_state),
+                    total_fees_token_0,
+                )
+            };
+
+            let (without_referral, _) = swap_with_referral_fee_rate(0);
+            let (with_referral, total_fees_token_0) = swap_with_referral_fee_rate(200_000);
+            assert_eq!(without_referral.referral_fee, 0);
+            assert!(with_referral.referral_fee > 0);
+            // the referral fee only reduces the liquidity providers' share
+            assert_eq!(with_referral.amount_in, without_referral.amount_in);
+            assert_eq!(with_referral.amount_out, without_referral.amount_out);
+            assert_eq!(with_referral.protocol_fee, without_referral.protocol_fee);
+            assert_eq!(with_referral.fund_fee, without_referral.fund_fee);
+            assert_eq!(
+                with_referral.lp_fee + with_referral.referral_fee,
+                without_referral.lp_fee
+            );
+            assert_eq!(with_referral.lp_fee, total_fees_token_0);
+        }
+    }
+
+    mod dry_run_test {
+        use super::*;
+        use std::convert::identity;
+
+        #[test]
+        fn dry_run_matches_swap_without_writing_tick_arrays_test() {
+            let (amm_config, pool_state, tick_array_states, observation_state) = build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![
+                    TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    },
+                    TickArrayInfo {
+                        start_tick_index: -36000,
+                        ticks: vec![
+                            build_tick(-32460, 1194569667438, 536061033698).take(),
+                            build_tick(-32520, 790917615645, 790917615645).take(),
+                        ],
+                    },
+                ],
+            );
+
+            let pool_state_copy = RefCell::new(*pool_state.borrow());
+            let (quote_amount_0, quote_amount_1, quote_state) = swap_internal_with_mode(
+                &amm_config,
+                &mut pool_state_copy.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &mut [],
+                &None,
+                134070640022,
+                3049500711113990606,
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+                0,
+                true,
+            )
+            .unwrap();
+            assert_eq!(quote_state.tick_array_start_indexes, vec![-32400, -36000]);
+            // the crossed tick is left untouched
+            let tick_state = *tick_array_states[0]
+                .borrow_mut()
+                .get_tick_state_mut(-32400, 60)
+                .unwrap();
+            assert_eq!(identity(tick_state.fee_growth_outside_0_x64), 0);
+
+            let (amount_0, amount_1, swap_state) = swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &mut [],
+                &None,
+                134070640022,
+                3049500711113990606,
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            assert_eq!(quote_amount_0, amount_0);
+            assert_eq!(quote_amount_1, amount_1);
+            assert_eq!(
+                SwapResult::new(true, quote_amount_0, quote_amount_1, &quote_state),
+                SwapResult::new(true, amount_0, amount_1, &swap_state)
+            );
+            assert_eq!(
+                quote_state.tick_array_start_indexes,
+                swap_state.tick_array_start_indexes
+            );
+            let tick_state = *tick_array_states[0]
+                .borrow_mut()
+                .get_tick_state_mut(-32400, 60)
+                .unwrap();
+            assert_ne!(identity(tick_state.fee_growth_outside_0_x64), 0);
+        }
+    }
+
+    mod limit_order_fill_test {
+        use super::*;
+        use std::convert::identity;
+
+        const ORDER_LIQUIDITY: u128 = 100000000000;
+
+        fn build_limit_order_swap_param(
+            order_liquidity: u128,
+        ) -> (
+            AmmConfig,
+            RefCell<PoolState>,
+            VecDeque<RefCell<TickArrayState>>,
+            RefCell<ObservationState>,
+        ) {
+            // an order selling token_1 in [-32520, -32460], filled when -32520 is crossed down,
+            // its liquidity is only part of the gross liquidity of the range bounds
+            let order_lower_tick = build_tick(-32520, 790917615645 + order_liquidity, 790917615645);
+            order_lower_tick.borrow_mut().order_liquidity = order_liquidity;
+            build_swap_param(
+                -32395,
+                60,
+                3651942632306380802,
+                5124165121219,
+                vec![
+                    TickArrayInfo {
+                        start_tick_index: -32400,
+                        ticks: vec![build_tick(-32400, 277065331032, -277065331032).take()],
+                    },
+                    TickArrayInfo {
+                        start_tick_index: -36000,
+                        ticks: vec![
+                            build_tick(-32460, 1194569667438 + order_liquidity, 536061033698)
+                                .take(),
+                            order_lower_tick.take(),
+                            build_tick(-33000, 1000000000, 1000000000).take(),
+                        ],
+                    },
+                ],
+            )
+        }
+
+        #[test]
+        fn cross_fill_tick_removes_order_liquidity_test() {
+            let (amm_config, pool_state, tick_array_states, observation_state) =
+                build_limit_order_swap_param(ORDER_LIQUIDITY);
+
+            swap_internal(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &mut [],
+                &None,
+                u64::MAX / 2,
+                tick_math::get_sqrt_price_at_tick(-32530).unwrap(),
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+            )
+            .unwrap();
+            assert_eq!(identity(pool_state.borrow().tick_current), -32530);
+            // the order liquidity is never part of the pool liquidity
+            assert_eq!(
+                identity(pool_state.borrow().liquidity),
+                5124165121219 + 277065331032 - 536061033698 - 790917615645
+            );
+
+            let tick_array = &mut tick_array_states[1].borrow_mut();
+            let fill_tick = *tick_array.get_tick_state_mut(-32520, 60).unwrap();
+            assert_eq!(identity(fill_tick.order_liquidity), 0);
+            assert_eq!(identity(fill_tick.order_epoch), 1);
+            // the filled liquidity is still reserved until the orders are claimed
+            assert_eq!(identity(fill_tick.liquidity_net), 790917615645);
+            assert_eq!(
+                identity(fill_tick.liquidity_gross),
+                790917615645 + ORDER_LIQUIDITY
+            );
+            let other_tick = *tick_array.get_tick_state_mut(-32460, 60).unwrap();
+            assert_eq!(identity(other_tick.liquidity_net), 536061033698);
+            assert_eq!(
+                identity(other_tick.liquidity_gross),
+                1194569667438 + ORDER_LIQUIDITY
+            );
+        }
+
+        #[test]
+        fn order_liquidity_earns_no_fee_test() {
+            let swap_into_order_range = |order_liquidity: u128| {
+                let (amm_config, pool_state, tick_array_states, observation_state) =
+                    build_limit_order_swap_param(order_liquidity);
+                let fee_growth_global_0_x64_before = pool_state.borrow().fee_growth_global_0_x64;
+                let (amount_0, _, _) = swap_internal(
+                    &amm_config,
+                    &mut pool_state.borrow_mut(),
+                    &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                    &mut observation_state.borrow_mut(),
+                    &mut [],
+                    &None,
+                    u64::MAX / 2,
+                    tick_math::get_sqrt_price_at_tick(-32490).unwrap(),
+                    true,
+                    true,
+                    oracle::block_timestamp_mock() as u32,
+                )
+                .unwrap();
+                let pool_state = pool_state.borrow();
+                (
+                    amount_0,
+                    pool_state.liquidity,
+                    pool_state.fee_growth_global_0_x64 - fee_growth_global_0_x64_before,
+                )
+            };
+
+            let (amount_0, liquidity, fee_growth_0_x64) = swap_into_order_range(ORDER_LIQUIDITY);
+            let (amount_0_without_order, liquidity_without_order, fee_growth_0_x64_without_order) =
+                swap_into_order_range(0);
+            // the order is swapped against, but the fees are shared over the positions only
+            assert!(amount_0 > amount_0_without_order);
+            assert_eq!(liquidity, liquidity_without_order);
+            assert!(fee_growth_0_x64 > fee_growth_0_x64_without_order);
+        }
+
+        #[test]
+        fn dry_run_does_not_fill_order_test() {
+            let (amm_config, pool_state, tick_array_states, observation_state) =
+                build_limit_order_swap_param(ORDER_LIQUIDITY);
+
+            swap_internal_with_mode(
+                &amm_config,
+                &mut pool_state.borrow_mut(),
+                &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
+                &mut observation_state.borrow_mut(),
+                &mut [],
+                &None,
+                u64::MAX / 2,
+                tick_math::get_sqrt_price_at_tick(-32530).unwrap(),
+                true,
+                true,
+                oracle::block_timestamp_mock() as u32,
+                0,
+                true,
+            )
+            .unwrap();
+            let fill_tick = *tick_array_states[1]
+                .borrow_mut()
+                .get_tick_state_mut(-32520, 60)
+                .unwrap();
+            assert_eq!(identity(fill_tick.order_liquidity), ORDER_LIQUIDITY);
+            assert_eq!(identity(fill_tick.order_epoch), 0);
+        }
+    }
+
     mod find_next_initialized_tick_test {
         use super::*;
 
@@ -1643,11 +2288,12 @@ mod swap_test {
             );
 
             // find the first initialzied tick(-28860) and cross it in tickarray
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 12188240002,
                 tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1688,11 +2334,12 @@ mod swap_test {
             );
 
             // find the first initialzied tick(-32400) and cross it in tickarray
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 12188240002,
                 tick_math::get_sqrt_price_at_tick(-28860).unwrap(),
@@ -1738,6 +2385,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 12188240002,
                 tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1774,11 +2422,12 @@ mod swap_test {
         );
 
         // not cross tick(-28860), but pool.tick_current = -28860
-        let (amount_0, amount_1) = swap_internal(
+        let (amount_0, amount_1, _) = swap_internal(
             &amm_config,
             &mut pool_state.borrow_mut(),
             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
             &mut observation_state.borrow_mut(),
+            &mut [],
             &None,
             25,
             tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1797,11 +2446,12 @@ mod swap_test {
         assert!(amount_0 == 25);
 
         // just cross tick(-28860), pool.tick_current = -28861
-        let (amount_0, amount_1) = swap_internal(
+        let (amount_0, amount_1, _) = swap_internal(
             &amm_config,
             &mut pool_state.borrow_mut(),
             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
             &mut observation_state.borrow_mut(),
+            &mut [],
             &None,
             3,
             tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1823,11 +2473,12 @@ mod swap_test {
 
         // we swap just a little amount, let pool tick_current also equal -28861
         // but pool.sqrt_price_x64 > tick_math::get_sqrt_price_at_tick(-28861)
-        let (amount_0, amount_1) = swap_internal(
+        let (amount_0, amount_1, _) = swap_internal(
             &amm_config,
             &mut pool_state.borrow_mut(),
             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
             &mut observation_state.borrow_mut(),
+            &mut [],
             &None,
             50,
             tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1876,11 +2527,12 @@ mod swap_test {
             );
 
             // zero for one, just cross tick(-28860),  pool.tick_current = -28861 and pool.sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(-28860)
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 27,
                 tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1903,11 +2555,12 @@ mod swap_test {
             sqrt_price_x64 = pool_state.borrow().sqrt_price_x64;
 
             // we swap just a little amount, it is completely taken by fees, the sqrt price and the tick will remain the same
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 1,
                 tick_math::get_sqrt_price_at_tick(-32400).unwrap(),
@@ -1928,11 +2581,12 @@ mod swap_test {
             // Actually, the loop for this swap was executed twice because the previous swap happened to have `pool.tick_current` exactly on the boundary that is divisible by `tick_spacing`.
             // In the first iteration of this swap's loop, it found the initial tick (-28860), but at this point, both the initial and final prices were equal to the price at tick -28860.
             // This did not meet the conditions for swapping so both swap_amount_input and swap_amount_output were 0. The actual output was calculated in the second iteration of the loop.
-            let (amount_0, amount_1) = swap_internal(
+            let (amount_0, amount_1, _) = swap_internal(
                 &amm_config,
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &None,
                 10,
                 tick_math::get_sqrt_price_at_tick(-28800).unwrap(),
@@ -1994,6 +2648,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2048,6 +2703,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2102,6 +2758,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2155,6 +2812,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2212,6 +2870,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2266,6 +2925,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2320,6 +2980,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2373,6 +3034,7 @@ mod swap_test {
                 &mut pool_state.borrow_mut(),
                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                 &mut observation_state.borrow_mut(),
+                &mut [],
                 &Some(bitmap_extension_state),
                 amount_specified,
                 tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2426,6 +3088,7 @@ mod swap_test {
                         &mut pool_state.borrow_mut(),
                         &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                         &mut observation_state.borrow_mut(),
+                        &mut [],
                         &Some(bitmap_extension_state),
                         amount_specified,
                         tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2435,7 +3098,7 @@ mod swap_test {
                     );
 
                     if result.is_ok() {
-                        let ( amount_0_before, amount_1_before) = result.unwrap();
+                        let (amount_0_before, amount_1_before, _) = result.unwrap();
 
                         let (amm_config, pool_state, tick_array_states, observation_state,bitmap_extension_state,  _sum_amount_0, _sum_amount_1) = setup_swap_test(
                             tick_current,
@@ -2448,6 +3111,7 @@ mod swap_test {
                             &mut pool_state.borrow_mut(),
                             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                             &mut observation_state.borrow_mut(),
+                            &mut [],
                             &Some(bitmap_extension_state),
                             amount_specified,
                             tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2459,7 +3123,7 @@ mod swap_test {
 
                         // println!("----- input: tick_current:{}, amount_0:{}, amount_1:{}, amount_specified:{},tick_lower:{}, tick_upper:{},liquidity:{}", tick_current, amount_0, amount_1,amount_specified, tick_lower, tick_upper, identity(pool_state.borrow().liquidity));
 
-                        let ( amount_0_after, amount_1_after) = result.unwrap();
+                        let (amount_0_after, amount_1_after, _) = result.unwrap();
                         assert_eq!(amount_0_before, amount_0_after);
                         assert_eq!(amount_1_before, amount_1_after);
 
@@ -2472,6 +3136,7 @@ mod swap_test {
                                 &mut pool_state.borrow_mut(),
                                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                                 &mut observation_state.borrow_mut(),
+                                &mut [],
                                 &Some(bitmap_extension_state),
                                 amount_specified,
                                 tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2517,6 +3182,7 @@ mod swap_test {
                         &mut pool_state.borrow_mut(),
                         &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                         &mut observation_state.borrow_mut(),
+                        &mut [],
                         &Some(bitmap_extension_state),
                         amount_specified,
                         tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2526,7 +3192,7 @@ mod swap_test {
                     );
 
                     if result.is_ok() {
-                        let ( amount_0_before, amount_1_before) = result.unwrap();
+                        let (amount_0_before, amount_1_before, _) = result.unwrap();
 
                         let (amm_config, pool_state, tick_array_states, observation_state,bitmap_extension_state, _sum_amount_0, _sum_amount_1) = setup_swap_test(
                             tick_current,
@@ -2539,6 +3205,7 @@ mod swap_test {
                             &mut pool_state.borrow_mut(),
                             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                             &mut observation_state.borrow_mut(),
+                            &mut [],
                             &Some(bitmap_extension_state),
                             amount_specified,
                             tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2550,7 +3217,7 @@ mod swap_test {
 
                         println!("----- input: tick_current:{}, amount_0:{}, amount_1:{}, amount_specified:{},tick_lower:{}, tick_upper:{},liquidity:{}", tick_current, amount_0, amount_1,amount_specified, tick_lower, tick_upper, identity(pool_state.borrow().liquidity));
 
-                        let ( amount_0_after, amount_1_after) = result.unwrap();
+                        let (amount_0_after, amount_1_after, _) = result.unwrap();
                         assert_eq!(amount_0_before, amount_0_after);
                         assert_eq!(amount_1_before, amount_1_after);
 
@@ -2563,6 +3230,7 @@ mod swap_test {
                                 &mut pool_state.borrow_mut(),
                                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                                 &mut observation_state.borrow_mut(),
+                                &mut [],
                                 &Some(bitmap_extension_state),
                                 amount_specified,
                                 tick_math::MIN_SQRT_PRICE_X64 + 1,
@@ -2609,6 +3277,7 @@ mod swap_test {
                         &mut pool_state.borrow_mut(),
                         &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                         &mut observation_state.borrow_mut(),
+                        &mut [],
                         &Some(bitmap_extension_state),
                         amount_specified,
                         tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2619,7 +3288,7 @@ mod swap_test {
 
 
                     if result.is_ok() {
-                        let ( amount_0_before, amount_1_before) = result.unwrap();
+                        let (amount_0_before, amount_1_before, _) = result.unwrap();
 
                         let (amm_config, pool_state, tick_array_states, observation_state,bitmap_extension_state,  _sum_amount_0, _sum_amount_1) = setup_swap_test(
                             tick_current,
@@ -2632,6 +3301,7 @@ mod swap_test {
                             &mut pool_state.borrow_mut(),
                             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                             &mut observation_state.borrow_mut(),
+                            &mut [],
                             &Some(bitmap_extension_state),
                             amount_specified,
                             tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2643,7 +3313,7 @@ mod swap_test {
 
                         // println!("----- input: tick_current:{}, amount_0:{}, amount_1:{}, amount_specified:{},tick_lower:{}, tick_upper:{},liquidity:{}", tick_current, amount_0, amount_1,amount_specified, tick_lower, tick_upper, identity(pool_state.borrow().liquidity));
 
-                        let (amount_0_after, amount_1_after) = result.unwrap();
+                        let (amount_0_after, amount_1_after, _) = result.unwrap();
                         assert_eq!(amount_0_before, amount_0_after);
                         assert_eq!(amount_1_before, amount_1_after);
 
@@ -2656,6 +3326,7 @@ mod swap_test {
                                 &mut pool_state.borrow_mut(),
                                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                                 &mut observation_state.borrow_mut(),
+                                &mut [],
                                 &Some(bitmap_extension_state),
                                 amount_specified,
                                 tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2700,6 +3371,7 @@ mod swap_test {
                         &mut pool_state.borrow_mut(),
                         &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                         &mut observation_state.borrow_mut(),
+                        &mut [],
                         &Some(bitmap_extension_state),
                         amount_specified,
                         tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2709,7 +3381,7 @@ mod swap_test {
                     );
 
                     if result.is_ok() {
-                        let ( amount_0_before, amount_1_before) = result.unwrap();
+                        let (amount_0_before, amount_1_before, _) = result.unwrap();
 
                         let (amm_config, pool_state, tick_array_states, observation_state,bitmap_extension_state,  _sum_amount_0, _sum_amount_1) = setup_swap_test(
                             tick_current,
@@ -2722,6 +3394,7 @@ mod swap_test {
                             &mut pool_state.borrow_mut(),
                             &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                             &mut observation_state.borrow_mut(),
+                            &mut [],
                             &Some(bitmap_extension_state),
                             amount_specified,
                             tick_math::MAX_SQRT_PRICE_X64 - 1,
@@ -2733,7 +3406,7 @@ mod swap_test {
 
                         // println!("----- input: tick_current:{}, amount_0:{}, amount_1:{}, amount_specified:{},tick_lower:{}, tick_upper:{},liquidity:{}", tick_current, amount_0, amount_1,amount_specified, tick_lower, tick_upper, identity(pool_state.borrow().liquidity));
 
-                        let (amount_0_after, amount_1_after) = result.unwrap();
+                        let (amount_0_after, amount_1_after, _) = result.unwrap();
                         assert_eq!(amount_0_before, amount_0_after);
                         assert_eq!(amount_1_before, amount_1_after);
 
@@ -2746,6 +3419,7 @@ mod swap_test {
                                 &mut pool_state.borrow_mut(),
                                 &mut get_tick_array_states_mut(&tick_array_states).borrow_mut(),
                                 &mut observation_state.borrow_mut(),
+                                &mut [],
                                 &Some(bitmap_extension_state),
                                 amount_specified,
                                 tick_math::MAX_SQRT_PRICE_X64 - 1,
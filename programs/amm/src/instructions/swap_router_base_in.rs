@@ -1,6 +1,7 @@
 use crate::error::ErrorCode;
 use crate::states::*;
-use crate::swap_v2::{exact_internal_v2, SwapSingleV2};
+use crate::swap::SwapResult;
+use crate::swap_v2::{exact_internal_v2, split_referrer_token_account, SwapSingleV2};
 use anchor_lang::prelude::*;
 use anchor_spl::{
     token::Token,
@@ -30,22 +31,32 @@ pub struct SwapRouterBaseIn<'info> {
     //     address = spl_memo::id()
     // )]
     pub memo_program: UncheckedAccount<'info>,
+    // remaining accounts
+    // referrer_token_account: optional, must be the first one, receives the referral fee of the first hop of `swap_router_base_in_with_referral`
+    // the accounts of every hop, in path order
 }
 
 pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
     amount_in: u64,
     amount_out_minimum: u64,
-) -> Result<()> {
+    referral_fee_rate: u32,
+) -> Result<Vec<SwapResult>> {
     // Initialize the internal amount with the input amount
     let mut amount_in_internal = amount_in;
+    // The execution report of every hop, in path order
+    let mut swap_results = Vec::new();
 
     // Initialize the input token account and mint with the provided accounts
     let mut input_token_account = Box::new(ctx.accounts.input_token_account.clone());
     let mut input_token_mint = Box::new(ctx.accounts.input_token_mint.clone());
 
+    // The referrer is paid in the input token of the first hop only
+    let (mut referrer_token_account, path_accounts) =
+        split_referrer_token_account(ctx.remaining_accounts)?;
+
     // Get the remaining accounts to process the swaps
-    let mut accounts: &[AccountInfo] = ctx.remaining_accounts;
+    let mut accounts: &[AccountInfo] = path_accounts;
 
     // Iterate through the remaining accounts to perform swaps
     while !accounts.is_empty() {
@@ -53,9 +64,7 @@ pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
         let account_info = remaining_accounts.next().unwrap();
 
         // Skip accounts that are not AMM configurations
-        if accounts.len() != ctx.remaining_accounts.len()
-            && account_info.data_len() != AmmConfig::LEN
-        {
+        if accounts.len() != path_accounts.len() && account_info.data_len() != AmmConfig::LEN {
             accounts = remaining_accounts.as_slice();
             continue;
         }
@@ -97,7 +106,8 @@ pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
         accounts = remaining_accounts.as_slice();
 
         // Perform the swap using the exact_internal_v2 function
-        amount_in_internal = exact_internal_v2(
+        let swap_result;
+        (amount_in_internal, swap_result) = exact_internal_v2(
             &mut SwapSingleV2 {
                 payer: ctx.accounts.payer.clone(),
                 amm_config,
@@ -117,7 +127,10 @@ pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
             amount_in_internal,
             0,
             true,
+            referrer_token_account.take(),
+            referral_fee_rate,
         )?;
+        swap_results.push(swap_result);
         // output token is the new swap input token
         // Update the input token account and mint for the next swap
         input_token_account = output_token_account;
@@ -131,5 +144,5 @@ pub fn swap_router_base_in<'a, 'b, 'c: 'info, 'info>(
         ErrorCode::TooLittleOutputReceived
     );
 
-    Ok(())
+    Ok(swap_results)
 }
@@ -0,0 +1,189 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::swap::SwapResult;
+use crate::swap_v2::{settle_swap_v2, swap_pool_v2, SwapSingleV2};
+use crate::util;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+#[derive(Accounts)]
+pub struct SwapRouterBaseOut<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// The token account that pays input tokens for the swap
+    #[account(mut)]
+    pub input_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The mint of input token
+    #[account(mut)]
+    pub input_token_mint: InterfaceAccount<'info, Mint>,
+
+    /// SPL program for token transfers
+    pub token_program: Program<'info, Token>,
+    /// SPL program 2022 for token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK:
+    // #[account(
+    //     address = spl_memo::id()
+    // )]
+    pub memo_program: UncheckedAccount<'info>,
+    // remaining accounts, one group for every pool in the path, in swap order:
+    // amm_config
+    // pool_state
+    // output_token_account
+    // input_vault
+    // output_vault
+    // output_token_mint
+    // observation_state
+    // tickarray_bitmap_extension(optional), tick_array_account_1, tick_array_account_2, ...
+}
+
+pub fn swap_router_base_out<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseOut<'info>>,
+    amount_out: u64,
+    amount_in_maximum: u64,
+) -> Result<Vec<SwapResult>> {
+    // Every hop of the path, with the tick array accounts that belong to it
+    let mut hops: Vec<(SwapSingleV2<'info>, &'c [AccountInfo<'info>])> = Vec::new();
+
+    // Initialize the input token account and mint with the provided accounts
+    let mut input_token_account = Box::new(ctx.accounts.input_token_account.clone());
+    let mut input_token_mint = Box::new(ctx.accounts.input_token_mint.clone());
+
+    // Get the remaining accounts to build the hops
+    let mut accounts: &'c [AccountInfo<'info>] = ctx.remaining_accounts;
+    while !accounts.is_empty() {
+        let mut remaining_accounts = accounts.iter();
+
+        // Load the AMM configuration and pool state
+        let amm_config = Box::new(Account::<AmmConfig>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?);
+        let pool_state_loader = AccountLoader::<PoolState>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?;
+
+        // Load the output token account, input vault, output vault, and output token mint
+        let output_token_account = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?);
+        let input_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?);
+        let output_vault = Box::new(InterfaceAccount::<TokenAccount>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?);
+        let output_token_mint = Box::new(InterfaceAccount::<Mint>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?);
+
+        // Load the observation state
+        let observation_state = AccountLoader::<ObservationState>::try_from(
+            remaining_accounts.next().ok_or(ErrorCode::AccountLack)?,
+        )?;
+
+        {
+            // Load the pool state and perform checks
+            let pool_state = pool_state_loader.load()?;
+            // check observation account is owned by the pool
+            require_keys_eq!(pool_state.observation_key, observation_state.key());
+            // check ammConfig account is associate with the pool
+            require_keys_eq!(pool_state.amm_config, amm_config.key());
+        }
+
+        // The tick arrays of this hop run until the amm_config of the next hop
+        accounts = remaining_accounts.as_slice();
+        let tick_array_count = accounts
+            .iter()
+            .position(|account_info| account_info.data_len() == AmmConfig::LEN)
+            .unwrap_or(accounts.len());
+        let (tick_array_accounts, next_accounts) = accounts.split_at(tick_array_count);
+        accounts = next_accounts;
+
+        hops.push((
+            SwapSingleV2 {
+                payer: ctx.accounts.payer.clone(),
+                amm_config,
+                input_token_account: input_token_account.clone(),
+                pool_state: pool_state_loader,
+                output_token_account: output_token_account.clone(),
+                input_vault,
+                output_vault,
+                input_vault_mint: input_token_mint.clone(),
+                output_vault_mint: output_token_mint.clone(),
+                observation_state,
+                token_program: ctx.accounts.token_program.clone(),
+                token_program_2022: ctx.accounts.token_program_2022.clone(),
+                memo_program: ctx.accounts.memo_program.clone(),
+            },
+            tick_array_accounts,
+        ));
+
+        // output token is the new swap input token
+        input_token_account = output_token_account;
+        input_token_mint = output_token_mint;
+    }
+    require!(!hops.is_empty(), ErrorCode::AccountLack);
+
+    // Walk the path backwards. Every hop must output exactly what the next hop pulls
+    // from the user, so the swaps are computed from the last pool to the first one.
+    let mut amount_specified = vec![0u64; hops.len()];
+    let mut swap_amounts = Vec::with_capacity(hops.len());
+    let mut amount_out_internal = amount_out;
+    for (i, (hop, tick_array_accounts)) in hops.iter_mut().enumerate().rev() {
+        amount_specified[i] = amount_out_internal;
+        let amounts = swap_pool_v2(hop, tick_array_accounts, amount_out_internal, 0, false, 0)?;
+
+        // the user pays the pool input plus the transfer fee of the input token
+        let amount_in = if amounts.zero_for_one {
+            amounts.amount_0
+        } else {
+            amounts.amount_1
+        };
+        amount_out_internal = amount_in
+            .checked_add(util::get_transfer_inverse_fee(
+                hop.input_vault_mint.clone(),
+                amount_in,
+            )?)
+            .ok_or(ErrorCode::CalculateOverflow)?;
+        swap_amounts.push(amounts);
+    }
+    swap_amounts.reverse();
+
+    // Ensure the input amount does not exceed the maximum the user accepted
+    require_gte!(
+        amount_in_maximum,
+        amount_out_internal,
+        ErrorCode::TooMuchInputPaid
+    );
+
+    // Settle the token transfers in path order, each hop funds the input of the next one
+    let mut amount_in_paid = 0;
+    for (i, (hop, _)) in hops.iter_mut().enumerate() {
+        hop.input_token_account.reload()?;
+        hop.output_token_account.reload()?;
+        let amount_paid =
+            settle_swap_v2(hop, &swap_amounts[i], amount_specified[i], 0, false, None)?;
+        if i == 0 {
+            amount_in_paid = amount_paid;
+        }
+    }
+
+    // Ensure the amount actually paid does not exceed the maximum
+    require_gte!(
+        amount_in_maximum,
+        amount_in_paid,
+        ErrorCode::TooMuchInputPaid
+    );
+
+    // The execution report of every hop, in path order
+    Ok(swap_amounts
+        .iter()
+        .map(|amounts| amounts.swap_result)
+        .collect())
+}
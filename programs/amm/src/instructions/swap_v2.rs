@@ -1,9 +1,10 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::ops::Deref;
 
 use crate::error::ErrorCode;
 use crate::libraries::tick_math;
-use crate::swap::swap_internal;
+use crate::swap::{swap_internal_with_mode, SwapResult};
 use crate::util::*;
 use crate::{states::*, util};
 use anchor_lang::prelude::*;
@@ -69,12 +70,56 @@ pub struct SwapSingleV2<'info> {
     )]
     pub output_vault_mint: Box<InterfaceAccount<'info, Mint>>,
     // remaining accounts
+    // referrer_token_account: optional, must be the first one, receives the referral fee in input token of `swap_v2_with_referral`
     // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // tick_array_reward_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // tick_array_oracle_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // hook_program: must add account if the pool has a swap hook regardless the sequence,
+    // followed by the accounts passed to the hook
     // tick_array_account_1
     // tick_array_account_2
     // tick_array_account_...
 }
 
+/// Pool side result of a swap, computed before any token is transferred
+pub struct SwapAmountsV2 {
+    /// The amount of token_0 swapped in or out of the pool, excluding transfer fee
+    pub amount_0: u64,
+    /// The amount of token_1 swapped in or out of the pool, excluding transfer fee
+    pub amount_1: u64,
+    /// The specified amount adjusted by the transfer fee, as passed to swap_internal
+    pub amount_calculate_specified: u64,
+    /// The swap direction
+    pub zero_for_one: bool,
+    /// The pool price before the swap
+    pub swap_price_before: u128,
+    /// The execution report of the swap
+    pub swap_result: SwapResult,
+}
+
+/// Splits the optional referrer token account off the front of the remaining accounts.
+/// It is told apart from the tick arrays by being owned by a token program.
+pub fn split_referrer_token_account<'c: 'info, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+) -> Result<(
+    Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    &'c [AccountInfo<'info>],
+)> {
+    match remaining_accounts.split_first() {
+        Some((account_info, rest))
+            if *account_info.owner == Token::id() || *account_info.owner == Token2022::id() =>
+        {
+            Ok((
+                Some(Box::new(InterfaceAccount::<TokenAccount>::try_from(
+                    account_info,
+                )?)),
+                rest,
+            ))
+        }
+        _ => Ok((None, remaining_accounts)),
+    }
+}
+
 /// Performs a single exact input/output swap
 /// if is_base_input = true, return vaule is the max_amount_out, otherwise is min_amount_in
 pub fn exact_internal_v2<'c: 'info, 'info>(
@@ -83,21 +128,67 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
     amount_specified: u64,
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
-) -> Result<u64> {
+    referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    referral_fee_rate: u32,
+) -> Result<(u64, SwapResult)> {
     // invoke_memo_instruction(SWAP_MEMO_MSG, ctx.memo_program.to_account_info())?;
 
+    // The referral fee rate is chosen by the swap, up to the cap of the amm config
+    let referral_fee_rate = match &referrer_token_account {
+        Some(referrer_token_account) => {
+            require_keys_neq!(
+                referrer_token_account.owner,
+                ctx.payer.key(),
+                ErrorCode::ReferrerIsPayer
+            );
+            require_gte!(
+                ctx.amm_config.max_referral_fee_rate,
+                referral_fee_rate,
+                ErrorCode::InvalidReferralFeeRate
+            );
+            referral_fee_rate
+        }
+        None => 0,
+    };
+    let swap_amounts = swap_pool_v2(
+        ctx,
+        remaining_accounts,
+        amount_specified,
+        sqrt_price_limit_x64,
+        is_base_input,
+        referral_fee_rate,
+    )?;
+    let amount = settle_swap_v2(
+        ctx,
+        &swap_amounts,
+        amount_specified,
+        sqrt_price_limit_x64,
+        is_base_input,
+        referrer_token_account,
+    )?;
+    Ok((amount, swap_amounts.swap_result))
+}
+
+/// Runs the swap against the pool state and tick arrays without moving any token.
+/// The returned amounts must be settled with `settle_swap_v2`.
+/// The referral fee, at `referral_fee_rate` of the trade fee, is carved out of the LP fee.
+pub fn swap_pool_v2<'c: 'info, 'info>(
+    ctx: &mut SwapSingleV2<'info>,
+    remaining_accounts: &'c [AccountInfo<'info>],
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    is_base_input: bool,
+    referral_fee_rate: u32,
+) -> Result<SwapAmountsV2> {
     // Get the current block timestamp
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
 
     let amount_0;
     let amount_1;
+    let swap_state;
     let zero_for_one;
     let swap_price_before;
 
-    // Store the input and output token account balances before the swap
-    let input_balance_before = ctx.input_token_account.amount;
-    let output_balance_before = ctx.output_token_account.amount;
-
     // calculate specified amount because the amount includes thransfer_fee as input and without thransfer_fee as output
     let amount_calculate_specified = if is_base_input {
         let transfer_fee =
@@ -110,6 +201,24 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         amount_specified + transfer_fee
     };
 
+    // Let the hook of the pool reject the swap before it runs
+    let (swap_hook, remaining_accounts) =
+        split_swap_hook(ctx.pool_state.load()?.hook_program, remaining_accounts)?;
+    let (tick_array_reward_extensions, remaining_accounts) =
+        split_tick_array_reward_extensions(remaining_accounts);
+    let (tick_array_oracle_extensions, remaining_accounts) =
+        split_tick_array_oracle_extensions(remaining_accounts);
+    if let Some(swap_hook) = &swap_hook {
+        swap_hook.before_swap(BeforeSwapArgs {
+            pool_state: ctx.pool_state.key(),
+            sender: ctx.payer.key(),
+            zero_for_one: ctx.input_vault.mint == ctx.pool_state.load()?.token_mint_0,
+            is_base_input,
+            amount_specified,
+            sqrt_price_limit_x64,
+        })?;
+    }
+
     {
         // Load the pool state and store the initial swap price
         swap_price_before = ctx.pool_state.load()?.sqrt_price_x64;
@@ -137,7 +246,7 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
 
         // Load the tick array bitmap extension if it exists
         let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state.key());
-        for account_info in remaining_accounts.into_iter() {
+        for account_info in remaining_accounts {
             if account_info.key().eq(&tick_array_bitmap_extension_key) {
                 tickarray_bitmap_extension = Some(
                     *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
@@ -149,12 +258,15 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
             tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
         }
 
-        // Perform the swap using the swap_internal function
-        (amount_0, amount_1) = swap_internal(
+        let (mut observation_state, mut observation_extension) =
+            load_observations_mut(ctx.observation_state.as_ref())?;
+        // Perform the swap using the swap_internal_with_mode function
+        (amount_0, amount_1, swap_state) = swap_internal_with_mode(
             &ctx.amm_config,
             pool_state,
             tick_array_states,
-            &mut ctx.observation_state.load_mut()?,
+            &mut observation_state,
+            &mut observation_extension,
             &tickarray_bitmap_extension,
             amount_calculate_specified,
             if sqrt_price_limit_x64 == 0 {
@@ -169,6 +281,24 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
             zero_for_one,
             is_base_input,
             oracle::block_timestamp(),
+            referral_fee_rate,
+            false,
+        )?;
+        cross_tick_array_reward_extensions(
+            ctx.pool_state.key(),
+            pool_state.tick_spacing,
+            &pool_state.extension_reward_growths,
+            &tick_array_reward_extensions,
+            &swap_state.reward_extension_crossed_ticks,
+        )?;
+        cross_tick_array_oracle_extensions(
+            ctx.pool_state.key(),
+            pool_state,
+            &observation_state,
+            &observation_extension,
+            oracle::block_timestamp(),
+            &tick_array_oracle_extensions,
+            &swap_state.oracle_extension_crossed_ticks,
         )?;
 
         #[cfg(feature = "enable-log")]
@@ -186,6 +316,54 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         );
     }
 
+    // Let the hook of the pool reject the swap from its result
+    if let Some(swap_hook) = &swap_hook {
+        let pool_state = ctx.pool_state.load()?;
+        swap_hook.after_swap(AfterSwapArgs {
+            pool_state: ctx.pool_state.key(),
+            sender: ctx.payer.key(),
+            zero_for_one,
+            amount_0,
+            amount_1,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+            tick: pool_state.tick_current,
+        })?;
+    }
+
+    Ok(SwapAmountsV2 {
+        amount_0,
+        amount_1,
+        amount_calculate_specified,
+        zero_for_one,
+        swap_price_before,
+        swap_result: SwapResult::new(zero_for_one, amount_0, amount_1, &swap_state),
+    })
+}
+
+/// Transfers the amounts computed by `swap_pool_v2` between the user and the pool vaults,
+/// and pays the referral fee from the input vault to the referrer
+/// if is_base_input = true, return vaule is the amount received, otherwise is the amount paid
+pub fn settle_swap_v2<'info>(
+    ctx: &mut SwapSingleV2<'info>,
+    swap_amounts: &SwapAmountsV2,
+    amount_specified: u64,
+    sqrt_price_limit_x64: u128,
+    is_base_input: bool,
+    referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+) -> Result<u64> {
+    let SwapAmountsV2 {
+        amount_0,
+        amount_1,
+        amount_calculate_specified,
+        zero_for_one,
+        swap_price_before,
+        ..
+    } = *swap_amounts;
+
+    // Store the input and output token account balances before the swap
+    let input_balance_before = ctx.input_token_account.amount;
+    let output_balance_before = ctx.output_token_account.amount;
+
     // Determine the token accounts and vaults based on the swap direction
     let (token_account_0, token_account_1, vault_0, vault_1, vault_0_mint, vault_1_mint) =
         if zero_for_one {
@@ -301,6 +479,25 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         )?;
     }
 
+    // Pay the referral fee in input token, it was kept out of the LP fee by the swap
+    let referral_fee = swap_amounts.swap_result.referral_fee;
+    if let Some(referrer_token_account) = referrer_token_account {
+        require_keys_eq!(
+            referrer_token_account.mint,
+            ctx.input_vault.mint,
+            ErrorCode::InvalidReferrerTokenAccount
+        );
+        transfer_from_pool_vault_to_user(
+            &ctx.pool_state,
+            &ctx.input_vault,
+            &referrer_token_account,
+            Some(ctx.input_vault_mint.clone()),
+            &ctx.token_program,
+            Some(ctx.token_program_2022.to_account_info()),
+            referral_fee,
+        )?;
+    }
+
     // Reload the token account balances after the transfers
     ctx.output_token_account.reload()?;
     ctx.input_token_account.reload()?;
@@ -319,7 +516,18 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
         zero_for_one,
         sqrt_price_x64: pool_state.sqrt_price_x64,
         liquidity: pool_state.liquidity,
-        tick: pool_state.tick_current
+        tick: pool_state.tick_current,
+        trade_fee_rate: swap_amounts.swap_result.trade_fee_rate,
+        fee_amount: swap_amounts
+            .swap_result
+            .lp_fee
+            .checked_add(swap_amounts.swap_result.protocol_fee)
+            .unwrap()
+            .checked_add(swap_amounts.swap_result.fund_fee)
+            .unwrap()
+            .checked_add(referral_fee)
+            .unwrap(),
+        referral_fee,
     });
 
     // Ensure the swap price has changed as expected
@@ -339,10 +547,11 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
                 require_eq!(amount_specified, transfer_amount_1);
             }
         } else {
+            // the pool must send out the specified amount plus the output transfer fee
             if zero_for_one {
-                require_eq!(amount_specified, transfer_amount_1);
+                require_eq!(amount_calculate_specified, transfer_amount_1);
             } else {
-                require_eq!(amount_specified, transfer_amount_0);
+                require_eq!(amount_calculate_specified, transfer_amount_0);
             }
         }
     }
@@ -361,19 +570,238 @@ pub fn exact_internal_v2<'c: 'info, 'info>(
     }
 }
 
+/// Swaps tokens the pool vaults already hold on behalf of a position, so no token is
+/// transferred. The swap hook of the pool is called and the swap is emitted like any other.
+/// Returns the amount swapped in and the amount swapped out.
+/// Quotes `swap_within_pool` against a copy of the pool without changing any account,
+/// returns the input and output amounts of the swap
+pub fn quote_swap_within_pool<'c: 'info, 'info>(
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+    amm_config: &AmmConfig,
+    observation_state: &AccountLoader<'info, ObservationState>,
+    tick_array_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    tick_array_accounts: &[&'c AccountInfo<'info>],
+    amount_in: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+) -> Result<(u64, u64)> {
+    // The swap loop updates the pool in place, so it runs against a copy
+    let pool_state = Box::new(RefCell::new(*pool_state_loader.load()?));
+    let tickarray_bitmap_extension = match tick_array_bitmap_extension {
+        Some(account_info) => Some(
+            *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                .load()?
+                .deref()),
+        ),
+        None => None,
+    };
+    let (_, tick_array_accounts) = split_tick_array_reward_extensions(tick_array_accounts.to_vec());
+    let (_, tick_array_accounts) = split_tick_array_oracle_extensions(tick_array_accounts);
+    let tick_array_states = &mut VecDeque::new();
+    for account_info in tick_array_accounts {
+        tick_array_states.push_back(AccountLoad::load_data_mut_unchecked(account_info)?);
+    }
+    let (mut observation_state, mut observation_extension) =
+        load_observations_mut(observation_state.as_ref())?;
+    let (amount_0, amount_1, _) = swap_internal_with_mode(
+        amm_config,
+        &mut pool_state.borrow_mut(),
+        tick_array_states,
+        &mut observation_state,
+        &mut observation_extension,
+        &tickarray_bitmap_extension,
+        amount_in,
+        if sqrt_price_limit_x64 == 0 {
+            if zero_for_one {
+                tick_math::MIN_SQRT_PRICE_X64 + 1
+            } else {
+                tick_math::MAX_SQRT_PRICE_X64 - 1
+            }
+        } else {
+            sqrt_price_limit_x64
+        },
+        zero_for_one,
+        true,
+        oracle::block_timestamp(),
+        0,
+        true,
+    )?;
+    Ok(if zero_for_one {
+        (amount_0, amount_1)
+    } else {
+        (amount_1, amount_0)
+    })
+}
+
+pub fn swap_within_pool<'c: 'info, 'info>(
+    pool_state_loader: &AccountLoader<'info, PoolState>,
+    amm_config: &AmmConfig,
+    observation_state: &AccountLoader<'info, ObservationState>,
+    sender: &Signer<'info>,
+    token_account_0: &InterfaceAccount<'info, TokenAccount>,
+    token_account_1: &InterfaceAccount<'info, TokenAccount>,
+    swap_hook: &Option<SwapHook<'info>>,
+    tick_array_bitmap_extension: Option<&'c AccountInfo<'info>>,
+    tick_array_accounts: Vec<&'c AccountInfo<'info>>,
+    amount_in: u64,
+    sqrt_price_limit_x64: u128,
+    zero_for_one: bool,
+) -> Result<(u64, u64)> {
+    if let Some(swap_hook) = swap_hook {
+        swap_hook.before_swap(BeforeSwapArgs {
+            pool_state: pool_state_loader.key(),
+            sender: sender.key(),
+            zero_for_one,
+            is_base_input: true,
+            amount_specified: amount_in,
+            sqrt_price_limit_x64,
+        })?;
+    }
+
+    let (amount_0, amount_1, swap_state);
+    {
+        let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+        let pool_state = &mut pool_state_loader.load_mut()?;
+        require_gt!(block_timestamp, pool_state.open_time);
+        let swap_price_before = pool_state.sqrt_price_x64;
+
+        let tickarray_bitmap_extension = match tick_array_bitmap_extension {
+            Some(account_info) => Some(
+                *(AccountLoader::<TickArrayBitmapExtension>::try_from(account_info)?
+                    .load()?
+                    .deref()),
+            ),
+            None => None,
+        };
+        let (tick_array_reward_extensions, tick_array_accounts) =
+            split_tick_array_reward_extensions(tick_array_accounts);
+        let (tick_array_oracle_extensions, tick_array_accounts) =
+            split_tick_array_oracle_extensions(tick_array_accounts);
+        let tick_array_states = &mut VecDeque::new();
+        for account_info in tick_array_accounts {
+            tick_array_states.push_back(AccountLoad::load_data_mut(account_info)?);
+        }
+        let (mut observation_state, mut observation_extension) =
+            load_observations_mut(observation_state.as_ref())?;
+        (amount_0, amount_1, swap_state) = swap_internal_with_mode(
+            amm_config,
+            pool_state,
+            tick_array_states,
+            &mut observation_state,
+            &mut observation_extension,
+            &tickarray_bitmap_extension,
+            amount_in,
+            if sqrt_price_limit_x64 == 0 {
+                if zero_for_one {
+                    tick_math::MIN_SQRT_PRICE_X64 + 1
+                } else {
+                    tick_math::MAX_SQRT_PRICE_X64 - 1
+                }
+            } else {
+                sqrt_price_limit_x64
+            },
+            zero_for_one,
+            true,
+            oracle::block_timestamp(),
+            0,
+            false,
+        )?;
+        cross_tick_array_reward_extensions(
+            pool_state_loader.key(),
+            pool_state.tick_spacing,
+            &pool_state.extension_reward_growths,
+            &tick_array_reward_extensions,
+            &swap_state.reward_extension_crossed_ticks,
+        )?;
+        cross_tick_array_oracle_extensions(
+            pool_state_loader.key(),
+            pool_state,
+            &observation_state,
+            &observation_extension,
+            oracle::block_timestamp(),
+            &tick_array_oracle_extensions,
+            &swap_state.oracle_extension_crossed_ticks,
+        )?;
+        require!(
+            amount_0 != 0 && amount_1 != 0,
+            ErrorCode::TooSmallInputOrOutputAmount
+        );
+        if zero_for_one {
+            require_gt!(swap_price_before, pool_state.sqrt_price_x64);
+        } else {
+            require_gt!(pool_state.sqrt_price_x64, swap_price_before);
+        }
+    }
+
+    let (swap_amount_in, swap_amount_out) = if zero_for_one {
+        (amount_0, amount_1)
+    } else {
+        (amount_1, amount_0)
+    };
+    // Does't allow partial filled without specified limit_price.
+    if sqrt_price_limit_x64 == 0 {
+        require_eq!(amount_in, swap_amount_in);
+    }
+
+    let pool_state = pool_state_loader.load()?;
+    if let Some(swap_hook) = swap_hook {
+        swap_hook.after_swap(AfterSwapArgs {
+            pool_state: pool_state_loader.key(),
+            sender: sender.key(),
+            zero_for_one,
+            amount_0,
+            amount_1,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+            tick: pool_state.tick_current,
+        })?;
+    }
+    let swap_result = SwapResult::new(zero_for_one, amount_0, amount_1, &swap_state);
+    emit!(SwapEvent {
+        pool_state: pool_state_loader.key(),
+        sender: sender.key(),
+        token_account_0: token_account_0.key(),
+        token_account_1: token_account_1.key(),
+        amount_0,
+        transfer_fee_0: 0,
+        amount_1,
+        transfer_fee_1: 0,
+        zero_for_one,
+        sqrt_price_x64: pool_state.sqrt_price_x64,
+        liquidity: pool_state.liquidity,
+        tick: pool_state.tick_current,
+        trade_fee_rate: swap_result.trade_fee_rate,
+        fee_amount: swap_result
+            .lp_fee
+            .checked_add(swap_result.protocol_fee)
+            .unwrap()
+            .checked_add(swap_result.fund_fee)
+            .unwrap()
+            .checked_add(swap_result.referral_fee)
+            .unwrap(),
+        referral_fee: swap_result.referral_fee,
+    });
+
+    Ok((swap_amount_in, swap_amount_out))
+}
+
 pub fn swap_v2<'a, 'b, 'c: 'info, 'info>(
     ctx: Context<'a, 'b, 'c, 'info, SwapSingleV2<'info>>,
     amount: u64,
     other_amount_threshold: u64,
     sqrt_price_limit_x64: u128,
     is_base_input: bool,
-) -> Result<()> {
-    let amount_result = exact_internal_v2(
+    referral_fee_rate: u32,
+) -> Result<SwapResult> {
+    let (referrer_token_account, remaining_accounts) =
+        split_referrer_token_account(ctx.remaining_accounts)?;
+    let (amount_result, swap_result) = exact_internal_v2(
         ctx.accounts,
-        ctx.remaining_accounts,
+        remaining_accounts,
         amount,
         sqrt_price_limit_x64,
         is_base_input,
+        referrer_token_account,
+        referral_fee_rate,
     )?;
     if is_base_input {
         require_gte!(
@@ -389,5 +817,5 @@ pub fn swap_v2<'a, 'b, 'c: 'info, 'info>(
         );
     }
 
-    Ok(())
+    Ok(swap_result)
 }
@@ -0,0 +1,68 @@
+use super::update_position;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct UpdatePositionBoost<'info> {
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The position to reprice the boosted liquidity of
+    #[account(mut, constraint = protocol_position.pool_id == pool_state.key())]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+}
+
+pub fn update_position_boost(ctx: Context<UpdatePositionBoost>) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let protocol_position = ctx.accounts.protocol_position.as_mut();
+    let tick_spacing = pool_state.tick_spacing;
+
+    let mut tick_lower_state = *ctx
+        .accounts
+        .tick_array_lower
+        .load_mut()?
+        .get_tick_state_mut(protocol_position.tick_lower_index, tick_spacing)?;
+    let mut tick_upper_state = *ctx
+        .accounts
+        .tick_array_upper
+        .load_mut()?
+        .get_tick_state_mut(protocol_position.tick_upper_index, tick_spacing)?;
+
+    // Accrues the boosted rewards at the old boost rate, then reprices the boosted liquidity of
+    // the position at the current boost of the pool
+    update_position(
+        0,
+        &mut pool_state,
+        protocol_position,
+        &mut tick_lower_state,
+        &mut tick_upper_state,
+        Clock::get()?.unix_timestamp as u64,
+    )?;
+
+    ctx.accounts
+        .tick_array_lower
+        .load_mut()?
+        .update_tick_state(
+            protocol_position.tick_lower_index,
+            tick_spacing,
+            tick_lower_state,
+        )?;
+    ctx.accounts
+        .tick_array_upper
+        .load_mut()?
+        .update_tick_state(
+            protocol_position.tick_upper_index,
+            tick_spacing,
+            tick_upper_state,
+        )?;
+
+    Ok(())
+}
@@ -0,0 +1,883 @@
+use super::{calculate_latest_token_fees, mint_liquidity, open_position, swap_within_pool};
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, sqrt_price_math, tick_math};
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+/// The search of the zap swap amount stops once it is known within this fraction of the input
+pub const ZAP_SWAP_AMOUNT_PRECISION: u64 = 1_000_000;
+
+#[derive(Accounts)]
+#[instruction(tick_lower_index: i32, tick_upper_index: i32,tick_array_lower_start_index:i32,tick_array_upper_start_index:i32)]
+pub struct ZapInOpenPositionV2<'info> {
+    /// Pays to mint the position
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Receives the position NFT
+    pub position_nft_owner: UncheckedAccount<'info>,
+
+    /// Unique token mint address
+    #[account(
+        init,
+        mint::decimals = 0,
+        mint::authority = pool_state.key(),
+        payer = payer,
+        mint::token_program = token_program,
+    )]
+    pub position_nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token account where position NFT will be minted
+    #[account(
+        init,
+        associated_token::mint = position_nft_mint,
+        associated_token::authority = position_nft_owner,
+        payer = payer,
+        token::token_program = token_program,
+    )]
+    pub position_nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// To store metaplex metadata
+    /// CHECK: Safety check performed inside function body
+    #[account(mut)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// The amm config of the pool, read by the swap
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// Add liquidity for this pool
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle observations of the pool, written by the swap
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// Store the information of market marking in range
+    #[account(
+        init_if_needed,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_lower_index.to_be_bytes(),
+            &tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = ProtocolPositionState::LEN
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// CHECK: Account to mark the lower tick as initialized
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_lower_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub tick_array_lower: UncheckedAccount<'info>,
+
+    /// CHECK:Account to store data for the position's upper tick
+    #[account(
+        mut,
+        seeds = [
+            TICK_ARRAY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &tick_array_upper_start_index.to_be_bytes(),
+        ],
+        bump,
+    )]
+    pub tick_array_upper: UncheckedAccount<'info>,
+
+    /// personal position state
+    #[account(
+        init,
+        seeds = [POSITION_SEED.as_bytes(), position_nft_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = PersonalPositionState::LEN
+    )]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The token_0 account of the payer, spent if it is the input and refunded the leftover
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token_1 account of the payer, spent if it is the input and refunded the leftover
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Sysvar for token mint and ATA creation
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Program to create the position manager state account
+    pub system_program: Program<'info, System>,
+
+    /// Program to create mint account and mint tokens
+    pub token_program: Program<'info, Token>,
+    /// Program to create an ATA for receiving position NFT
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Program to create NFT metadata
+    /// CHECK: Metadata program address constraint applied
+    pub metadata_program: Program<'info, Metadata>,
+    /// Program to create mint account and mint tokens
+    pub token_program_2022: Program<'info, Token2022>,
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must be the first one if the position needs it
+    // the swap hook program of the pool and its accounts, if the pool has one
+    // the tick arrays crossed by the swap, in the swap direction
+}
+
+#[derive(Accounts)]
+pub struct ZapInIncreaseLiquidityV2<'info> {
+    /// Pays to mint the position
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for nft
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The amm config of the pool, read by the swap
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle observations of the pool, written by the swap
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Increase liquidity for this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The token_0 account of the payer, spent if it is the input and refunded the leftover
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The token_1 account of the payer, spent if it is the input and refunded the leftover
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Program to create mint account and mint tokens
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+            address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+            address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // tick_array_reward_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // tick_array_oracle_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // the swap hook program of the pool and its accounts, if the pool has one
+    // the tick arrays crossed by the swap, in the swap direction
+}
+
+pub fn zap_in_open_position_v2<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ZapInOpenPositionV2<'info>>,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+    tick_array_lower_start_index: i32,
+    tick_array_upper_start_index: i32,
+    with_matedata: bool,
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+) -> Result<()> {
+    // Open an empty position, then zap into it
+    open_position(
+        &ctx.accounts.payer,
+        &ctx.accounts.position_nft_owner,
+        &ctx.accounts.position_nft_mint,
+        &ctx.accounts.position_nft_account,
+        &ctx.accounts.metadata_account,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.token_account_0,
+        &ctx.accounts.token_account_1,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.rent,
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.metadata_program,
+        Some(ctx.accounts.token_program_2022.clone()),
+        Some(ctx.accounts.vault_0_mint.clone()),
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.remaining_accounts,
+        ctx.bumps.protocol_position,
+        ctx.bumps.personal_position,
+        0,
+        0,
+        0,
+        tick_lower_index,
+        tick_upper_index,
+        tick_array_lower_start_index,
+        tick_array_upper_start_index,
+        with_matedata,
+        None,
+    )?;
+
+    zap_in(
+        &ctx.accounts.payer,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.tick_array_lower,
+        &ctx.accounts.tick_array_upper,
+        &ctx.accounts.token_account_0,
+        &ctx.accounts.token_account_1,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.remaining_accounts,
+        amount_in,
+        zero_for_one,
+        liquidity_min,
+    )
+}
+
+pub fn zap_in_increase_liquidity_v2<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ZapInIncreaseLiquidityV2<'info>>,
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+) -> Result<()> {
+    zap_in(
+        &ctx.accounts.nft_owner,
+        &ctx.accounts.pool_state,
+        &ctx.accounts.amm_config,
+        &ctx.accounts.observation_state,
+        &mut ctx.accounts.protocol_position,
+        &mut ctx.accounts.personal_position,
+        &ctx.accounts.tick_array_lower.to_account_info(),
+        &ctx.accounts.tick_array_upper.to_account_info(),
+        &ctx.accounts.token_account_0,
+        &ctx.accounts.token_account_1,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_program_2022,
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        &ctx.remaining_accounts,
+        amount_in,
+        zero_for_one,
+        liquidity_min,
+    )
+}
+
+/// Deposits a single token into the pool, swaps the part of it that makes the rest fit the
+/// ratio of the position range, adds the liquidity to the position and refunds the leftover
+pub fn zap_in<'b, 'c: 'info, 'info>(
+    payer: &'b Signer<'info>,
+    pool_state_loader: &'b AccountLoader<'info, PoolState>,
+    amm_config: &'b AmmConfig,
+    observation_state: &'b AccountLoader<'info, ObservationState>,
+    protocol_position: &'b mut ProtocolPositionState,
+    personal_position: &'b mut PersonalPositionState,
+    tick_array_lower: &'b AccountInfo<'info>,
+    tick_array_upper: &'b AccountInfo<'info>,
+    token_account_0: &'b Box<InterfaceAccount<'info, TokenAccount>>,
+    token_account_1: &'b Box<InterfaceAccount<'info, TokenAccount>>,
+    token_vault_0: &'b Box<InterfaceAccount<'info, TokenAccount>>,
+    token_vault_1: &'b Box<InterfaceAccount<'info, TokenAccount>>,
+    token_program: &'b Program<'info, Token>,
+    token_program_2022: &'b Program<'info, Token2022>,
+    vault_0_mint: &'b Box<InterfaceAccount<'info, Mint>>,
+    vault_1_mint: &'b Box<InterfaceAccount<'info, Mint>>,
+    remaining_accounts: &'c [AccountInfo<'info>],
+    amount_in: u64,
+    zero_for_one: bool,
+    liquidity_min: u128,
+) -> Result<()> {
+    let tick_lower_index = personal_position.tick_lower_index;
+    let tick_upper_index = personal_position.tick_upper_index;
+    let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(tick_lower_index)?;
+    let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(tick_upper_index)?;
+
+    let (swap_hook, remaining_accounts) =
+        split_swap_hook(pool_state_loader.load()?.hook_program, remaining_accounts)?;
+    let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state_loader.key());
+    let tick_array_bitmap_extension = remaining_accounts
+        .iter()
+        .find(|account_info| account_info.key() == tick_array_bitmap_extension_key)
+        .copied();
+    let swap_tick_array_accounts: Vec<&AccountInfo> = remaining_accounts
+        .into_iter()
+        .filter(|account_info| account_info.key() != tick_array_bitmap_extension_key)
+        .collect();
+
+    {
+        let pool_state = pool_state_loader.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::OpenPositionOrIncreaseLiquidity) {
+            return err!(ErrorCode::NotApproved);
+        }
+        if pool_state.is_overflow_default_tickarray_bitmap(vec![tick_lower_index, tick_upper_index])
+        {
+            require!(
+                tick_array_bitmap_extension.is_some(),
+                ErrorCode::MissingTickArrayBitmapExtensionAccount
+            );
+        }
+    }
+
+    // Deposit the whole input, the vault receives it without the transfer fee
+    let (input_token_account, input_vault, input_vault_mint) = if zero_for_one {
+        (token_account_0, token_vault_0, vault_0_mint)
+    } else {
+        (token_account_1, token_vault_1, vault_1_mint)
+    };
+    let input_transfer_fee = get_transfer_fee(input_vault_mint.clone(), amount_in)?;
+    transfer_from_user_to_pool_vault(
+        payer,
+        input_token_account,
+        input_vault,
+        Some(input_vault_mint.clone()),
+        token_program,
+        Some(token_program_2022.to_account_info()),
+        amount_in,
+    )?;
+    let amount_received = amount_in - input_transfer_fee;
+
+    let swap_amount = {
+        let pool_state = pool_state_loader.load()?;
+        let (observation_state, observation_extension) =
+            load_observations_mut(observation_state.as_ref())?;
+        let trade_fee_rate = pool_state.get_trade_fee_rate(
+            pool_state.get_fee_rates(amm_config).0,
+            &observation_state,
+            &observation_extension,
+            oracle::block_timestamp(),
+        );
+        get_zap_swap_amount(
+            pool_state.sqrt_price_x64,
+            pool_state.liquidity,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_received,
+            zero_for_one,
+            trade_fee_rate,
+        )
+    };
+    let (swap_amount_in, swap_amount_out) = if swap_amount > 0 {
+        swap_within_pool(
+            pool_state_loader,
+            amm_config,
+            observation_state,
+            payer,
+            token_account_0,
+            token_account_1,
+            &swap_hook,
+            tick_array_bitmap_extension,
+            swap_tick_array_accounts,
+            swap_amount,
+            0,
+            zero_for_one,
+        )?
+    } else {
+        (0, 0)
+    };
+    let (available_0, available_1) = if zero_for_one {
+        (amount_received - swap_amount_in, swap_amount_out)
+    } else {
+        (swap_amount_out, amount_received - swap_amount_in)
+    };
+
+    // The rewards of the reward extension must be accrued over the liquidity before it changes
+    personal_position.check_reward_extension_updated(Clock::get()?.unix_timestamp as u64)?;
+    let (liquidity, amount_0, amount_1);
+    {
+        let pool_state = &mut pool_state_loader.load_mut()?;
+
+        // Keep one unit of each token as a margin for the amounts being rounded up
+        liquidity = liquidity_math::get_liquidity_from_amounts(
+            pool_state.sqrt_price_x64,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            available_0.saturating_sub(1),
+            available_1.saturating_sub(1),
+        );
+        require!(liquidity > 0, ErrorCode::TooSmallInputOrOutputAmount);
+        require_gte!(liquidity, liquidity_min, ErrorCode::PriceSlippageCheck);
+
+        let pool_liquidity_before = pool_state.liquidity;
+        (amount_0, amount_1) = mint_liquidity(
+            pool_state,
+            &AccountLoad::<TickArrayState>::try_from(tick_array_lower)?,
+            &AccountLoad::<TickArrayState>::try_from(tick_array_upper)?,
+            protocol_position,
+            tick_array_bitmap_extension,
+            liquidity,
+            tick_lower_index,
+            tick_upper_index,
+        )?;
+        require_gte!(available_0, amount_0, ErrorCode::PriceSlippageCheck);
+        require_gte!(available_1, amount_1, ErrorCode::PriceSlippageCheck);
+
+        emit!(LiquidityChangeEvent {
+            pool_state: pool_state.key(),
+            tick: pool_state.tick_current,
+            tick_lower: tick_lower_index,
+            tick_upper: tick_upper_index,
+            liquidity_before: pool_liquidity_before,
+            liquidity_after: pool_state.liquidity,
+        });
+    }
+
+    personal_position.token_fees_owed_0 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_0,
+        personal_position.fee_growth_inside_0_last_x64,
+        protocol_position.fee_growth_inside_0_last_x64,
+        personal_position.liquidity,
+    );
+    personal_position.token_fees_owed_1 = calculate_latest_token_fees(
+        personal_position.token_fees_owed_1,
+        personal_position.fee_growth_inside_1_last_x64,
+        protocol_position.fee_growth_inside_1_last_x64,
+        personal_position.liquidity,
+    );
+    personal_position.fee_growth_inside_0_last_x64 = protocol_position.fee_growth_inside_0_last_x64;
+    personal_position.fee_growth_inside_1_last_x64 = protocol_position.fee_growth_inside_1_last_x64;
+    // update rewards, must update before increase liquidity
+    personal_position.update_rewards(protocol_position.reward_growth_inside, true)?;
+    personal_position.liquidity = personal_position.liquidity.checked_add(liquidity).unwrap();
+
+    // Send back what does not fit the range
+    let refund_amount_0 = available_0 - amount_0;
+    let refund_amount_1 = available_1 - amount_1;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        token_vault_0,
+        token_account_0,
+        Some(vault_0_mint.clone()),
+        token_program,
+        Some(token_program_2022.to_account_info()),
+        refund_amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        token_vault_1,
+        token_account_1,
+        Some(vault_1_mint.clone()),
+        token_program,
+        Some(token_program_2022.to_account_info()),
+        refund_amount_1,
+    )?;
+
+    let (amount_0_transfer_fee, amount_1_transfer_fee) = if zero_for_one {
+        (input_transfer_fee, 0)
+    } else {
+        (0, input_transfer_fee)
+    };
+    emit!(IncreaseLiquidityEvent {
+        position_nft_mint: personal_position.nft_mint,
+        liquidity,
+        amount_0,
+        amount_1,
+        amount_0_transfer_fee,
+        amount_1_transfer_fee
+    });
+    emit!(ZapInEvent {
+        position_nft_mint: personal_position.nft_mint,
+        zero_for_one,
+        amount_in,
+        swap_amount_in,
+        swap_amount_out,
+        liquidity,
+        refund_amount_0,
+        refund_amount_1,
+    });
+
+    Ok(())
+}
+
+/// Returns the amount of the input token to swap so that the input left and the output
+/// received fit the ratio of the range at the price after the swap.
+/// The swap is simulated against the current liquidity of the pool only, what does not fit
+/// once ticks are crossed is refunded.
+pub fn get_zap_swap_amount(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    amount_in: u64,
+    zero_for_one: bool,
+    trade_fee_rate: u32,
+) -> u64 {
+    if liquidity == 0 || amount_in == 0 {
+        return 0;
+    }
+    // Find the largest swap amount after which the input left is still in excess
+    let precision = amount_in / ZAP_SWAP_AMOUNT_PRECISION;
+    let (mut low, mut high) = (0, amount_in);
+    while high - low > precision {
+        let mid = high - (high - low) / 2;
+        if is_zap_input_in_excess(
+            sqrt_price_x64,
+            liquidity,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in,
+            mid,
+            zero_for_one,
+            trade_fee_rate,
+        ) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// Whether, after swapping `swap_amount` of `amount_in`, the input left supports more
+/// liquidity in the range than the output received
+fn is_zap_input_in_excess(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    amount_in: u64,
+    swap_amount: u64,
+    zero_for_one: bool,
+    trade_fee_rate: u32,
+) -> bool {
+    let swap_amount_less_fee = (u128::from(swap_amount)
+        * u128::from(FEE_RATE_DENOMINATOR_VALUE - trade_fee_rate)
+        / u128::from(FEE_RATE_DENOMINATOR_VALUE)) as u64;
+    // Past the far bound of the range only the input token is needed. Checking it first keeps
+    // the next price within the range, where the price math cannot overflow.
+    let amount_to_far_bound = if zero_for_one {
+        if sqrt_price_x64 <= sqrt_price_lower_x64 {
+            return false;
+        }
+        liquidity_math::get_delta_amount_0_unsigned(
+            sqrt_price_lower_x64,
+            sqrt_price_x64,
+            liquidity,
+            true,
+        )
+    } else {
+        if sqrt_price_x64 >= sqrt_price_upper_x64 {
+            return false;
+        }
+        liquidity_math::get_delta_amount_1_unsigned(
+            sqrt_price_x64,
+            sqrt_price_upper_x64,
+            liquidity,
+            true,
+        )
+    };
+    if matches!(amount_to_far_bound, Ok(amount) if swap_amount_less_fee >= amount) {
+        return false;
+    }
+    let sqrt_price_next_x64 = sqrt_price_math::get_next_sqrt_price_from_input(
+        sqrt_price_x64,
+        liquidity,
+        swap_amount_less_fee,
+        zero_for_one,
+    );
+    let amount_left = amount_in - swap_amount;
+    if zero_for_one {
+        // Below the range only token_0 is needed, above it only token_1
+        if sqrt_price_next_x64 <= sqrt_price_lower_x64 {
+            return false;
+        }
+        if sqrt_price_next_x64 >= sqrt_price_upper_x64 {
+            return true;
+        }
+        let amount_out = match liquidity_math::get_delta_amount_1_unsigned(
+            sqrt_price_next_x64,
+            sqrt_price_x64,
+            liquidity,
+            false,
+        ) {
+            Ok(amount_out) => amount_out,
+            Err(_) => return false,
+        };
+        liquidity_math::get_liquidity_from_amount_0(
+            sqrt_price_next_x64,
+            sqrt_price_upper_x64,
+            amount_left,
+        ) > liquidity_math::get_liquidity_from_amount_1(
+            sqrt_price_lower_x64,
+            sqrt_price_next_x64,
+            amount_out,
+        )
+    } else {
+        // Above the range only token_1 is needed, below it only token_0
+        if sqrt_price_next_x64 >= sqrt_price_upper_x64 {
+            return false;
+        }
+        if sqrt_price_next_x64 <= sqrt_price_lower_x64 {
+            return true;
+        }
+        let amount_out = match liquidity_math::get_delta_amount_0_unsigned(
+            sqrt_price_x64,
+            sqrt_price_next_x64,
+            liquidity,
+            false,
+        ) {
+            Ok(amount_out) => amount_out,
+            Err(_) => return false,
+        };
+        liquidity_math::get_liquidity_from_amount_1(
+            sqrt_price_lower_x64,
+            sqrt_price_next_x64,
+            amount_left,
+        ) > liquidity_math::get_liquidity_from_amount_0(
+            sqrt_price_next_x64,
+            sqrt_price_upper_x64,
+            amount_out,
+        )
+    }
+}
+
+#[cfg(test)]
+mod zap_in_test {
+    use super::*;
+
+    #[test]
+    fn get_zap_swap_amount_test() {
+        let liquidity = 1_000_000_000_000u128;
+        let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(-1000).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(1000).unwrap();
+        let amount_in = 1_000_000u64;
+
+        for zero_for_one in [true, false] {
+            // a symmetric range around the price takes about half of each token
+            let swap_amount = get_zap_swap_amount(
+                sqrt_price_x64,
+                liquidity,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in,
+                zero_for_one,
+                0,
+            );
+            assert!(swap_amount > 499_000 && swap_amount < 501_000);
+
+            // after the swap the tokens fit the range
+            let sqrt_price_next_x64 = sqrt_price_math::get_next_sqrt_price_from_input(
+                sqrt_price_x64,
+                liquidity,
+                swap_amount,
+                zero_for_one,
+            );
+            let (amount_0, amount_1) = if zero_for_one {
+                (
+                    amount_in - swap_amount,
+                    liquidity_math::get_delta_amount_1_unsigned(
+                        sqrt_price_next_x64,
+                        sqrt_price_x64,
+                        liquidity,
+                        false,
+                    )
+                    .unwrap(),
+                )
+            } else {
+                (
+                    liquidity_math::get_delta_amount_0_unsigned(
+                        sqrt_price_x64,
+                        sqrt_price_next_x64,
+                        liquidity,
+                        false,
+                    )
+                    .unwrap(),
+                    amount_in - swap_amount,
+                )
+            };
+            let liquidity_0 = liquidity_math::get_liquidity_from_amount_0(
+                sqrt_price_next_x64,
+                sqrt_price_upper_x64,
+                amount_0,
+            );
+            let liquidity_1 = liquidity_math::get_liquidity_from_amount_1(
+                sqrt_price_lower_x64,
+                sqrt_price_next_x64,
+                amount_1,
+            );
+            let diff = liquidity_0.abs_diff(liquidity_1);
+            assert!(diff * 1000 < liquidity_0);
+
+            // the swap fee asks to swap a bit more
+            assert!(
+                get_zap_swap_amount(
+                    sqrt_price_x64,
+                    liquidity,
+                    sqrt_price_lower_x64,
+                    sqrt_price_upper_x64,
+                    amount_in,
+                    zero_for_one,
+                    3000,
+                ) > swap_amount
+            );
+        }
+
+        // a range above the price only takes token_0
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(100).unwrap();
+        assert_eq!(
+            get_zap_swap_amount(
+                sqrt_price_x64,
+                liquidity,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in,
+                true,
+                0,
+            ),
+            0
+        );
+        let swap_amount = get_zap_swap_amount(
+            sqrt_price_x64,
+            liquidity,
+            sqrt_price_lower_x64,
+            sqrt_price_upper_x64,
+            amount_in,
+            false,
+            0,
+        );
+        assert!(swap_amount >= amount_in - amount_in / ZAP_SWAP_AMOUNT_PRECISION);
+
+        // nothing to swap against
+        assert_eq!(
+            get_zap_swap_amount(
+                sqrt_price_x64,
+                0,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                amount_in,
+                true,
+                0,
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn get_zap_swap_amount_overflow_test() {
+        // the input could move the price of a thin pool far past the range and out of bounds
+        let sqrt_price_x64 = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(-1000).unwrap();
+        let sqrt_price_upper_x64 = tick_math::get_sqrt_price_at_tick(1000).unwrap();
+        for zero_for_one in [true, false] {
+            let swap_amount = get_zap_swap_amount(
+                sqrt_price_x64,
+                1,
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                u64::MAX,
+                zero_for_one,
+                0,
+            );
+            assert!(swap_amount < u64::MAX);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use super::{
+    check_unclaimed_fees_and_vault, decrease_liquidity_and_update_position, quote_swap_within_pool,
+    swap_within_pool,
+};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::util::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+use std::ops::DerefMut;
+
+#[derive(Accounts)]
+pub struct ZapOutDecreaseLiquidityV2<'info> {
+    /// The position owner or delegated authority
+    pub nft_owner: Signer<'info>,
+
+    /// The token account for the tokenized position
+    #[account(
+        constraint = nft_account.mint == personal_position.nft_mint,
+        token::token_program = token_program,
+    )]
+    pub nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Decrease liquidity for this position
+    #[account(mut, constraint = personal_position.pool_id == pool_state.key())]
+    pub personal_position: Box<Account<'info, PersonalPositionState>>,
+
+    /// The amm config of the pool, read by the swap
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The oracle observations of the pool, written by the swap
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            &personal_position.tick_lower_index.to_be_bytes(),
+            &personal_position.tick_upper_index.to_be_bytes(),
+        ],
+        bump,
+        constraint = protocol_position.pool_id == pool_state.key(),
+    )]
+    pub protocol_position: Box<Account<'info, ProtocolPositionState>>,
+
+    /// Token_0 vault
+    #[account(
+        mut,
+        constraint = token_vault_0.key() == pool_state.load()?.token_vault_0
+    )]
+    pub token_vault_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token_1 vault
+    #[account(
+        mut,
+        constraint = token_vault_1.key() == pool_state.load()?.token_vault_1
+    )]
+    pub token_vault_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Stores init state for the lower tick
+    #[account(mut, constraint = tick_array_lower.load()?.pool_id == pool_state.key())]
+    pub tick_array_lower: AccountLoader<'info, TickArrayState>,
+
+    /// Stores init state for the upper tick
+    #[account(mut, constraint = tick_array_upper.load()?.pool_id == pool_state.key())]
+    pub tick_array_upper: AccountLoader<'info, TickArrayState>,
+
+    /// The destination token account for token_0, receives the output if token_1 is swapped
+    #[account(
+        mut,
+        token::mint = token_vault_0.mint
+    )]
+    pub recipient_token_account_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The destination token account for token_1, receives the output if token_0 is swapped
+    #[account(
+        mut,
+        token::mint = token_vault_1.mint
+    )]
+    pub recipient_token_account_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program to transfer out tokens
+    pub token_program: Program<'info, Token>,
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of token vault 0
+    #[account(
+        address = token_vault_0.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token vault 1
+    #[account(
+        address = token_vault_1.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    // remaining accounts
+    // tickarray_bitmap_extension: must add account if need regardless the sequence
+    // tick_array_reward_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // tick_array_oracle_extension: must add the ones of the crossed tick arrays that have one, regardless the sequence
+    // the swap hook program of the pool and its accounts, if the pool has one
+    // the tick arrays crossed by the swap, in the swap direction
+}
+
+pub fn zap_out_decrease_liquidity_v2<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, ZapOutDecreaseLiquidityV2<'info>>,
+    liquidity: u128,
+    zero_for_one: bool,
+    sqrt_price_limit_x64: u128,
+    amount_out_min: u64,
+) -> Result<()> {
+    let pool_state_loader = &ctx.accounts.pool_state;
+    let personal_position = &mut ctx.accounts.personal_position;
+    require_gte!(personal_position.liquidity, liquidity);
+
+    let (swap_hook, remaining_accounts) = split_swap_hook(
+        pool_state_loader.load()?.hook_program,
+        ctx.remaining_accounts,
+    )?;
+    let tick_array_bitmap_extension_key = TickArrayBitmapExtension::key(pool_state_loader.key());
+    let tick_array_bitmap_extension = remaining_accounts
+        .iter()
+        .find(|account_info| account_info.key() == tick_array_bitmap_extension_key)
+        .copied();
+    let swap_tick_array_accounts: Vec<&AccountInfo> = remaining_accounts
+        .into_iter()
+        .filter(|account_info| account_info.key() != tick_array_bitmap_extension_key)
+        .collect();
+
+    {
+        let pool_state = pool_state_loader.load()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::DecreaseLiquidity)
+            || !pool_state.get_status_by_bit(PoolStatusBitIndex::CollectFee)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+        if pool_state.is_overflow_default_tickarray_bitmap(vec![
+            ctx.accounts.tick_array_lower.load()?.start_tick_index,
+            ctx.accounts.tick_array_upper.load()?.start_tick_index,
+        ]) {
+            require!(
+                tick_array_bitmap_extension.is_some(),
+                ErrorCode::MissingTickArrayBitmapExtensionAccount
+            );
+        }
+    }
+
+    // Remove the liquidity and take the fees, the tokens stay in the vaults until paid out
+    let (decrease_amount_0, fee_amount_0, decrease_amount_1, fee_amount_1) =
+        decrease_liquidity_and_update_position(
+            pool_state_loader,
+            &mut ctx.accounts.protocol_position,
+            personal_position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+            tick_array_bitmap_extension,
+            liquidity,
+        )?;
+    let available_0 = decrease_amount_0 + fee_amount_0;
+    let available_1 = decrease_amount_1 + fee_amount_1;
+
+    // Swap the unwanted side into the output token. A remainder too small to buy anything
+    // is paid out as is.
+    let amount_to_swap = if zero_for_one {
+        available_0
+    } else {
+        available_1
+    };
+    let is_dust = amount_to_swap > 0
+        && quote_swap_within_pool(
+            pool_state_loader,
+            &ctx.accounts.amm_config,
+            &ctx.accounts.observation_state,
+            tick_array_bitmap_extension,
+            &swap_tick_array_accounts,
+            amount_to_swap,
+            sqrt_price_limit_x64,
+            zero_for_one,
+        )?
+        .1 == 0;
+    let (swap_amount_in, swap_amount_out) = if amount_to_swap > 0 && !is_dust {
+        swap_within_pool(
+            pool_state_loader,
+            &ctx.accounts.amm_config,
+            &ctx.accounts.observation_state,
+            &ctx.accounts.nft_owner,
+            &ctx.accounts.recipient_token_account_0,
+            &ctx.accounts.recipient_token_account_1,
+            &swap_hook,
+            tick_array_bitmap_extension,
+            swap_tick_array_accounts,
+            amount_to_swap,
+            sqrt_price_limit_x64,
+            zero_for_one,
+        )?
+    } else {
+        (0, 0)
+    };
+
+    // Pay out the output token, and what the price limit left unswapped of the other one
+    let (amount_0, amount_1) = if zero_for_one {
+        (available_0 - swap_amount_in, available_1 + swap_amount_out)
+    } else {
+        (available_0 + swap_amount_out, available_1 - swap_amount_in)
+    };
+    let (amount_out, output_vault_mint) = if zero_for_one {
+        (amount_1, &ctx.accounts.vault_1_mint)
+    } else {
+        (amount_0, &ctx.accounts.vault_0_mint)
+    };
+    let transfer_fee = get_transfer_fee(output_vault_mint.clone(), amount_out)?;
+    require_gte!(
+        amount_out - transfer_fee,
+        amount_out_min,
+        ErrorCode::TooLittleOutputReceived
+    );
+
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_0,
+        &ctx.accounts.recipient_token_account_0,
+        Some(ctx.accounts.vault_0_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_0,
+    )?;
+    transfer_from_pool_vault_to_user(
+        pool_state_loader,
+        &ctx.accounts.token_vault_1,
+        &ctx.accounts.recipient_token_account_1,
+        Some(ctx.accounts.vault_1_mint.clone()),
+        &ctx.accounts.token_program,
+        Some(ctx.accounts.token_program_2022.to_account_info()),
+        amount_1,
+    )?;
+    check_unclaimed_fees_and_vault(
+        pool_state_loader,
+        ctx.accounts.token_vault_0.deref_mut(),
+        ctx.accounts.token_vault_1.deref_mut(),
+    )?;
+
+    emit!(ZapOutEvent {
+        position_nft_mint: personal_position.nft_mint,
+        liquidity,
+        decrease_amount_0,
+        decrease_amount_1,
+        fee_amount_0,
+        fee_amount_1,
+        zero_for_one,
+        swap_amount_in,
+        swap_amount_out,
+        amount_out,
+        transfer_fee,
+    });
+
+    Ok(())
+}
@@ -82,7 +82,8 @@ pub mod amm_v3 {
     /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
     /// * `new_owner`- The config's new owner, be set when `param` is 3
     /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
-    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
+    /// * `max_referral_fee_rate`- The new max referral fee rate of amm config, be set when `param` is 5
+    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4 | 5, otherwise will report a error
     ///
     pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u32) -> Result<()> {
         instructions::update_amm_config(ctx, param, value)
@@ -92,7 +93,7 @@ pub mod amm_v3 {
     ///
     /// # Arguments
     ///
-    /// * `ctx`- The context of accounts
+    /// * `ctx`- The context of accounts, an optional swap hook program is the first remaining account
     /// * `sqrt_price_x64` - the initial sqrt price (amount_token_1 / amount_token_0) of the pool as a Q64.64
     ///
     pub fn create_pool(
@@ -114,6 +115,115 @@ pub mod amm_v3 {
         instructions::update_pool_status(ctx, status)
     }
 
+    /// Update the volatility based swap fee of the pool, only admin can update
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `enabled` - Whether swaps are charged the dynamic fee instead of the amm config trade fee
+    /// * `volatility_window` - The seconds of observation history the average tick is taken over
+    /// * `volatility_fee_rate` - The fee rate added for every tick between the current tick and the average tick
+    /// * `min_fee_rate` - The lower bound of the fee rate
+    /// * `max_fee_rate` - The upper bound of the fee rate
+    ///
+    pub fn update_pool_dynamic_fee(
+        ctx: Context<UpdatePoolDynamicFee>,
+        enabled: bool,
+        volatility_window: u32,
+        volatility_fee_rate: u32,
+        min_fee_rate: u32,
+        max_fee_rate: u32,
+    ) -> Result<()> {
+        instructions::update_pool_dynamic_fee(
+            ctx,
+            enabled,
+            volatility_window,
+            volatility_fee_rate,
+            min_fee_rate,
+            max_fee_rate,
+        )
+    }
+
+    /// Update the minimum seconds between two oracle observations of the pool, only admin can update
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update_duration` - The minimum seconds between two observations
+    ///
+    pub fn update_observation_duration(
+        ctx: Context<UpdateObservationDuration>,
+        update_duration: u32,
+    ) -> Result<()> {
+        instructions::update_observation_duration(ctx, update_duration)
+    }
+
+    /// Override the fee rates of the amm config for a pool, only admin or operation owners can update
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `enabled` - Whether the pool uses the given fee rates instead of the amm config ones
+    /// * `trade_fee_rate` - The trade fee rate of the pool, denominated in hundredths of a bip (10^-6)
+    /// * `protocol_fee_rate` - The protocol fee rate of the pool, as a share of the trade fee
+    /// * `fund_fee_rate` - The fund fee rate of the pool, as a share of the trade fee
+    ///
+    pub fn update_pool_fee(
+        ctx: Context<UpdatePoolFee>,
+        enabled: bool,
+        trade_fee_rate: u32,
+        protocol_fee_rate: u32,
+        fund_fee_rate: u32,
+    ) -> Result<()> {
+        instructions::update_pool_fee(
+            ctx,
+            enabled,
+            trade_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+        )
+    }
+
+    /// Update the range tightness boost of the rewards of a pool, only admin can update.
+    /// The boost applies to an existing position on its next liquidity change or
+    /// `update_position_boost`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `boosted_reward_flags` - The bitmask of the rewards emitted over the boosted liquidity,
+    ///   a reward can only be switched before it emits
+    /// * `curve` - The shape of the boost, 0 for inverse to the range width, 1 for linear
+    /// * `reference_tick_width` - The range width in ticks from which the liquidity is not boosted
+    /// * `max_boost_rate` - The boost rate cap, denominated in hundredths of a bip (10^-6), 0 to disable
+    ///
+    pub fn update_pool_reward_boost(
+        ctx: Context<UpdatePoolRewardBoost>,
+        boosted_reward_flags: u8,
+        curve: u8,
+        reference_tick_width: u32,
+        max_boost_rate: u32,
+    ) -> Result<()> {
+        instructions::update_pool_reward_boost(
+            ctx,
+            boosted_reward_flags,
+            curve,
+            reference_tick_width,
+            max_boost_rate,
+        )
+    }
+
+    /// Register the program called before and after every swap of the pool, only admin can update
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the hook program is the first remaining account,
+    /// the hook is removed if none is given
+    ///
+    pub fn update_pool_hook(ctx: Context<UpdatePoolHook>) -> Result<()> {
+        instructions::update_pool_hook(ctx)
+    }
+
     /// Creates an operation account for the program
     ///
     /// # Arguments
@@ -227,6 +337,113 @@ pub mod amm_v3 {
         )
     }
 
+    /// Queue a future emission epoch of a reward, funded up front into the reward vault.
+    /// The reward rolls into the next queued epoch when it becomes due, the later ones are moved
+    /// in by `advance_reward_schedule`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `reward_index` - The index of reward token in the pool.
+    /// * `param` - The open time, end time and emissions of the epoch, it can't open before
+    ///    the end of the last epoch of the reward
+    ///
+    pub fn queue_reward_epoch(
+        ctx: Context<QueueRewardEpoch>,
+        reward_index: u8,
+        param: InitializeRewardParam,
+    ) -> Result<()> {
+        instructions::queue_reward_epoch(ctx, reward_index, param)
+    }
+
+    /// Roll a reward into every queued epoch opened by now and move the next one into the pool,
+    /// can be called for everyone. An epoch moved in after it opened still emits from its opening.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn advance_reward_schedule(ctx: Context<AdvanceRewardSchedule>) -> Result<()> {
+        instructions::advance_reward_schedule(ctx)
+    }
+
+    /// Deposit reward tokens to extend the end time of a running reward at its current
+    /// emission rate, can be called for everyone. A deposit must fund at least `MIN_REWARD_PERIOD`
+    /// of emissions. The deposit is refunded to the funder as far as it is not emitted when the
+    /// reward is cancelled. The reward can't be extended past the opening of its next queued
+    /// epoch, which must be moved in by `advance_reward_schedule` first.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `reward_index` - The index of reward token in the pool.
+    /// * `amount` - The amount of reward token to deposit, at least what the reward emits in
+    /// `MIN_REWARD_PERIOD`
+    ///
+    pub fn fund_reward(ctx: Context<FundReward>, reward_index: u8, amount: u64) -> Result<()> {
+        instructions::fund_reward(ctx, reward_index, amount)
+    }
+
+    /// Stop a reward now along with its queued epochs, only the reward authority or admin can
+    /// cancel. The reward emitted so far stays owed to the positions. What is left of the
+    /// deposits of `fund_reward` is refunded to their funders pro rata, the pending and queued
+    /// epochs to whoever queued them, the rest of the unemitted tokens to the reward authority.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `reward_index` - The index of reward token in the pool.
+    ///
+    pub fn cancel_reward<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CancelReward<'info>>,
+        reward_index: u8,
+    ) -> Result<()> {
+        instructions::cancel_reward(ctx, reward_index)
+    }
+
+    /// Initialize a reward of the reward extension of a pool, which offers up to
+    /// `EXTENSION_REWARD_NUM` rewards on top of its `REWARD_NUM` rewards.
+    /// The reward extension is created along with the first one.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `param` - The open time, end time and emissions of the reward
+    ///
+    pub fn initialize_extension_reward(
+        ctx: Context<InitializeExtensionReward>,
+        param: InitializeRewardParam,
+    ) -> Result<()> {
+        instructions::initialize_extension_reward(ctx, param)
+    }
+
+    /// Initialize the extension reward growths of a tick array, only for the pool owner, admin
+    /// or operation owners. Swaps crossing a tick of the tick array must pass it in from then on.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn initialize_tick_array_reward_extension(
+        ctx: Context<InitializeTickArrayRewardExtension>,
+    ) -> Result<()> {
+        instructions::initialize_tick_array_reward_extension(ctx)
+    }
+
+    /// Initialize the tick cumulatives and seconds outside the ticks of a tick array, only for
+    /// the pool owner, admin or operation owners. Swaps crossing a tick of the tick array must
+    /// pass it in from then on.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn initialize_tick_array_oracle_extension(
+        ctx: Context<InitializeTickArrayOracleExtension>,
+    ) -> Result<()> {
+        instructions::initialize_tick_array_oracle_extension(ctx)
+    }
+
     /// Collect the protocol fee accrued to the pool
     ///
     /// # Arguments
@@ -336,13 +553,86 @@ pub mod amm_v3 {
         )
     }
 
+    /// Same as `open_position_v2`, but fails once the cluster clock has passed `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The unix timestamp after which the transaction is rejected
+    ///
+    pub fn open_position_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, OpenPositionV2<'info>>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        tick_array_lower_start_index: i32,
+        tick_array_upper_start_index: i32,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64,
+        with_matedata: bool,
+        base_flag: Option<bool>,
+        deadline: i64,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        instructions::open_position_v2(
+            ctx,
+            liquidity,
+            amount_0_max,
+            amount_1_max,
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            with_matedata,
+            base_flag,
+        )
+    }
+
+    /// Opens a position with a single token. Part of `amount_in` is swapped in the pool so that
+    /// the rest fits the ratio of the range, then the liquidity is added and the leftover refunded.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `tick_lower_index` - The low boundary of market
+    /// * `tick_upper_index` - The upper boundary of market
+    /// * `tick_array_lower_start_index` - The start index of tick array which include tick low
+    /// * `tick_array_upper_start_index` - The start index of tick array which include tick upper
+    /// * `with_matedata` - The flag indicating whether to create NFT mint metadata
+    /// * `amount_in` - The amount of the input token deposited
+    /// * `zero_for_one` - Whether token_0 is the input, otherwise token_1
+    /// * `liquidity_min` - The minimum liquidity added to the position, for slippage
+    ///
+    pub fn zap_in_open_position_v2<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ZapInOpenPositionV2<'info>>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        tick_array_lower_start_index: i32,
+        tick_array_upper_start_index: i32,
+        with_matedata: bool,
+        amount_in: u64,
+        zero_for_one: bool,
+        liquidity_min: u128,
+    ) -> Result<()> {
+        instructions::zap_in_open_position_v2(
+            ctx,
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            with_matedata,
+            amount_in,
+            zero_for_one,
+            liquidity_min,
+        )
+    }
+
     /// Close a position, the nft mint and nft account
     ///
     /// # Arguments
     ///
     /// * `ctx` - The context of accounts
     ///
-    pub fn close_position<'a, 'b, 'c, 'info>(
+    pub fn close_position<'a, 'b, 'c: 'info, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, ClosePosition<'info>>,
     ) -> Result<()> {
         instructions::close_position(ctx)
@@ -392,6 +682,48 @@ pub mod amm_v3 {
         instructions::increase_liquidity_v2(ctx, liquidity, amount_0_max, amount_1_max, base_flag)
     }
 
+    /// Same as `increase_liquidity_v2`, but fails once the cluster clock has passed `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The unix timestamp after which the transaction is rejected
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn increase_liquidity_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, IncreaseLiquidityV2<'info>>,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64,
+        base_flag: Option<bool>,
+        deadline: i64,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        if liquidity == 0 {
+            assert!(base_flag.is_some());
+        }
+        instructions::increase_liquidity_v2(ctx, liquidity, amount_0_max, amount_1_max, base_flag)
+    }
+
+    /// Increases liquidity of a position with a single token. Part of `amount_in` is swapped in
+    /// the pool so that the rest fits the ratio of the range, and the leftover is refunded.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_in` - The amount of the input token deposited
+    /// * `zero_for_one` - Whether token_0 is the input, otherwise token_1
+    /// * `liquidity_min` - The minimum liquidity added to the position, for slippage
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn zap_in_increase_liquidity_v2<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ZapInIncreaseLiquidityV2<'info>>,
+        amount_in: u64,
+        zero_for_one: bool,
+        liquidity_min: u128,
+    ) -> Result<()> {
+        instructions::zap_in_increase_liquidity_v2(ctx, amount_in, zero_for_one, liquidity_min)
+    }
+
     /// Decreases liquidity with a exist position
     ///
     /// # Arguments
@@ -430,6 +762,327 @@ pub mod amm_v3 {
         instructions::decrease_liquidity_v2(ctx, liquidity, amount_0_min, amount_1_min)
     }
 
+    /// Same as `decrease_liquidity_v2`, but fails once the cluster clock has passed `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The unix timestamp after which the transaction is rejected
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn decrease_liquidity_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, DecreaseLiquidityV2<'info>>,
+        liquidity: u128,
+        amount_0_min: u64,
+        amount_1_min: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        util::check_deadline(deadline)?;
+        instructions::decrease_liquidity_v2(ctx, liquidity, amount_0_min, amount_1_min)
+    }
+
+    /// Decreases liquidity of a position and pays it out as a single token. The other token,
+    /// with the fees, is swapped in the pool into the output token, unless too little of it is
+    /// left to buy anything. The rewards are not collected, they stay owed to the position.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `liquidity` - The amount by which liquidity will be decreased
+    /// * `zero_for_one` - Whether token_0 is swapped into token_1, otherwise token_1 into token_0
+    /// * `sqrt_price_limit_x64` - The Q64.64 sqrt price √P limit of the swap, what is left
+    ///    unswapped at the limit is paid out as is
+    /// * `amount_out_min` - The minimum amount of the output token received, net of transfer fee
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn zap_out_decrease_liquidity_v2<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ZapOutDecreaseLiquidityV2<'info>>,
+        liquidity: u128,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: u128,
+        amount_out_min: u64,
+    ) -> Result<()> {
+        instructions::zap_out_decrease_liquidity_v2(
+            ctx,
+            liquidity,
+            zero_for_one,
+            sqrt_price_limit_x64,
+            amount_out_min,
+        )
+    }
+
+    /// Collects up to the requested amounts of the fees owed to a position, and its rewards
+    /// if the reward accounts are given, to any recipient token accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `amount_0_requested` - The maximum amount of token_0 fee to collect
+    /// * `amount_1_requested` - The maximum amount of token_1 fee to collect
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn collect<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, Collect<'info>>,
+        amount_0_requested: u64,
+        amount_1_requested: u64,
+    ) -> Result<()> {
+        instructions::collect(ctx, amount_0_requested, amount_1_requested)
+    }
+
+    /// Reprices the boosted liquidity of a position at the current reward boost of the pool,
+    /// can be called for everyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    ///
+    pub fn update_position_boost(ctx: Context<UpdatePositionBoost>) -> Result<()> {
+        instructions::update_position_boost(ctx)
+    }
+
+    /// Reinvests the fees owed to a position as liquidity in its own range, at the current
+    /// price. The part of the fees that does not fit the current ratio of the range stays owed.
+    /// Callable by the position owner, or by its compound keeper which takes its tip on the
+    /// reinvested amounts when the tip token accounts are given. Not allowed while the position
+    /// is locked without allowing fee collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    ///
+    pub fn compound_position<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CompoundPosition<'info>>,
+    ) -> Result<()> {
+        instructions::compound_position(ctx)
+    }
+
+    /// Sets the keeper allowed to compound the fees of a position, and the share of the
+    /// compounded fees it is tipped. The default pubkey removes the keeper. The keeper loses
+    /// its approval once the NFT of the position changes hands.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `keeper` - The compound keeper of the position
+    /// * `tip_rate` - The share of the compounded fees paid to the keeper, in hundredths of a bip,
+    /// up to `MAX_COMPOUND_TIP_RATE`
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn set_compound_keeper(
+        ctx: Context<SetCompoundKeeper>,
+        keeper: Pubkey,
+        tip_rate: u32,
+    ) -> Result<()> {
+        instructions::set_compound_keeper(ctx, keeper, tip_rate)
+    }
+
+    /// Moves a position to a new range in one instruction. All its liquidity is removed with
+    /// its fees, part of the tokens is optionally swapped in the pool, and as much as fits the
+    /// new range at the resulting price is added back under the same NFT. The rest is sent to
+    /// the recipient token accounts, the rewards stay owed to the position.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `tick_lower_index` - The low boundary of the new range
+    /// * `tick_upper_index` - The upper boundary of the new range
+    /// * `tick_array_lower_start_index` - The start index of the tick array of the new lower tick
+    /// * `tick_array_upper_start_index` - The start index of the tick array of the new upper tick
+    /// * `swap_amount` - The amount of the input token swapped before adding liquidity, zero for none
+    /// * `zero_for_one` - The swap direction
+    /// * `sqrt_price_limit_x64` - The Q64.64 sqrt price √P limit of the swap
+    /// * `liquidity_min` - The minimum liquidity added to the new range, for slippage
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn rebalance_position<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, RebalancePosition<'info>>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        tick_array_lower_start_index: i32,
+        tick_array_upper_start_index: i32,
+        swap_amount: u64,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: u128,
+        liquidity_min: u128,
+    ) -> Result<()> {
+        instructions::rebalance_position(
+            ctx,
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            swap_amount,
+            zero_for_one,
+            sqrt_price_limit_x64,
+            liquidity_min,
+        )
+    }
+
+    /// Merges positions sharing the pool and range into the first one, summing their liquidity,
+    /// fees and rewards owed. The merged positions are left empty for their owner to close.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts, the merged positions are passed as pairs of
+    /// nft account and personal position in the remaining accounts
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn merge_positions<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MergePositions<'info>>,
+    ) -> Result<()> {
+        instructions::merge_positions(ctx)
+    }
+
+    /// Splits liquidity out of a position into a new position NFT over the same range.
+    /// The fees and rewards owed so far stay with the original position.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `liquidity` - The liquidity moved into the new position
+    /// * `with_matedata` - The flag whether create metadata for the new position NFT
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn split_position<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SplitPosition<'info>>,
+        liquidity: u128,
+        with_matedata: bool,
+    ) -> Result<()> {
+        instructions::split_position(ctx, liquidity, with_matedata)
+    }
+
+    /// Locks the liquidity of a position until the unlock time, it cannot be decreased nor
+    /// the position closed before then. The position can still be transferred, merged into
+    /// and have liquidity added.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `unlock_timestamp` - The time until which the position is locked, at most `MAX_LOCK_DURATION` from now
+    /// * `allow_collect_fee` - Whether the fees of the position can be collected while it is locked
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn lock_position(
+        ctx: Context<LockPosition>,
+        unlock_timestamp: u64,
+        allow_collect_fee: bool,
+    ) -> Result<()> {
+        instructions::lock_position(ctx, unlock_timestamp, allow_collect_fee)
+    }
+
+    /// Pushes back the unlock time of a locked position
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    /// * `unlock_timestamp` - The new unlock time, later than the current one and at most
+    /// `MAX_LOCK_DURATION` from now
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn extend_lock(ctx: Context<LockPosition>, unlock_timestamp: u64) -> Result<()> {
+        instructions::extend_lock(ctx, unlock_timestamp)
+    }
+
+    /// Lets a position earn the rewards of the reward extension of its pool. From then on, its
+    /// liquidity can only change once `update_position_reward_extension` was called in the same transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn initialize_position_reward_extension(
+        ctx: Context<InitializePositionRewardExtension>,
+    ) -> Result<()> {
+        instructions::initialize_position_reward_extension(ctx)
+    }
+
+    /// Accrues the extension rewards of a position up to now, can be called for everyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    ///
+    pub fn update_position_reward_extension(
+        ctx: Context<UpdatePositionRewardExtension>,
+    ) -> Result<()> {
+        instructions::update_position_reward_extension(ctx)
+    }
+
+    /// Collects the extension rewards owed to a position
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` -  The context of accounts
+    ///
+    #[access_control(is_authorized_for_token(&ctx.accounts.nft_owner, &ctx.accounts.nft_account))]
+    pub fn collect_extension_rewards<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CollectExtensionRewards<'info>>,
+    ) -> Result<()> {
+        instructions::collect_extension_rewards(ctx)
+    }
+
+    /// Collect the remaining extension reward token for the reward authority, once the reward
+    /// has ended. The rewards emitted but not claimed yet stay in the vault for the positions.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `reward_index` - the index to the reward of the reward extension
+    ///
+    pub fn collect_remaining_extension_rewards(
+        ctx: Context<CollectRemainingExtensionRewards>,
+        reward_index: u8,
+    ) -> Result<()> {
+        instructions::collect_remaining_extension_rewards(ctx, reward_index)
+    }
+
+    /// Places a limit order, a range one tick spacing wide that is converted fully into the
+    /// other token once the price crosses it. The order does not earn fees or rewards.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `tick_lower_index` - The low boundary of the order, the upper boundary is one tick spacing above
+    /// * `zero_for_one` - true to sell token_0 above the current price, false to sell token_1 below it
+    /// * `amount_in` - The max amount of input token to spend, including the transfer fee
+    ///
+    pub fn place_limit_order<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, PlaceLimitOrder<'info>>,
+        tick_lower_index: i32,
+        zero_for_one: bool,
+        amount_in: u64,
+    ) -> Result<()> {
+        instructions::place_limit_order(ctx, tick_lower_index, zero_for_one, amount_in)
+    }
+
+    /// Cancels a pending limit order and returns its tokens to the owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0_min` - The minimum amount of token_0 to receive
+    /// * `amount_1_min` - The minimum amount of token_1 to receive
+    ///
+    pub fn cancel_limit_order<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CancelLimitOrder<'info>>,
+        amount_0_min: u64,
+        amount_1_min: u64,
+    ) -> Result<()> {
+        instructions::cancel_limit_order(ctx, amount_0_min, amount_1_min)
+    }
+
+    /// Claims the output token of a filled limit order and closes the order
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn claim_limit_order<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ClaimLimitOrder<'info>>,
+    ) -> Result<()> {
+        instructions::claim_limit_order(ctx)
+    }
+
     /// Swaps one token for as much as possible of another token across a single pool
     ///
     /// # Arguments
@@ -446,7 +1099,7 @@ pub mod amm_v3 {
         other_amount_threshold: u64,
         sqrt_price_limit_x64: u128,
         is_base_input: bool,
-    ) -> Result<()> {
+    ) -> Result<SwapResult> {
         instructions::swap(
             ctx,
             amount,
@@ -472,13 +1125,65 @@ pub mod amm_v3 {
         other_amount_threshold: u64,
         sqrt_price_limit_x64: u128,
         is_base_input: bool,
-    ) -> Result<()> {
+    ) -> Result<SwapResult> {
+        instructions::swap_v2(
+            ctx,
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64,
+            is_base_input,
+            0,
+        )
+    }
+
+    /// Same as `swap_v2`, but pays a share of the trade fee to the referrer token account passed
+    /// as the first remaining account
+    ///
+    /// # Arguments
+    ///
+    /// * `referral_fee_rate` - The share of the trade fee paid to the referrer token account,
+    /// up to the max referral fee rate of the amm config
+    ///
+    pub fn swap_v2_with_referral<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapSingleV2<'info>>,
+        amount: u64,
+        other_amount_threshold: u64,
+        sqrt_price_limit_x64: u128,
+        is_base_input: bool,
+        referral_fee_rate: u32,
+    ) -> Result<SwapResult> {
+        instructions::swap_v2(
+            ctx,
+            amount,
+            other_amount_threshold,
+            sqrt_price_limit_x64,
+            is_base_input,
+            referral_fee_rate,
+        )
+    }
+
+    /// Same as `swap_v2`, but fails once the cluster clock has passed `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The unix timestamp after which the transaction is rejected
+    ///
+    pub fn swap_v2_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapSingleV2<'info>>,
+        amount: u64,
+        other_amount_threshold: u64,
+        sqrt_price_limit_x64: u128,
+        is_base_input: bool,
+        deadline: i64,
+    ) -> Result<SwapResult> {
+        util::check_deadline(deadline)?;
         instructions::swap_v2(
             ctx,
             amount,
             other_amount_threshold,
             sqrt_price_limit_x64,
             is_base_input,
+            0,
         )
     }
 
@@ -494,7 +1199,146 @@ pub mod amm_v3 {
         ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
         amount_in: u64,
         amount_out_minimum: u64,
+    ) -> Result<Vec<SwapResult>> {
+        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum, 0)
+    }
+
+    /// Same as `swap_router_base_in`, but pays a share of the trade fee of the first hop to the
+    /// referrer token account passed as the first remaining account
+    ///
+    /// # Arguments
+    ///
+    /// * `referral_fee_rate` - The share of the trade fee of the first hop paid to the referrer
+    /// token account, up to the max referral fee rate of the amm config
+    ///
+    pub fn swap_router_base_in_with_referral<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
+        amount_in: u64,
+        amount_out_minimum: u64,
+        referral_fee_rate: u32,
+    ) -> Result<Vec<SwapResult>> {
+        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum, referral_fee_rate)
+    }
+
+    /// Same as `swap_router_base_in`, but fails once the cluster clock has passed `deadline`
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The unix timestamp after which the transaction is rejected
+    ///
+    pub fn swap_router_base_in_with_deadline<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseIn<'info>>,
+        amount_in: u64,
+        amount_out_minimum: u64,
+        deadline: i64,
+    ) -> Result<Vec<SwapResult>> {
+        util::check_deadline(deadline)?;
+        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum, 0)
+    }
+
+    /// Swap as little as possible of one token for an exact amount of another token across the path provided, base output
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_out` - Token amount to be received from the last pool of the path
+    /// * `amount_in_maximum` - Panic if input amount is above maximum amount. For slippage.
+    ///
+    pub fn swap_router_base_out<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRouterBaseOut<'info>>,
+        amount_out: u64,
+        amount_in_maximum: u64,
+    ) -> Result<Vec<SwapResult>> {
+        instructions::swap_router_base_out(ctx, amount_out, amount_in_maximum)
+    }
+
+    /// Quote a swap across a single pool without writing any account or moving any token
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_specified` - The amount in for base input, or the amount out for base output
+    /// * `sqrt_price_limit_x64` - The Q64.64 sqrt price √P limit, zero means no limit
+    /// * `zero_for_one` - swap token_0 for token_1 or token_1 for token_0
+    /// * `is_base_input` - swap base input or swap base output
+    ///
+    pub fn quote_swap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, QuoteSwap<'info>>,
+        amount_specified: u64,
+        sqrt_price_limit_x64: u128,
+        zero_for_one: bool,
+        is_base_input: bool,
+    ) -> Result<QuoteSwapResult> {
+        instructions::quote_swap(
+            ctx,
+            amount_specified,
+            sqrt_price_limit_x64,
+            zero_for_one,
+            is_base_input,
+        )
+    }
+
+    /// Lend tokens from the pool vaults to a callback program, which must repay them plus
+    /// a fee at the trade fee rate before it returns
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_0` - The amount of token_0 to borrow
+    /// * `amount_1` - The amount of token_1 to borrow
+    /// * `data` - Any data to be passed through to the callback
+    ///
+    pub fn flash<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, Flash<'info>>,
+        amount_0: u64,
+        amount_1: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash(ctx, amount_0, amount_1, data)
+    }
+
+    /// Read the oracle of a pool without writing any account
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `seconds_agos` - From how long ago each tick cumulative and time weighted average should be returned
+    ///
+    pub fn observe(ctx: Context<Observe>, seconds_agos: Vec<u32>) -> Result<ObserveResult> {
+        instructions::observe(ctx, seconds_agos)
+    }
+
+    /// Grow the oracle of a pool to keep more observations, the payer funds the rent.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `observation_cardinality_next` - The number of observations the pool should keep,
+    /// the account can grow by at most 232 observations per call
+    ///
+    pub fn increase_observation_cardinality_next(
+        ctx: Context<IncreaseObservationCardinalityNext>,
+        observation_cardinality_next: u16,
     ) -> Result<()> {
-        instructions::swap_router_base_in(ctx, amount_in, amount_out_minimum)
+        instructions::increase_observation_cardinality_next(ctx, observation_cardinality_next)
+    }
+
+    /// Returns the tick cumulative, the seconds per unit of liquidity as a Q64.64 and the seconds
+    /// the price spent inside a range. Only the difference between two snapshots taken while the
+    /// range ticks stayed initialized is meaningful. The tick arrays of both ticks must have an
+    /// oracle extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `tick_lower_index` - The lower tick of the range, must be initialized
+    /// * `tick_upper_index` - The upper tick of the range, must be initialized
+    ///
+    pub fn snapshot_cumulatives_inside(
+        ctx: Context<SnapshotCumulativesInside>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+    ) -> Result<CumulativesInside> {
+        instructions::snapshot_cumulatives_inside(ctx, tick_lower_index, tick_upper_index)
     }
 }
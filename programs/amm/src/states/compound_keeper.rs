@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// Seed to derive account address and signature
+pub const COMPOUND_KEEPER_SEED: &str = "compound_keeper";
+
+/// The maximum share of the compounded fees a keeper can be tipped, in hundredths of a bip (10^-6)
+pub const MAX_COMPOUND_TIP_RATE: u32 = 100_000;
+
+/// The keeper allowed to compound the fees of a position besides its owner. The approval is
+/// only valid while the NFT of the position stays with the owner who set it.
+///
+/// PDA of `[COMPOUND_KEEPER_SEED, nft_mint]`
+#[account]
+#[derive(Default, Debug)]
+pub struct CompoundKeeperState {
+    /// Bump to identify PDA
+    pub bump: u8,
+
+    /// Mint address of the tokenized position
+    pub nft_mint: Pubkey,
+
+    /// The holder of the position NFT who set the keeper
+    pub owner: Pubkey,
+
+    /// The keeper allowed to compound the fees of the position, the default pubkey for none
+    pub keeper: Pubkey,
+
+    /// The share of the compounded fees paid to the keeper as a tip, in hundredths of a bip (10^-6)
+    pub tip_rate: u32,
+
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 4],
+}
+
+impl CompoundKeeperState {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 32 + 4 + 8 * 4;
+
+    /// Whether `authority` may compound the position as its keeper, the NFT being held by `owner`
+    pub fn is_keeper(&self, nft_mint: Pubkey, owner: Pubkey, authority: Pubkey) -> bool {
+        self.keeper != Pubkey::default()
+            && self.nft_mint == nft_mint
+            && self.owner == owner
+            && self.keeper == authority
+    }
+}
+
+/// Finds the compound keeper state among the remaining accounts of an instruction
+pub fn find_compound_keeper_state<'c: 'info, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+) -> Result<Option<Account<'info, CompoundKeeperState>>> {
+    remaining_accounts
+        .iter()
+        .find(|account_info| {
+            *account_info.owner == crate::id()
+                && account_info
+                    .try_borrow_data()
+                    .map(|data| {
+                        data.len() >= 8 && data[..8] == CompoundKeeperState::discriminator()
+                    })
+                    .unwrap_or(false)
+        })
+        .map(Account::<CompoundKeeperState>::try_from)
+        .transpose()
+}
+
+#[cfg(test)]
+mod compound_keeper_test {
+    use super::*;
+
+    #[test]
+    fn is_keeper_test() {
+        let nft_mint = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let keeper = Pubkey::new_unique();
+        let state = CompoundKeeperState {
+            nft_mint,
+            owner,
+            keeper,
+            tip_rate: 10_000,
+            ..Default::default()
+        };
+        assert!(state.is_keeper(nft_mint, owner, keeper));
+        // the NFT changed hands since the keeper was set
+        assert!(!state.is_keeper(nft_mint, Pubkey::new_unique(), keeper));
+        assert!(!state.is_keeper(nft_mint, owner, Pubkey::new_unique()));
+        assert!(!state.is_keeper(Pubkey::new_unique(), owner, keeper));
+        // the keeper was removed
+        let state = CompoundKeeperState {
+            keeper: Pubkey::default(),
+            ..state
+        };
+        assert!(!state.is_keeper(nft_mint, owner, Pubkey::default()));
+    }
+}
@@ -22,8 +22,8 @@ pub struct AmmConfig {
     pub tick_spacing: u16,
     /// The fund fee, denominated in hundredths of a bip (10^-6)
     pub fund_fee_rate: u32,
-    // padding space for upgrade
-    pub padding_u32: u32,
+    /// The max share of the trade fee a swap can pay to its referrer, denominated in hundredths of a bip (10^-6)
+    pub max_referral_fee_rate: u32,
     pub fund_owner: Pubkey,
     pub padding: [u64; 3],
 }
@@ -56,4 +56,5 @@ pub struct ConfigChangeEvent {
     pub tick_spacing: u16,
     pub fund_fee_rate: u32,
     pub fund_owner: Pubkey,
+    pub max_referral_fee_rate: u32,
 }
@@ -0,0 +1,276 @@
+use crate::error::ErrorCode;
+use crate::libraries::{liquidity_math, tick_math};
+use crate::states::{TickArrayState, TickState};
+use crate::util::get_recent_epoch;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const LIMIT_ORDER_SEED: &str = "limit_order";
+
+/// A range order one tick spacing wide, converted fully into the other token once the price
+/// crosses it. The order's liquidity does not earn trading fees or rewards.
+///
+/// PDA of `[LIMIT_ORDER_SEED, pool, owner, tick_lower_index]`
+#[account]
+#[derive(Default, Debug)]
+pub struct LimitOrderState {
+    /// Bump to identify PDA
+    pub bump: u8,
+
+    /// The ID of the pool the order is placed in
+    pub pool_id: Pubkey,
+
+    /// The owner of the order, the only one able to cancel or claim it
+    pub owner: Pubkey,
+
+    /// The lower bound tick of the order, the upper bound is one tick spacing above
+    pub tick_lower_index: i32,
+
+    /// True if the order sells token_0 for token_1, false if it sells token_1 for token_0
+    pub zero_for_one: bool,
+
+    /// The amount of liquidity owned by this order
+    pub liquidity: u128,
+
+    /// The `order_epoch` of the fill tick when the order was placed,
+    /// the order is filled once the tick epoch moves past it
+    pub order_epoch: u32,
+
+    // account update recent epoch
+    pub recent_epoch: u64,
+
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 8],
+}
+
+impl LimitOrderState {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 4 + 1 + 16 + 4 + 8 + 8 * 8;
+
+    pub fn initialize(
+        &mut self,
+        bump: u8,
+        pool_id: Pubkey,
+        owner: Pubkey,
+        tick_lower_index: i32,
+        zero_for_one: bool,
+    ) -> Result<()> {
+        self.bump = bump;
+        self.pool_id = pool_id;
+        self.owner = owner;
+        self.tick_lower_index = tick_lower_index;
+        self.zero_for_one = zero_for_one;
+        self.liquidity = 0;
+        self.recent_epoch = get_recent_epoch()?;
+        Ok(())
+    }
+
+    pub fn tick_upper_index(&self, tick_spacing: u16) -> i32 {
+        self.tick_lower_index + i32::from(tick_spacing)
+    }
+
+    /// The tick whose crossing fills the order: the upper tick when selling token_0,
+    /// as the price has to move up through the range, and the lower tick otherwise
+    pub fn fill_tick_index(&self, tick_spacing: u16) -> i32 {
+        if self.zero_for_one {
+            self.tick_upper_index(tick_spacing)
+        } else {
+            self.tick_lower_index
+        }
+    }
+
+    pub fn is_filled(&self, fill_tick: &TickState) -> bool {
+        self.liquidity != 0 && fill_tick.order_epoch != self.order_epoch
+    }
+
+    /// The amount of output token a filled order can claim, rounded down
+    pub fn get_filled_amount(&self, tick_spacing: u16) -> Result<u64> {
+        let sqrt_price_lower_x64 = tick_math::get_sqrt_price_at_tick(self.tick_lower_index)?;
+        let sqrt_price_upper_x64 =
+            tick_math::get_sqrt_price_at_tick(self.tick_upper_index(tick_spacing))?;
+        if self.zero_for_one {
+            liquidity_math::get_delta_amount_1_unsigned(
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                self.liquidity,
+                false,
+            )
+        } else {
+            liquidity_math::get_delta_amount_0_unsigned(
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                self.liquidity,
+                false,
+            )
+        }
+    }
+}
+
+/// Checks the order range is one tick spacing wide, lies in a single tick array and is
+/// entirely on the input token side of the current price
+pub fn check_limit_order_range(
+    tick_lower_index: i32,
+    tick_spacing: u16,
+    tick_current: i32,
+    zero_for_one: bool,
+) -> Result<()> {
+    let tick_upper_index = tick_lower_index + i32::from(tick_spacing);
+    require!(
+        tick_lower_index % i32::from(tick_spacing) == 0,
+        ErrorCode::TickAndSpacingNotMatch
+    );
+    require!(
+        tick_lower_index >= tick_math::MIN_TICK && tick_upper_index <= tick_math::MAX_TICK,
+        ErrorCode::InvaildTickIndex
+    );
+    require_eq!(
+        TickArrayState::get_array_start_index(tick_lower_index, tick_spacing),
+        TickArrayState::get_array_start_index(tick_upper_index, tick_spacing),
+        ErrorCode::InvalidLimitOrderRange
+    );
+    if zero_for_one {
+        require_gt!(
+            tick_lower_index,
+            tick_current,
+            ErrorCode::InvalidLimitOrderRange
+        );
+    } else {
+        require_gte!(
+            tick_current,
+            tick_upper_index,
+            ErrorCode::InvalidLimitOrderRange
+        );
+    }
+    Ok(())
+}
+
+/// Emitted when a limit order is placed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PlaceLimitOrderEvent {
+    /// The pool the order is placed in
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The owner of the order
+    pub owner: Pubkey,
+
+    /// The lower tick of the order
+    #[index]
+    pub tick_lower_index: i32,
+
+    /// The direction of the order
+    pub zero_for_one: bool,
+
+    /// The amount of liquidity added to the order
+    pub liquidity: u128,
+
+    /// The amount of input token deposited
+    pub amount_in: u64,
+
+    /// The token transfer fee for amount_in
+    pub amount_in_transfer_fee: u64,
+}
+
+/// Emitted when a pending limit order is cancelled
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CancelLimitOrderEvent {
+    /// The pool the order was placed in
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The owner of the order
+    pub owner: Pubkey,
+
+    /// The lower tick of the order
+    #[index]
+    pub tick_lower_index: i32,
+
+    /// The amount of liquidity removed from the order
+    pub liquidity: u128,
+
+    /// The amount of token_0 returned to the owner
+    pub amount_0: u64,
+
+    /// The amount of token_1 returned to the owner
+    pub amount_1: u64,
+}
+
+/// Emitted when a filled limit order is claimed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ClaimLimitOrderEvent {
+    /// The pool the order was placed in
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The owner of the order
+    pub owner: Pubkey,
+
+    /// The lower tick of the order
+    #[index]
+    pub tick_lower_index: i32,
+
+    /// The direction of the order
+    pub zero_for_one: bool,
+
+    /// The amount of output token paid to the owner
+    pub amount_out: u64,
+
+    /// The token transfer fee for amount_out
+    pub amount_out_transfer_fee: u64,
+}
+
+#[cfg(test)]
+mod limit_order_test {
+    use super::*;
+
+    #[test]
+    fn check_limit_order_range_test() {
+        // sell token_0, the range must be above the current tick
+        assert!(check_limit_order_range(60, 60, 0, true).is_ok());
+        assert!(check_limit_order_range(0, 60, 0, true).is_err());
+        // sell token_1, the range must be at or below the current tick
+        assert!(check_limit_order_range(-120, 60, -60, false).is_ok());
+        assert!(check_limit_order_range(-120, 60, -61, false).is_err());
+        // the tick must match the spacing
+        assert!(check_limit_order_range(30, 60, 0, true).is_err());
+        // the range can not span two tick arrays
+        assert!(check_limit_order_range(3540, 60, 0, true).is_err());
+        assert!(check_limit_order_range(3480, 60, 0, true).is_ok());
+    }
+
+    #[test]
+    fn get_filled_amount_test() {
+        let mut order = LimitOrderState {
+            tick_lower_index: 0,
+            zero_for_one: true,
+            liquidity: 1_000_000_000,
+            ..Default::default()
+        };
+        let sqrt_price_0 = tick_math::get_sqrt_price_at_tick(0).unwrap();
+        let sqrt_price_60 = tick_math::get_sqrt_price_at_tick(60).unwrap();
+        // selling token_0 pays token_1, the amount the range holds at its upper bound
+        assert_eq!(
+            order.get_filled_amount(60).unwrap(),
+            liquidity_math::get_delta_amount_1_unsigned(
+                sqrt_price_0,
+                sqrt_price_60,
+                1_000_000_000,
+                false
+            )
+            .unwrap()
+        );
+        order.zero_for_one = false;
+        assert_eq!(
+            order.get_filled_amount(60).unwrap(),
+            liquidity_math::get_delta_amount_0_unsigned(
+                sqrt_price_0,
+                sqrt_price_60,
+                1_000_000_000,
+                false
+            )
+            .unwrap()
+        );
+    }
+}
@@ -1,19 +1,31 @@
+pub mod compound_keeper;
 pub mod config;
+pub mod limit_order;
 pub mod operation_account;
 pub mod oracle;
+pub mod oracle_extension;
 pub mod personal_position;
 pub mod pool;
 pub mod pool_pal;
 pub mod protocol_position;
+pub mod reward_extension;
+pub mod reward_funding;
+pub mod reward_schedule;
 pub mod tick_array;
 pub mod tickarray_bitmap_extension;
 
+pub use compound_keeper::*;
 pub use config::*;
+pub use limit_order::*;
 pub use operation_account::*;
 pub use oracle::*;
+pub use oracle_extension::*;
 pub use personal_position::*;
 pub use pool::*;
 pub use pool_pal::*;
 pub use protocol_position::*;
+pub use reward_extension::*;
+pub use reward_funding::*;
+pub use reward_schedule::*;
 pub use tick_array::*;
 pub use tickarray_bitmap_extension::*;
@@ -1,14 +1,19 @@
 /// Oracle provides price data useful for a wide variety of system designs
 ///
 use anchor_lang::prelude::*;
+use std::cell::{Ref, RefMut};
 
-use crate::util::get_recent_epoch;
+use crate::error::ErrorCode;
+use crate::libraries::{big_num::U128, full_math::MulDiv, tick_math};
+use crate::util::{get_recent_epoch, AccountLoad};
 
 /// Seed to derive account address and signature
 pub const OBSERVATION_SEED: &str = "observation";
 // Number of ObservationState element
 pub const OBSERVATION_NUM: usize = 100;
 pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u32 = 15;
+// The longest a pool can be configured to wait between two observations
+pub const OBSERVATION_UPDATE_DURATION_MAX: u32 = 3600;
 
 /// The element of observations in ObservationState
 #[zero_copy(unsafe)]
@@ -19,14 +24,21 @@ pub struct Observation {
     pub block_timestamp: u32,
     /// the cumulative of tick during the duration time
     pub tick_cumulative: i64,
+    /// the seconds per unit of in range liquidity accumulated by the pool, as a Q64.64
+    pub seconds_per_liquidity_cumulative_x64: u128,
     /// padding for feature update
-    pub padding: [u64; 4],
+    pub padding: [u64; 2],
 }
 
 impl Observation {
-    pub const LEN: usize = 4 + 8 + 8 * 4;
+    pub const LEN: usize = 4 + 8 + 16 + 8 * 2;
 }
 
+// The observations stored after the ObservationState are cast from the account data,
+// the struct is packed plain old data like the zero copy accounts
+unsafe impl bytemuck::Pod for Observation {}
+unsafe impl bytemuck::Zeroable for Observation {}
+
 #[account(zero_copy(unsafe))]
 #[repr(packed)]
 #[cfg_attr(feature = "client", derive(Debug))]
@@ -41,8 +53,16 @@ pub struct ObservationState {
     pub pool_id: Pubkey,
     /// observation array
     pub observations: [Observation; OBSERVATION_NUM],
+    /// the number of observations in use, the ones past `OBSERVATION_NUM` are stored
+    /// after this struct in the account data. Zero means `OBSERVATION_NUM`
+    pub observation_cardinality: u16,
+    /// the number of observations to use once the index wraps around,
+    /// the account is grown to hold them beforehand
+    pub observation_cardinality_next: u16,
+    /// the minimum seconds between two observations, zero means `OBSERVATION_UPDATE_DURATION_DEFAULT`
+    pub update_duration: u32,
     /// padding for feature update
-    pub padding: [u64; 4],
+    pub padding: [u64; 3],
 }
 
 impl Default for ObservationState {
@@ -54,13 +74,22 @@ impl Default for ObservationState {
             observation_index: 0,
             pool_id: Pubkey::default(),
             observations: [Observation::default(); OBSERVATION_NUM],
-            padding: [0u64; 4],
+            observation_cardinality: 0,
+            observation_cardinality_next: 0,
+            update_duration: 0,
+            padding: [0u64; 3],
         }
     }
 }
 
 impl ObservationState {
-    pub const LEN: usize = 8 + 1 + 8 + 2 + 32 + (Observation::LEN * OBSERVATION_NUM) + 8 * 4;
+    pub const LEN: usize =
+        8 + 1 + 8 + 2 + 32 + (Observation::LEN * OBSERVATION_NUM) + 2 + 2 + 4 + 8 * 3;
+
+    /// The account size needed to hold `cardinality` observations
+    pub fn account_len(cardinality: u16) -> usize {
+        Self::LEN + Observation::LEN * usize::from(cardinality).saturating_sub(OBSERVATION_NUM)
+    }
 
     pub fn initialize(&mut self, pool_id: Pubkey) -> Result<()> {
         self.initialized = false;
@@ -68,46 +97,393 @@ impl ObservationState {
         self.observation_index = 0;
         self.pool_id = pool_id;
         self.observations = [Observation::default(); OBSERVATION_NUM];
-        self.padding = [0u64; 4];
+        self.observation_cardinality = 0;
+        self.observation_cardinality_next = 0;
+        self.update_duration = 0;
+        self.padding = [0u64; 3];
         Ok(())
     }
 
+    /// Returns the number of observations in the ring buffer, bounded by the `extension` available
+    pub fn cardinality(&self, extension: &[Observation]) -> usize {
+        usize::from(self.observation_cardinality)
+            .clamp(OBSERVATION_NUM, OBSERVATION_NUM + extension.len())
+    }
+
+    /// Returns the number of observations the ring buffer grows to once the index wraps around
+    pub fn cardinality_next(&self, extension: &[Observation]) -> usize {
+        usize::from(self.observation_cardinality_next).clamp(
+            self.cardinality(extension),
+            OBSERVATION_NUM + extension.len(),
+        )
+    }
+
+    pub fn update_duration(&self) -> u32 {
+        if self.update_duration == 0 {
+            OBSERVATION_UPDATE_DURATION_DEFAULT
+        } else {
+            self.update_duration
+        }
+    }
+
+    /// Returns the seconds of history the observations in use can cover at most
+    pub fn history_duration(&self) -> u32 {
+        let cardinality = usize::from(self.observation_cardinality).max(OBSERVATION_NUM);
+        (cardinality as u32).saturating_mul(self.update_duration())
+    }
+
+    fn get_observation(&self, extension: &[Observation], index: usize) -> Observation {
+        if index < OBSERVATION_NUM {
+            self.observations[index]
+        } else {
+            extension[index - OBSERVATION_NUM]
+        }
+    }
+
+    fn set_observation(
+        &mut self,
+        extension: &mut [Observation],
+        index: usize,
+        block_timestamp: u32,
+        tick_cumulative: i64,
+        seconds_per_liquidity_cumulative_x64: u128,
+    ) {
+        let observation = if index < OBSERVATION_NUM {
+            &mut self.observations[index]
+        } else {
+            &mut extension[index - OBSERVATION_NUM]
+        };
+        observation.block_timestamp = block_timestamp;
+        observation.tick_cumulative = tick_cumulative;
+        observation.seconds_per_liquidity_cumulative_x64 = seconds_per_liquidity_cumulative_x64;
+    }
+
     // Writes an oracle observation to the account
     ///
     /// # Arguments
     ///
     /// * `self` - The ObservationState account to write in
+    /// * `extension` - The observations stored after the ObservationState in the account
     /// * `block_timestamp` - The current timestamp of to update
+    /// * `tick` - The current tick of the pool
+    /// * `seconds_per_liquidity_cumulative_x64` - The seconds per liquidity accumulator of the pool as of `block_timestamp`
     ///
     // This function updates a record of observations with a new block timestamp and tick value.
     // It either initializes the observation on the first run or updates it based on time intervals.
-    pub fn update(&mut self, block_timestamp: u32, tick: i32) {
+    pub fn update(
+        &mut self,
+        extension: &mut [Observation],
+        block_timestamp: u32,
+        tick: i32,
+        seconds_per_liquidity_cumulative_x64: u128,
+    ) {
         // Retrieve the current observation index
-        let observation_index = self.observation_index;
+        let observation_index = self.observation_index as usize;
         if !self.initialized {
             self.initialized = true;
-            self.observations[observation_index as usize].block_timestamp = block_timestamp;
-            self.observations[observation_index as usize].tick_cumulative = 0;
+            self.set_observation(
+                extension,
+                observation_index,
+                block_timestamp,
+                0,
+                seconds_per_liquidity_cumulative_x64,
+            );
         } else {
-            let last_observation = self.observations[observation_index as usize];
+            let last_observation = self.get_observation(extension, observation_index);
             let delta_time = block_timestamp.saturating_sub(last_observation.block_timestamp);
-            if delta_time < OBSERVATION_UPDATE_DURATION_DEFAULT {
+            if delta_time < self.update_duration() {
                 return;
             }
 
             let delta_tick_cumulative = i64::from(tick).checked_mul(delta_time.into()).unwrap();
-            let next_observation_index = if observation_index as usize == OBSERVATION_NUM - 1 {
+            // The ring buffer only grows when the index wraps around, so the new slots
+            // are written in order after the oldest observation
+            let mut cardinality = self.cardinality(extension);
+            if observation_index == cardinality - 1 {
+                cardinality = self.cardinality_next(extension);
+                self.observation_cardinality = cardinality as u16;
+            }
+            let next_observation_index = (observation_index + 1) % cardinality;
+            self.set_observation(
+                extension,
+                next_observation_index,
+                block_timestamp,
+                last_observation
+                    .tick_cumulative
+                    .wrapping_add(delta_tick_cumulative),
+                seconds_per_liquidity_cumulative_x64,
+            );
+            self.observation_index = next_observation_index as u16;
+        }
+    }
+
+    /// Returns the absolute difference between `tick` and the average tick of the
+    /// observations recorded in the last `window` seconds, zero if there is not enough history
+    pub fn get_tick_deviation(
+        &self,
+        extension: &[Observation],
+        block_timestamp: u32,
+        tick: i32,
+        window: u32,
+    ) -> u32 {
+        if !self.initialized {
+            return 0;
+        }
+        let cardinality = self.cardinality(extension);
+        let last_observation = self.get_observation(extension, self.observation_index as usize);
+        let window_start = block_timestamp.saturating_sub(window);
+
+        // Walk back from the most recent observation to the first one at or before the window start
+        let mut first_observation = last_observation;
+        for i in 1..cardinality {
+            let index = (self.observation_index as usize + cardinality - i) % cardinality;
+            let observation = self.get_observation(extension, index);
+            if observation.block_timestamp == 0 {
+                break;
+            }
+            first_observation = observation;
+            if observation.block_timestamp <= window_start {
+                break;
+            }
+        }
+
+        let delta_time = last_observation
+            .block_timestamp
+            .saturating_sub(first_observation.block_timestamp);
+        if delta_time == 0 {
+            return 0;
+        }
+        let average_tick = last_observation
+            .tick_cumulative
+            .wrapping_sub(first_observation.tick_cumulative)
+            / i64::from(delta_time);
+        i64::from(tick).abs_diff(average_tick).min(u32::MAX.into()) as u32
+    }
+
+    /// Returns the number of observations written to the ring buffer
+    fn observation_count(&self, extension: &[Observation]) -> usize {
+        let cardinality = self.cardinality(extension);
+        let next_index = (self.observation_index as usize + 1) % cardinality;
+        if self.get_observation(extension, next_index).block_timestamp == 0 {
+            self.observation_index as usize + 1
+        } else {
+            cardinality
+        }
+    }
+
+    /// Returns the tick cumulative and the seconds per liquidity cumulative as of `target`,
+    /// interpolated between the two observations around it. After the latest observation the
+    /// tick cumulative is extrapolated with `tick_current`, and the seconds per liquidity
+    /// is interpolated up to the pool accumulator as of `block_timestamp`, since the liquidity
+    /// may have changed in between.
+    pub fn observe_single(
+        &self,
+        extension: &[Observation],
+        block_timestamp: u32,
+        target: u32,
+        tick_current: i32,
+        seconds_per_liquidity_global_x64: u128,
+    ) -> Result<(i64, u128)> {
+        require!(self.initialized, ErrorCode::ObservationOutOfRange);
+        let last = self.get_observation(extension, self.observation_index as usize);
+        if target >= last.block_timestamp {
+            require_gte!(block_timestamp, target, ErrorCode::ObservationOutOfRange);
+            let delta_time = target - last.block_timestamp;
+            let seconds_per_liquidity_delta_x64 = if delta_time == 0 {
                 0
             } else {
-                observation_index + 1
+                U128::from(
+                    seconds_per_liquidity_global_x64
+                        .wrapping_sub(last.seconds_per_liquidity_cumulative_x64),
+                )
+                .mul_div_floor(
+                    U128::from(delta_time),
+                    U128::from(block_timestamp - last.block_timestamp),
+                )
+                .unwrap()
+                .as_u128()
             };
-            self.observations[next_observation_index as usize].block_timestamp = block_timestamp;
-            self.observations[next_observation_index as usize].tick_cumulative = last_observation
-                .tick_cumulative
-                .wrapping_add(delta_tick_cumulative);
-            self.observation_index = next_observation_index;
+            return Ok((
+                last.tick_cumulative
+                    .wrapping_add(i64::from(tick_current) * i64::from(delta_time)),
+                last.seconds_per_liquidity_cumulative_x64
+                    .wrapping_add(seconds_per_liquidity_delta_x64),
+            ));
         }
+
+        // The oldest observation follows the latest one once the ring buffer is full
+        let cardinality = self.cardinality(extension);
+        let count = self.observation_count(extension);
+        let oldest_index =
+            (self.observation_index as usize + cardinality + 1 - count) % cardinality;
+        let oldest = self.get_observation(extension, oldest_index);
+        require_gte!(
+            target,
+            oldest.block_timestamp,
+            ErrorCode::ObservationOutOfRange
+        );
+
+        // Binary search the last observation at or before the target
+        let (mut left, mut right) = (0, count - 1);
+        while left < right {
+            let middle = (left + right + 1) / 2;
+            let observation =
+                self.get_observation(extension, (oldest_index + middle) % cardinality);
+            if observation.block_timestamp <= target {
+                left = middle;
+            } else {
+                right = middle - 1;
+            }
+        }
+        let before = self.get_observation(extension, (oldest_index + left) % cardinality);
+        if before.block_timestamp == target {
+            return Ok((
+                before.tick_cumulative,
+                before.seconds_per_liquidity_cumulative_x64,
+            ));
+        }
+        let after = self.get_observation(extension, (oldest_index + left + 1) % cardinality);
+        let observation_time_delta = after.block_timestamp - before.block_timestamp;
+        let target_delta = target - before.block_timestamp;
+        let seconds_per_liquidity_delta_x64 = U128::from(
+            after
+                .seconds_per_liquidity_cumulative_x64
+                .wrapping_sub(before.seconds_per_liquidity_cumulative_x64),
+        )
+        .mul_div_floor(U128::from(target_delta), U128::from(observation_time_delta))
+        .unwrap()
+        .as_u128();
+        Ok((
+            before.tick_cumulative.wrapping_add(
+                after.tick_cumulative.wrapping_sub(before.tick_cumulative)
+                    / i64::from(observation_time_delta)
+                    * i64::from(target_delta),
+            ),
+            before
+                .seconds_per_liquidity_cumulative_x64
+                .wrapping_add(seconds_per_liquidity_delta_x64),
+        ))
     }
+
+    /// Returns the tick cumulative and the seconds per liquidity cumulative as of each
+    /// `seconds_ago` from `block_timestamp`
+    pub fn observe(
+        &self,
+        extension: &[Observation],
+        block_timestamp: u32,
+        tick_current: i32,
+        seconds_per_liquidity_global_x64: u128,
+        seconds_agos: &[u32],
+    ) -> Result<Vec<(i64, u128)>> {
+        seconds_agos
+            .iter()
+            .map(|seconds_ago| {
+                self.observe_single(
+                    extension,
+                    block_timestamp,
+                    block_timestamp
+                        .checked_sub(*seconds_ago)
+                        .ok_or(ErrorCode::ObservationOutOfRange)?,
+                    tick_current,
+                    seconds_per_liquidity_global_x64,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the time weighted average tick and sqrt price over the last `window` seconds
+    pub fn get_twap(
+        &self,
+        extension: &[Observation],
+        block_timestamp: u32,
+        tick_current: i32,
+        window: u32,
+    ) -> Result<(i32, u128)> {
+        if window == 0 {
+            return Ok((
+                tick_current,
+                tick_math::get_sqrt_price_at_tick(tick_current)?,
+            ));
+        }
+        // Only the tick cumulatives are used, so the seconds per liquidity does not matter
+        let cumulatives =
+            self.observe(extension, block_timestamp, tick_current, 0, &[window, 0])?;
+        let twap_tick = get_twap_tick(cumulatives[0].0, cumulatives[1].0, window);
+        Ok((twap_tick, tick_math::get_sqrt_price_at_tick(twap_tick)?))
+    }
+}
+
+/// Emitted when the observations a pool keeps are increased
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct IncreaseObservationCardinalityNextEvent {
+    /// The pool of the observations
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The previous number of observations to grow to
+    pub observation_cardinality_next_old: u16,
+
+    /// The new number of observations to grow to
+    pub observation_cardinality_next_new: u16,
+}
+
+/// Emitted when the seconds between two observations of a pool are changed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ObservationUpdateDurationChangeEvent {
+    /// The pool of the observations
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The minimum seconds between two observations
+    pub update_duration: u32,
+}
+
+/// Loads the ObservationState of an account together with the observations stored after it,
+/// the account is only checked to be an ObservationState owned by this program
+pub fn load_observations_mut<'a>(
+    account_info: &'a AccountInfo,
+) -> Result<(RefMut<'a, ObservationState>, RefMut<'a, [Observation]>)> {
+    drop(AccountLoad::<ObservationState>::load_data_mut_unchecked(
+        account_info,
+    )?);
+    let data = account_info.try_borrow_mut_data()?;
+    Ok(RefMut::map_split(data, |data| {
+        let (state, extension) = data.split_at_mut(ObservationState::LEN);
+        let extension_len = extension.len() / Observation::LEN * Observation::LEN;
+        (
+            bytemuck::from_bytes_mut(&mut state[8..]),
+            bytemuck::cast_slice_mut(&mut extension[..extension_len]),
+        )
+    }))
+}
+
+/// Loads the ObservationState of an account together with the observations stored after it
+/// for reading only, the account is only checked to be an ObservationState owned by this program
+pub fn load_observations<'a>(
+    account_info: &'a AccountInfo,
+) -> Result<(Ref<'a, ObservationState>, Ref<'a, [Observation]>)> {
+    drop(AccountLoad::<ObservationState>::load_data(account_info)?);
+    let data = account_info.try_borrow_data()?;
+    Ok(Ref::map_split(data, |data| {
+        let (state, extension) = data.split_at(ObservationState::LEN);
+        let extension_len = extension.len() / Observation::LEN * Observation::LEN;
+        (
+            bytemuck::from_bytes(&state[8..]),
+            bytemuck::cast_slice(&extension[..extension_len]),
+        )
+    }))
+}
+
+/// Returns the average tick between two tick cumulatives `window` seconds apart, rounded to negative infinity
+pub fn get_twap_tick(tick_cumulative_start: i64, tick_cumulative_end: i64, window: u32) -> i32 {
+    let tick_cumulative_delta = tick_cumulative_end.wrapping_sub(tick_cumulative_start);
+    let mut twap_tick = tick_cumulative_delta / i64::from(window);
+    if tick_cumulative_delta < 0 && tick_cumulative_delta % i64::from(window) != 0 {
+        twap_tick -= 1;
+    }
+    twap_tick as i32
 }
 
 /// Returns the block timestamp truncated to 32 bits, i.e. mod 2**32
@@ -124,3 +500,240 @@ pub fn block_timestamp_mock() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+#[cfg(test)]
+mod observe_test {
+    use super::*;
+    use std::convert::identity;
+
+    fn build_observation_state(ticks: Vec<(u32, i32)>) -> ObservationState {
+        let mut observation_state = ObservationState::default();
+        for (block_timestamp, tick) in ticks {
+            observation_state.update(&mut [], block_timestamp, tick, 0);
+        }
+        observation_state
+    }
+
+    #[test]
+    fn observe_interpolate_and_extrapolate_test() {
+        // cumulatives: 1000 => 0, 1020 => 2000, 1040 => 1600
+        let observation_state =
+            build_observation_state(vec![(1000, 100), (1020, 100), (1040, -20)]);
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1050, 1000, 50, 0)
+                .unwrap()
+                .0,
+            0
+        );
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1050, 1020, 50, 0)
+                .unwrap()
+                .0,
+            2000
+        );
+        // between two observations
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1050, 1010, 50, 0)
+                .unwrap()
+                .0,
+            1000
+        );
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1050, 1030, 50, 0)
+                .unwrap()
+                .0,
+            1800
+        );
+        // after the latest observation, with the current tick
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1050, 1050, 50, 0)
+                .unwrap()
+                .0,
+            2100
+        );
+        // before the oldest observation
+        assert!(observation_state
+            .observe_single(&[], 1050, 999, 50, 0)
+            .is_err());
+        assert!(ObservationState::default()
+            .observe_single(&[], 1000, 1000, 50, 0)
+            .is_err());
+
+        assert_eq!(
+            observation_state
+                .observe(&[], 1050, 50, 10 << 64, &[50, 40, 0])
+                .unwrap(),
+            vec![(0, 0), (1000, 0), (2100, 10 << 64)]
+        );
+        assert!(observation_state.observe(&[], 1050, 50, 0, &[51]).is_err());
+    }
+
+    #[test]
+    fn observe_full_ring_buffer_test() {
+        let mut observation_state = ObservationState::default();
+        for i in 0..(OBSERVATION_NUM as u32 + 10) {
+            observation_state.update(&mut [], 1000 + i * 15, 10, 0);
+        }
+        // the first 10 observations were overwritten
+        let oldest = 1000 + 10 * 15;
+        let latest = 1000 + (OBSERVATION_NUM as u32 + 9) * 15;
+        assert!(observation_state
+            .observe_single(&[], latest, oldest - 1, 10, 0)
+            .is_err());
+        let cumulative_oldest = observation_state
+            .observe_single(&[], latest, oldest, 10, 0)
+            .unwrap()
+            .0;
+        let cumulative_latest = observation_state
+            .observe_single(&[], latest, latest, 10, 0)
+            .unwrap()
+            .0;
+        assert_eq!(
+            cumulative_latest - cumulative_oldest,
+            i64::from(latest - oldest) * 10
+        );
+        assert_eq!(
+            observation_state
+                .observe_single(&[], latest, oldest + 7, 10, 0)
+                .unwrap()
+                .0,
+            cumulative_oldest + 70
+        );
+    }
+
+    #[test]
+    fn observe_seconds_per_liquidity_test() {
+        let mut observation_state = ObservationState::default();
+        observation_state.update(&mut [], 1000, 0, 0);
+        // 20 seconds at 4 units of liquidity
+        observation_state.update(&mut [], 1020, 0, 5 << 64);
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1020, 1020, 0, 5 << 64)
+                .unwrap()
+                .1,
+            5 << 64
+        );
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1020, 1005, 0, 5 << 64)
+                .unwrap()
+                .1,
+            (5 << 64) / 4
+        );
+        // the liquidity changed since the latest observation, 2 seconds at 1 unit then
+        // 8 seconds at 2 units, so the pool accumulator is used up to now
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1030, 1030, 0, 11 << 64)
+                .unwrap()
+                .1,
+            11 << 64
+        );
+        assert_eq!(
+            observation_state
+                .observe_single(&[], 1030, 1025, 0, 11 << 64)
+                .unwrap()
+                .1,
+            8 << 64
+        );
+        // the target can not be after now
+        assert!(observation_state
+            .observe_single(&[], 1030, 1031, 0, 11 << 64)
+            .is_err());
+    }
+
+    #[test]
+    fn get_twap_test() {
+        let observation_state =
+            build_observation_state(vec![(1000, 100), (1020, 100), (1040, -20)]);
+        let (twap_tick, twap_sqrt_price_x64) =
+            observation_state.get_twap(&[], 1050, 50, 50).unwrap();
+        assert_eq!(twap_tick, 42);
+        assert_eq!(
+            twap_sqrt_price_x64,
+            tick_math::get_sqrt_price_at_tick(42).unwrap()
+        );
+        assert_eq!(observation_state.get_twap(&[], 1050, 50, 0).unwrap().0, 50);
+        // rounded to negative infinity
+        assert_eq!(get_twap_tick(0, -7, 2), -4);
+        assert_eq!(get_twap_tick(0, 7, 2), 3);
+    }
+
+    #[test]
+    fn grow_cardinality_test() {
+        let mut observation_state = ObservationState::default();
+        let mut extension = [Observation::default(); 20];
+        for i in 0..50u32 {
+            observation_state.update(&mut extension, 1000 + i * 15, 10, 0);
+        }
+        // the ring grows once the index wraps around, not before
+        observation_state.observation_cardinality_next = OBSERVATION_NUM as u16 + 20;
+        assert_eq!(observation_state.cardinality(&extension), OBSERVATION_NUM);
+        for i in 50..(OBSERVATION_NUM as u32 + 30) {
+            observation_state.update(&mut extension, 1000 + i * 15, 10, 0);
+        }
+        assert_eq!(
+            observation_state.cardinality(&extension),
+            OBSERVATION_NUM + 20
+        );
+        assert_eq!(identity(observation_state.observation_index), 9);
+        // only the first 10 observations were overwritten
+        let oldest = 1000 + 10 * 15;
+        let latest = 1000 + (OBSERVATION_NUM as u32 + 29) * 15;
+        assert!(observation_state
+            .observe_single(&extension, latest, oldest - 1, 10, 0)
+            .is_err());
+        assert_eq!(
+            observation_state
+                .observe_single(&extension, latest, latest, 10, 0)
+                .unwrap()
+                .0
+                - observation_state
+                    .observe_single(&extension, latest, oldest, 10, 0)
+                    .unwrap()
+                    .0,
+            i64::from(latest - oldest) * 10
+        );
+        // without the extension the ring can not grow
+        assert_eq!(observation_state.cardinality(&[]), OBSERVATION_NUM);
+        assert_eq!(
+            observation_state.cardinality_next(&extension[..10]),
+            OBSERVATION_NUM + 10
+        );
+    }
+
+    #[test]
+    fn update_duration_test() {
+        let mut observation_state = ObservationState::default();
+        observation_state.update_duration = 60;
+        for i in 0..10u32 {
+            observation_state.update(&mut [], 1000 + i * 15, 10, 0);
+        }
+        // one observation every 4 updates
+        assert_eq!(identity(observation_state.observation_index), 2);
+        assert_eq!(
+            observation_state.history_duration(),
+            OBSERVATION_NUM as u32 * 60
+        );
+        observation_state.observation_cardinality = OBSERVATION_NUM as u16 + 20;
+        assert_eq!(
+            observation_state.history_duration(),
+            (OBSERVATION_NUM as u32 + 20) * 60
+        );
+        assert_eq!(
+            ObservationState::account_len(OBSERVATION_NUM as u16 + 2),
+            ObservationState::LEN + 2 * Observation::LEN
+        );
+        assert_eq!(ObservationState::account_len(1), ObservationState::LEN);
+        assert_eq!(
+            ObservationState::LEN,
+            8 + std::mem::size_of::<ObservationState>()
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::error::ErrorCode;
+use crate::states::{
+    Observation, ObservationState, PoolState, TickArrayState, TICK_ARRAY_SIZE_USIZE,
+};
+use crate::util::AccountLoad;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// Seed to derive account address and signature
+pub const TICK_ARRAY_ORACLE_EXTENSION_SEED: &str = "tick_array_oracle_extension";
+
+/// The tick cumulatives and seconds outside the ticks of a tick array, which do not fit in the
+/// tick states. Once it exists, every swap crossing a tick of the array must pass it in.
+///
+/// PDA of `[TICK_ARRAY_ORACLE_EXTENSION_SEED, pool, start_tick_index]`
+#[account(zero_copy(unsafe))]
+#[repr(packed)]
+pub struct TickArrayOracleExtension {
+    pub pool_id: Pubkey,
+    pub start_tick_index: i32,
+    // The tick cumulative on the _other_ side of every tick of the tick array, like the fee growth
+    // it only has relative meaning
+    pub tick_cumulatives_outside: [i64; TICK_ARRAY_SIZE_USIZE],
+    // The seconds spent on the _other_ side of every tick of the tick array
+    pub seconds_outside: [u32; TICK_ARRAY_SIZE_USIZE],
+    // Unused bytes for future upgrades.
+    pub padding: [u8; 64],
+}
+
+impl TickArrayOracleExtension {
+    pub const LEN: usize = 8 + 32 + 4 + (8 + 4) * TICK_ARRAY_SIZE_USIZE + 64;
+
+    /// Initializes the cumulatives outside of the initialized ticks of the tick array,
+    /// assuming like the tick itself that all of them were accumulated below them
+    pub fn initialize(
+        &mut self,
+        tick_array: &TickArrayState,
+        tick_current: i32,
+        tick_cumulative: i64,
+        block_timestamp: u32,
+    ) {
+        self.pool_id = tick_array.pool_id;
+        self.start_tick_index = tick_array.start_tick_index;
+        for (i, tick_state) in tick_array.ticks.iter().enumerate() {
+            if tick_state.is_initialized() && tick_state.tick <= tick_current {
+                self.tick_cumulatives_outside[i] = tick_cumulative;
+                self.seconds_outside[i] = block_timestamp;
+            }
+        }
+    }
+
+    fn get_tick_offset(&self, tick_index: i32, tick_spacing: u16) -> Result<usize> {
+        require_eq!(
+            TickArrayState::get_array_start_index(tick_index, tick_spacing),
+            self.start_tick_index,
+            ErrorCode::InvalidTickArray
+        );
+        Ok(((tick_index - self.start_tick_index) / i32::from(tick_spacing)) as usize)
+    }
+
+    /// Returns the tick cumulative and the seconds outside of a tick
+    pub fn get_cumulatives_outside(
+        &self,
+        tick_index: i32,
+        tick_spacing: u16,
+    ) -> Result<(i64, u32)> {
+        let offset = self.get_tick_offset(tick_index, tick_spacing)?;
+        Ok((
+            self.tick_cumulatives_outside[offset],
+            self.seconds_outside[offset],
+        ))
+    }
+
+    /// Flips the cumulatives outside of a tick crossed by the price, like `TickState::cross`
+    pub fn cross(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        tick_cumulative: i64,
+        block_timestamp: u32,
+    ) -> Result<()> {
+        let offset = self.get_tick_offset(tick_index, tick_spacing)?;
+        // The cumulatives may wrap around, only differences between their values are used
+        self.tick_cumulatives_outside[offset] =
+            tick_cumulative.wrapping_sub(self.tick_cumulatives_outside[offset]);
+        self.seconds_outside[offset] = block_timestamp.wrapping_sub(self.seconds_outside[offset]);
+        Ok(())
+    }
+}
+
+/// Calculates the tick cumulative and the seconds inside of a range from the cumulatives
+/// outside of its ticks, like `get_seconds_per_liquidity_inside`
+pub fn get_cumulatives_inside(
+    tick_lower_index: i32,
+    (tick_cumulative_outside_lower, seconds_outside_lower): (i64, u32),
+    tick_upper_index: i32,
+    (tick_cumulative_outside_upper, seconds_outside_upper): (i64, u32),
+    tick_current: i32,
+    tick_cumulative: i64,
+    block_timestamp: u32,
+) -> (i64, u32) {
+    if tick_current < tick_lower_index {
+        (
+            tick_cumulative_outside_lower.wrapping_sub(tick_cumulative_outside_upper),
+            seconds_outside_lower.wrapping_sub(seconds_outside_upper),
+        )
+    } else if tick_current < tick_upper_index {
+        (
+            tick_cumulative
+                .wrapping_sub(tick_cumulative_outside_lower)
+                .wrapping_sub(tick_cumulative_outside_upper),
+            block_timestamp
+                .wrapping_sub(seconds_outside_lower)
+                .wrapping_sub(seconds_outside_upper),
+        )
+    } else {
+        (
+            tick_cumulative_outside_upper.wrapping_sub(tick_cumulative_outside_lower),
+            seconds_outside_upper.wrapping_sub(seconds_outside_lower),
+        )
+    }
+}
+
+/// Returns the tick cumulative of a pool as of now from its observations
+pub fn get_tick_cumulative(
+    pool_state: &PoolState,
+    observation_state: &ObservationState,
+    observation_extension: &[Observation],
+    block_timestamp: u32,
+) -> Result<i64> {
+    let (tick_cumulative, _) = observation_state.observe_single(
+        observation_extension,
+        block_timestamp,
+        block_timestamp,
+        pool_state.tick_current,
+        pool_state.seconds_per_liquidity_global_x64,
+    )?;
+    Ok(tick_cumulative)
+}
+
+/// Splits the tick array oracle extensions out of the remaining accounts of a swap
+pub fn split_tick_array_oracle_extensions<'c, 'info>(
+    remaining_accounts: Vec<&'c AccountInfo<'info>>,
+) -> (Vec<&'c AccountInfo<'info>>, Vec<&'c AccountInfo<'info>>) {
+    remaining_accounts.into_iter().partition(|account_info| {
+        *account_info.owner == crate::id()
+            && account_info
+                .try_borrow_data()
+                .map(|data| {
+                    data.len() >= 8 && data[..8] == TickArrayOracleExtension::discriminator()
+                })
+                .unwrap_or(false)
+    })
+}
+
+/// Flips the cumulatives outside of the ticks crossed by a swap. All the ticks are crossed at
+/// the same time, so they can be flipped once the swap and its observation are done.
+pub fn cross_tick_array_oracle_extensions(
+    pool_id: Pubkey,
+    pool_state: &PoolState,
+    observation_state: &ObservationState,
+    observation_extension: &[Observation],
+    block_timestamp: u32,
+    tick_array_oracle_extensions: &[&AccountInfo],
+    crossed_ticks: &[i32],
+) -> Result<()> {
+    if crossed_ticks.is_empty() {
+        return Ok(());
+    }
+    let tick_cumulative = get_tick_cumulative(
+        pool_state,
+        observation_state,
+        observation_extension,
+        block_timestamp,
+    )?;
+    for tick_index in crossed_ticks {
+        let start_tick_index =
+            TickArrayState::get_array_start_index(*tick_index, pool_state.tick_spacing);
+        let mut tick_array_oracle_extension = None;
+        for account_info in tick_array_oracle_extensions {
+            let extension = AccountLoad::<TickArrayOracleExtension>::load_data_mut(account_info)?;
+            if extension.pool_id == pool_id && extension.start_tick_index == start_tick_index {
+                tick_array_oracle_extension = Some(extension);
+                break;
+            }
+        }
+        tick_array_oracle_extension
+            .ok_or(ErrorCode::MissingTickArrayOracleExtension)?
+            .cross(
+                *tick_index,
+                pool_state.tick_spacing,
+                tick_cumulative,
+                block_timestamp,
+            )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod oracle_extension_test {
+    use super::*;
+
+    #[test]
+    fn initialize_test() {
+        let tick_spacing = 10;
+        let mut tick_array = TickArrayState::default();
+        tick_array.start_tick_index = -600;
+        for tick_index in [-600, -10] {
+            let tick_state = tick_array
+                .get_tick_state_mut(tick_index, tick_spacing)
+                .unwrap();
+            tick_state.tick = tick_index;
+            tick_state.liquidity_gross = 100;
+        }
+
+        let mut extension = TickArrayOracleExtension {
+            pool_id: Pubkey::default(),
+            start_tick_index: 0,
+            tick_cumulatives_outside: [0; TICK_ARRAY_SIZE_USIZE],
+            seconds_outside: [0; TICK_ARRAY_SIZE_USIZE],
+            padding: [0; 64],
+        };
+        extension.initialize(&tick_array, -300, 5000, 100);
+        // initialized below the price
+        assert_eq!(
+            extension
+                .get_cumulatives_outside(-600, tick_spacing)
+                .unwrap(),
+            (5000, 100)
+        );
+        // initialized above the price
+        assert_eq!(
+            extension
+                .get_cumulatives_outside(-10, tick_spacing)
+                .unwrap(),
+            (0, 0)
+        );
+        // out of the tick array
+        assert!(extension.get_cumulatives_outside(0, tick_spacing).is_err());
+    }
+
+    #[test]
+    fn cumulatives_inside_test() {
+        let tick_spacing = 10;
+        let mut extension = TickArrayOracleExtension {
+            pool_id: Pubkey::default(),
+            start_tick_index: -600,
+            tick_cumulatives_outside: [0; TICK_ARRAY_SIZE_USIZE],
+            seconds_outside: [0; TICK_ARRAY_SIZE_USIZE],
+            padding: [0; 64],
+        };
+        let get_cumulatives_inside_at =
+            |extension: &TickArrayOracleExtension,
+             tick_current: i32,
+             tick_cumulative: i64,
+             block_timestamp: u32| {
+                get_cumulatives_inside(
+                    -100,
+                    extension
+                        .get_cumulatives_outside(-100, tick_spacing)
+                        .unwrap(),
+                    -50,
+                    extension
+                        .get_cumulatives_outside(-50, tick_spacing)
+                        .unwrap(),
+                    tick_current,
+                    tick_cumulative,
+                    block_timestamp,
+                )
+            };
+
+        // The price stays above the range [-100, -50) from 0 to 100 at tick -10
+        let (tick_cumulative_start, seconds_start) =
+            get_cumulatives_inside_at(&extension, -10, 0, 0);
+        // it crosses -50 down at 100 and stays at -70 until 150
+        extension.cross(-50, tick_spacing, -1000, 100).unwrap();
+        // it crosses -100 down at 150 and stays at -120 until 170
+        extension
+            .cross(-100, tick_spacing, -1000 - 70 * 50, 150)
+            .unwrap();
+        let (tick_cumulative_end, seconds_end) =
+            get_cumulatives_inside_at(&extension, -120, -1000 - 70 * 50 - 120 * 20, 170);
+
+        assert_eq!(seconds_end.wrapping_sub(seconds_start), 50);
+        assert_eq!(
+            tick_cumulative_end.wrapping_sub(tick_cumulative_start),
+            -70 * 50
+        );
+    }
+}
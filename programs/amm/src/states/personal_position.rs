@@ -1,8 +1,12 @@
+use crate::error::ErrorCode;
 use crate::libraries::{big_num::U256, fixed_point_64, full_math::MulDiv};
 use crate::pool::REWARD_NUM;
 use crate::util::get_recent_epoch;
 use anchor_lang::prelude::*;
 
+/// The longest a position can be locked for from now, in seconds
+pub const MAX_LOCK_DURATION: u64 = 10 * 365 * 24 * 60 * 60;
+
 #[account]
 #[derive(Default, Debug)]
 pub struct PersonalPositionState {
@@ -40,8 +44,20 @@ pub struct PersonalPositionState {
     pub reward_infos: [PositionRewardInfo; REWARD_NUM],
     // account update recent epoch
     pub recent_epoch: u64,
+
+    /// The time until which the liquidity of the position cannot be removed, zero for unlocked
+    pub unlock_timestamp: u64,
+
+    /// Whether the fees of the position can be collected while it is locked
+    pub lock_allow_collect_fee: bool,
+
+    /// The last time the `PositionRewardExtension` of the position was updated,
+    /// zero if the position has none
+    pub reward_extension_update_time: u64,
+
     // Unused bytes for future upgrades.
-    pub padding: [u64; 7],
+    pub padding: [u8; 7],
+    pub padding1: [u64; 4],
 }
 
 impl PersonalPositionState {
@@ -83,6 +99,33 @@ impl PersonalPositionState {
         self.recent_epoch = get_recent_epoch()?;
         Ok(())
     }
+
+    pub fn is_locked(&self, current_timestamp: u64) -> bool {
+        current_timestamp < self.unlock_timestamp
+    }
+
+    /// Checks the liquidity can be removed from the position. While it is locked, only
+    /// its fees can be taken, and only if the lock allows it.
+    pub fn check_unlocked(&self, liquidity: u128, current_timestamp: u64) -> Result<()> {
+        if self.is_locked(current_timestamp) {
+            require!(
+                liquidity == 0 && self.lock_allow_collect_fee,
+                ErrorCode::PositionLocked
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks the `PositionRewardExtension` of the position, if it has one, was updated at the
+    /// current timestamp, so its rewards are accrued over the liquidity about to change.
+    pub fn check_reward_extension_updated(&self, current_timestamp: u64) -> Result<()> {
+        require!(
+            self.reward_extension_update_time == 0
+                || self.reward_extension_update_time == current_timestamp,
+            ErrorCode::RewardExtensionNotUpdated
+        );
+        Ok(())
+    }
 }
 
 #[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Default, Debug, PartialEq)]
@@ -227,6 +270,186 @@ pub struct CollectPersonalFeeEvent {
     pub amount_1: u64,
 }
 
+/// Emitted when the fees of a position are compounded into its liquidity
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CompoundPositionEvent {
+    /// The ID of the token for which fees were compounded
+    #[index]
+    pub position_nft_mint: Pubkey,
+
+    /// The amount by which the liquidity of the position was increased
+    pub liquidity: u128,
+
+    /// The amount of token_0 fee that was reinvested
+    pub amount_0: u64,
+
+    /// The amount of token_1 fee that was reinvested
+    pub amount_1: u64,
+
+    /// The amount of token_0 fee paid to the keeper
+    pub tip_amount_0: u64,
+
+    /// The amount of token_1 fee paid to the keeper
+    pub tip_amount_1: u64,
+}
+
+/// Emitted when a position is moved to a new range
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct RebalancePositionEvent {
+    /// The ID of the token for which the position was moved
+    #[index]
+    pub position_nft_mint: Pubkey,
+
+    /// The lower bound tick of the position before the move
+    pub tick_lower_index_before: i32,
+
+    /// The upper bound tick of the position before the move
+    pub tick_upper_index_before: i32,
+
+    /// The liquidity removed from the previous range
+    pub liquidity_before: u128,
+
+    /// The lower bound tick of the position after the move
+    pub tick_lower_index: i32,
+
+    /// The upper bound tick of the position after the move
+    pub tick_upper_index: i32,
+
+    /// The liquidity added to the new range
+    pub liquidity: u128,
+
+    /// The amount of token_0 fee collected from the previous range
+    pub fee_amount_0: u64,
+
+    /// The amount of token_1 fee collected from the previous range
+    pub fee_amount_1: u64,
+
+    /// The amount of the input token swapped in the pool
+    pub swap_amount_in: u64,
+
+    /// The amount of the output token received from the swap
+    pub swap_amount_out: u64,
+
+    /// The amount of token_0 that did not fit the new range, sent to the recipient
+    pub refund_amount_0: u64,
+
+    /// The amount of token_1 that did not fit the new range, sent to the recipient
+    pub refund_amount_1: u64,
+}
+
+/// Emitted when a single token is zapped into a position
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ZapInEvent {
+    /// The ID of the token for which liquidity was increased
+    #[index]
+    pub position_nft_mint: Pubkey,
+
+    /// Whether token_0 is the input, otherwise token_1
+    pub zero_for_one: bool,
+
+    /// The amount of the input token deposited
+    pub amount_in: u64,
+
+    /// The amount of the input token swapped in the pool
+    pub swap_amount_in: u64,
+
+    /// The amount of the output token received from the swap
+    pub swap_amount_out: u64,
+
+    /// The amount by which liquidity for the position was increased
+    pub liquidity: u128,
+
+    /// The amount of token_0 that did not fit the range, refunded
+    pub refund_amount_0: u64,
+
+    /// The amount of token_1 that did not fit the range, refunded
+    pub refund_amount_1: u64,
+}
+
+/// Emitted when liquidity of a position is withdrawn as a single token
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct ZapOutEvent {
+    /// The ID of the token for which liquidity was decreased
+    #[index]
+    pub position_nft_mint: Pubkey,
+
+    /// The amount by which liquidity for the position was decreased
+    pub liquidity: u128,
+
+    /// The amount of token_0 that was paid for the decrease in liquidity
+    pub decrease_amount_0: u64,
+
+    /// The amount of token_1 that was paid for the decrease in liquidity
+    pub decrease_amount_1: u64,
+
+    /// The amount of token_0 fee collected
+    pub fee_amount_0: u64,
+
+    /// The amount of token_1 fee collected
+    pub fee_amount_1: u64,
+
+    /// Whether token_0 is swapped into token_1, otherwise token_1 into token_0
+    pub zero_for_one: bool,
+
+    /// The amount of the unwanted token swapped in the pool
+    pub swap_amount_in: u64,
+
+    /// The amount of the output token received from the swap
+    pub swap_amount_out: u64,
+
+    /// The amount of the output token paid out
+    pub amount_out: u64,
+
+    /// The transfer fee of the output token paid out
+    pub transfer_fee: u64,
+}
+
+/// Emitted when positions sharing a range are merged into one
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct MergePositionsEvent {
+    /// The position the others are merged into
+    pub position_nft_mint: Pubkey,
+
+    /// The positions emptied by the merge
+    pub merged_position_nft_mints: Vec<Pubkey>,
+
+    /// The liquidity of the position after the merge
+    pub liquidity: u128,
+}
+
+/// Emitted when liquidity is split out of a position into a new one
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct SplitPositionEvent {
+    /// The position being split
+    pub position_nft_mint: Pubkey,
+
+    /// The new position
+    pub new_position_nft_mint: Pubkey,
+
+    /// The liquidity moved into the new position
+    pub liquidity: u128,
+}
+
+/// Emitted when a position is locked or its lock is extended
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct LockPositionEvent {
+    /// The locked position
+    pub position_nft_mint: Pubkey,
+
+    /// The time until which the liquidity of the position cannot be removed
+    pub unlock_timestamp: u64,
+
+    /// Whether the fees of the position can be collected while it is locked
+    pub allow_collect_fee: bool,
+}
+
 /// Emitted when Reward are updated for a pool
 #[event]
 #[cfg_attr(feature = "client", derive(Debug))]
@@ -234,3 +457,26 @@ pub struct UpdateRewardInfosEvent {
     /// Reward info
     pub reward_growth_global_x64: [u128; REWARD_NUM],
 }
+
+#[cfg(test)]
+mod personal_position_test {
+    use super::*;
+
+    #[test]
+    fn check_unlocked_test() {
+        let mut personal_position = PersonalPositionState::default();
+        assert!(!personal_position.is_locked(100));
+        assert!(personal_position.check_unlocked(10, 100).is_ok());
+
+        personal_position.unlock_timestamp = 200;
+        assert!(personal_position.is_locked(199));
+        assert!(!personal_position.is_locked(200));
+        assert!(personal_position.check_unlocked(10, 199).is_err());
+        assert!(personal_position.check_unlocked(0, 199).is_err());
+        assert!(personal_position.check_unlocked(10, 200).is_ok());
+
+        personal_position.lock_allow_collect_fee = true;
+        assert!(personal_position.check_unlocked(0, 199).is_ok());
+        assert!(personal_position.check_unlocked(10, 199).is_err());
+    }
+}
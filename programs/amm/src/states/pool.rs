@@ -40,6 +40,7 @@ pub enum PoolStatusBitIndex {
     CollectFee,
     CollectReward,
     Swap,
+    Flash,
 }
 
 #[derive(PartialEq, Eq)]
@@ -144,9 +145,37 @@ pub struct PoolState {
     // account recent update epoch
     pub recent_epoch: u64,
 
+    /// Volatility based swap fee parameters
+    pub dynamic_fee_info: DynamicFeeInfo,
+
+    /// The seconds per unit of in range liquidity accumulated by the pool, as a Q64.64.
+    /// Only the difference between two values is meaningful
+    pub seconds_per_liquidity_global_x64: u128,
+    /// The timestamp `seconds_per_liquidity_global_x64` was last accumulated to
+    pub seconds_per_liquidity_update_time: u64,
+
+    /// Fee rates replacing the amm config ones for this pool
+    pub fee_override: PoolFeeOverride,
+
+    /// The program called before and after every swap, the default pubkey if none
+    pub hook_program: Pubkey,
+
+    /// The emissions of the rewards of the reward extension, updated with the pool rewards
+    pub extension_reward_growths: [ExtensionRewardGrowth; EXTENSION_REWARD_NUM],
+
+    /// The in range liquidity weighted by the range tightness boost, the boosted rewards
+    /// are emitted over it instead of `liquidity`
+    pub boosted_liquidity: u128,
+    /// The range tightness boost of the rewards
+    pub reward_boost: RewardBoost,
+
+    /// The next emission epoch of each reward, rolled into once the current one has ended
+    pub reward_pending_epochs: [RewardEpoch; REWARD_NUM],
+    /// The amount funded up front for the epochs of each reward not rolled into yet
+    pub reward_scheduled_amounts: [u64; REWARD_NUM],
+
     // Unused bytes for future upgrades.
-    pub padding1: [u64; 24],
-    pub padding2: [u64; 32],
+    pub padding2: [u64; 1],
 }
 
 impl PoolState {
@@ -237,8 +266,17 @@ impl PoolState {
         self.fund_fees_token_1 = 0;
         self.open_time = open_time;
         self.recent_epoch = get_recent_epoch()?;
-        self.padding1 = [0; 24];
-        self.padding2 = [0; 32];
+        self.dynamic_fee_info = DynamicFeeInfo::default();
+        self.seconds_per_liquidity_global_x64 = 0;
+        self.seconds_per_liquidity_update_time = 0;
+        self.fee_override = PoolFeeOverride::default();
+        self.hook_program = Pubkey::default();
+        self.extension_reward_growths = [ExtensionRewardGrowth::default(); EXTENSION_REWARD_NUM];
+        self.boosted_liquidity = 0;
+        self.reward_boost = RewardBoost::default();
+        self.reward_pending_epochs = [RewardEpoch::default(); REWARD_NUM];
+        self.reward_scheduled_amounts = [0; REWARD_NUM];
+        self.padding2 = [0; 1];
         self.observation_key = observation_state_key;
 
         Ok(())
@@ -328,6 +366,9 @@ impl PoolState {
         #[cfg(feature = "enable-log")]
         msg!("current block timestamp:{}", curr_timestamp);
 
+        // Every change of the in range liquidity updates the rewards first, so is the time accumulator
+        self.update_seconds_per_liquidity(curr_timestamp);
+
         // Make a mutable copy of the current reward information array.
         let mut next_reward_infos = self.reward_infos;
 
@@ -340,78 +381,44 @@ impl PoolState {
                 continue;
             }
 
-            // If the current timestamp is less than or equal to the reward’s open time, skip it.
-            if curr_timestamp <= reward_info.open_time {
-                continue;
-            }
-
-            // Determine the latest possible update time, either the current timestamp or the reward’s end time, whichever is smaller.
-            let latest_update_timestamp = curr_timestamp.min(reward_info.end_time);
-
-            // Only proceed if there’s liquidity in the pool.
-            if self.liquidity != 0 {
-                // Ensure the latest update timestamp is not earlier than the last update time.
-                require_gte!(latest_update_timestamp, reward_info.last_update_time);
-                // Calculate the time difference since the last update.
-                let time_delta = latest_update_timestamp
-                    .checked_sub(reward_info.last_update_time)
-                    .unwrap();
-
-                // Calculate the reward growth increment based on emissions and liquidity.
-                let reward_growth_delta = U256::from(time_delta)
-                    .mul_div_floor(
-                        U256::from(reward_info.emissions_per_second_x64), // Emissions per second
-                        U256::from(self.liquidity), // Total liquidity in the pool
-                    )
-                    .unwrap();
-
-                // Update the global reward growth by adding the calculated increment.
-                reward_info.reward_growth_global_x64 = reward_info
-                    .reward_growth_global_x64
-                    .checked_add(reward_growth_delta.as_u128())
-                    .unwrap();
-
-                // Update the total rewards emitted by adding the increment based on time and emissions per second.
-                reward_info.reward_total_emissioned = reward_info
-                    .reward_total_emissioned
-                    .checked_add(
-                        U128::from(time_delta)
-                            .mul_div_ceil(
-                                U128::from(reward_info.emissions_per_second_x64),
-                                U128::from(fixed_point_64::Q64),
-                            )
-                            .unwrap()
-                            .as_u64(),
-                    )
-                    .unwrap();
-                #[cfg(feature = "enable-log")]
-                msg!(
-                    "reward_index:{},latest_update_timestamp:{},reward_info.reward_last_update_time:{},time_delta:{},reward_emission_per_second_x64:{},reward_growth_delta:{},reward_info.reward_growth_global_x64:{}, reward_info.reward_claim:{}",
-                    i,
-                    latest_update_timestamp,
-                    identity(reward_info.last_update_time),
-                    time_delta,
-                    identity(reward_info.emissions_per_second_x64),
-                    reward_growth_delta,
-                    identity(reward_info.reward_growth_global_x64),
-                    identity(reward_info.reward_claimed)
-                );
-            }
+            // A boosted reward is emitted over the boosted liquidity
+            let liquidity = if self.reward_boost.is_boosted(i) {
+                self.boosted_liquidity
+            } else {
+                self.liquidity
+            };
+            reward_info.update(curr_timestamp, liquidity)?;
 
-            // Update the last update time for the reward.
-            reward_info.last_update_time = latest_update_timestamp;
-            // update reward state
-            // Set the reward’s state based on whether the latest update time is within the reward's active period.
-            if latest_update_timestamp >= reward_info.open_time
-                && latest_update_timestamp < reward_info.end_time
+            // Roll into the pending epoch once the current one has ended
+            let pending_epoch = self.reward_pending_epochs[i];
+            if pending_epoch.initialized()
+                && reward_info.last_update_time == reward_info.end_time
+                && curr_timestamp > pending_epoch.open_time
             {
-                reward_info.reward_state = RewardState::Opening as u8;
-            } else if latest_update_timestamp == next_reward_infos[i].end_time {
-                next_reward_infos[i].reward_state = RewardState::Ended as u8;
+                self.reward_scheduled_amounts[i] = self.reward_scheduled_amounts[i]
+                    .saturating_sub(pending_epoch.get_reward_amount());
+                self.reward_pending_epochs[i] = RewardEpoch::default();
+                // An epoch moved in late still emits from its opening, but never over the time
+                // the previous epoch was emitting
+                reward_info.last_update_time = pending_epoch
+                    .open_time
+                    .max(reward_info.end_time)
+                    .min(pending_epoch.end_time);
+                reward_info.open_time = pending_epoch.open_time;
+                reward_info.end_time = pending_epoch.end_time;
+                reward_info.emissions_per_second_x64 = pending_epoch.emissions_per_second_x64;
+                reward_info.update(curr_timestamp, liquidity)?;
             }
         }
         // Update the pool's reward info array to the newly calculated values.
         self.reward_infos = next_reward_infos;
+
+        // The rewards of the reward extension are emitted over the same liquidity
+        let mut next_extension_reward_growths = self.extension_reward_growths;
+        for reward_growth in next_extension_reward_growths.iter_mut() {
+            reward_growth.update(curr_timestamp, self.liquidity)?;
+        }
+        self.extension_reward_growths = next_extension_reward_growths;
         #[cfg(feature = "enable-log")]
         msg!("update pool reward info, reward_0_total_emissioned:{}, reward_1_total_emissioned:{}, reward_2_total_emissioned:{}, pool.liquidity:{}",
         identity(self.reward_infos[0].reward_total_emissioned),identity(self.reward_infos[1].reward_total_emissioned),identity(self.reward_infos[2].reward_total_emissioned), identity(self.liquidity));
@@ -596,6 +603,30 @@ impl PoolState {
         }
     }
 
+    /// Returns the seconds per liquidity accumulator as of `curr_timestamp`. As in Uniswap v3,
+    /// the seconds without any in range liquidity are counted as if there was one unit.
+    pub fn get_seconds_per_liquidity_global(&self, curr_timestamp: u64) -> u128 {
+        // Pools created before the accumulator start counting from its first update
+        if self.seconds_per_liquidity_update_time == 0 {
+            return self.seconds_per_liquidity_global_x64;
+        }
+        let time_delta = curr_timestamp.saturating_sub(self.seconds_per_liquidity_update_time);
+        self.seconds_per_liquidity_global_x64.wrapping_add(
+            (u128::from(time_delta) << fixed_point_64::RESOLUTION) / self.liquidity.max(1),
+        )
+    }
+
+    /// Accumulates the seconds per liquidity up to `curr_timestamp`, must be called
+    /// before the in range liquidity changes
+    pub fn update_seconds_per_liquidity(&mut self, curr_timestamp: u64) {
+        if curr_timestamp <= self.seconds_per_liquidity_update_time {
+            return;
+        }
+        self.seconds_per_liquidity_global_x64 =
+            self.get_seconds_per_liquidity_global(curr_timestamp);
+        self.seconds_per_liquidity_update_time = curr_timestamp;
+    }
+
     pub fn set_status(&mut self, status: u8) {
         self.status = status
     }
@@ -616,6 +647,55 @@ impl PoolState {
         self.status.bitand(status) == 0
     }
 
+    /// Returns the trade, protocol and fund fee rates of the pool, from the fee override
+    /// if it is enabled and from the amm config otherwise
+    pub fn get_fee_rates(&self, amm_config: &AmmConfig) -> (u32, u32, u32) {
+        let fee_override = self.fee_override;
+        if fee_override.enabled {
+            (
+                fee_override.trade_fee_rate,
+                fee_override.protocol_fee_rate,
+                fee_override.fund_fee_rate,
+            )
+        } else {
+            (
+                amm_config.trade_fee_rate,
+                amm_config.protocol_fee_rate,
+                amm_config.fund_fee_rate,
+            )
+        }
+    }
+
+    /// Returns the trade fee rate a swap is charged.
+    /// Without the dynamic fee it is the base fee rate, otherwise the base fee rate plus a
+    /// volatility component, clamped to the configured bounds.
+    pub fn get_trade_fee_rate(
+        &self,
+        base_fee_rate: u32,
+        observation_state: &ObservationState,
+        observation_extension: &[Observation],
+        block_timestamp: u32,
+    ) -> u32 {
+        let dynamic_fee_info = self.dynamic_fee_info;
+        if !dynamic_fee_info.enabled {
+            return base_fee_rate;
+        }
+        // The volatility is measured as how far the price moved away from its recent average
+        let tick_deviation = observation_state.get_tick_deviation(
+            observation_extension,
+            block_timestamp,
+            self.tick_current,
+            dynamic_fee_info.volatility_window,
+        );
+        let fee_rate = u64::from(base_fee_rate).saturating_add(
+            u64::from(tick_deviation).saturating_mul(dynamic_fee_info.volatility_fee_rate.into()),
+        );
+        fee_rate.clamp(
+            dynamic_fee_info.min_fee_rate.into(),
+            dynamic_fee_info.max_fee_rate.into(),
+        ) as u32
+    }
+
     pub fn is_overflow_default_tickarray_bitmap(&self, tick_indexs: Vec<i32>) -> bool {
         let (min_tick_array_start_index_boundary, max_tick_array_index_boundary) =
             self.tick_array_start_index_range();
@@ -711,6 +791,89 @@ impl RewardInfo {
         self.token_mint.ne(&Pubkey::default())
     }
 
+    /// Emits the reward over `liquidity` up to the current timestamp, or its end time
+    pub fn update(&mut self, curr_timestamp: u64, liquidity: u128) -> Result<()> {
+        // If the current timestamp is less than or equal to the reward’s open time, skip it.
+        if curr_timestamp <= self.open_time {
+            return Ok(());
+        }
+
+        // Determine the latest possible update time, either the current timestamp or the reward’s end time, whichever is smaller.
+        let latest_update_timestamp = curr_timestamp.min(self.end_time);
+
+        // Only proceed if there’s liquidity in the pool.
+        if liquidity != 0 {
+            // Ensure the latest update timestamp is not earlier than the last update time.
+            require_gte!(latest_update_timestamp, self.last_update_time);
+            // Calculate the time difference since the last update.
+            let time_delta = latest_update_timestamp
+                .checked_sub(self.last_update_time)
+                .unwrap();
+
+            // Calculate the reward growth increment based on emissions and liquidity.
+            let reward_growth_delta = U256::from(time_delta)
+                .mul_div_floor(
+                    U256::from(self.emissions_per_second_x64), // Emissions per second
+                    U256::from(liquidity),                     // Total liquidity in the pool
+                )
+                .unwrap();
+
+            // Update the global reward growth by adding the calculated increment.
+            self.reward_growth_global_x64 = self
+                .reward_growth_global_x64
+                .checked_add(reward_growth_delta.as_u128())
+                .unwrap();
+
+            // Update the total rewards emitted by adding the increment based on time and emissions per second.
+            self.reward_total_emissioned = self
+                .reward_total_emissioned
+                .checked_add(
+                    U128::from(time_delta)
+                        .mul_div_ceil(
+                            U128::from(self.emissions_per_second_x64),
+                            U128::from(fixed_point_64::Q64),
+                        )
+                        .unwrap()
+                        .as_u64(),
+                )
+                .unwrap();
+            #[cfg(feature = "enable-log")]
+            msg!(
+                "latest_update_timestamp:{},reward_info.reward_last_update_time:{},time_delta:{},reward_emission_per_second_x64:{},reward_growth_delta:{},reward_info.reward_growth_global_x64:{}, reward_info.reward_claim:{}",
+                latest_update_timestamp,
+                identity(self.last_update_time),
+                time_delta,
+                identity(self.emissions_per_second_x64),
+                reward_growth_delta,
+                identity(self.reward_growth_global_x64),
+                identity(self.reward_claimed)
+            );
+        }
+
+        // Update the last update time for the reward.
+        self.last_update_time = latest_update_timestamp;
+        // update reward state
+        // Set the reward’s state based on whether the latest update time is within the reward's active period.
+        if latest_update_timestamp >= self.open_time && latest_update_timestamp < self.end_time {
+            self.reward_state = RewardState::Opening as u8;
+        } else if latest_update_timestamp == self.end_time {
+            self.reward_state = RewardState::Ended as u8;
+        }
+        Ok(())
+    }
+
+    /// Returns the seconds the reward amount lasts at the current emission rate
+    pub fn get_funded_period(&self, reward_amount: u64) -> u64 {
+        U256::from(reward_amount)
+            .mul_div_floor(
+                U256::from(fixed_point_64::Q64),
+                U256::from(self.emissions_per_second_x64),
+            )
+            .unwrap()
+            .min(U256::from(u64::MAX))
+            .as_u64()
+    }
+
     pub fn get_reward_growths(reward_infos: &[RewardInfo; REWARD_NUM]) -> [u128; REWARD_NUM] {
         let mut reward_growths = [0u128; REWARD_NUM];
         for i in 0..REWARD_NUM {
@@ -720,6 +883,152 @@ impl RewardInfo {
     }
 }
 
+/// A future emission epoch of a reward
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct RewardEpoch {
+    /// Epoch open time
+    pub open_time: u64,
+    /// Epoch end time, zero if there is no epoch
+    pub end_time: u64,
+    /// Q64.64 number indicates how many tokens per second are earned per unit of liquidity.
+    pub emissions_per_second_x64: u128,
+}
+
+impl RewardEpoch {
+    pub const LEN: usize = 8 + 8 + 16;
+
+    pub fn initialized(&self) -> bool {
+        self.end_time != 0
+    }
+
+    /// Returns the amount of reward emitted over the epoch
+    pub fn get_reward_amount(&self) -> u64 {
+        U256::from(self.end_time - self.open_time)
+            .mul_div_ceil(
+                U256::from(self.emissions_per_second_x64),
+                U256::from(fixed_point_64::Q64),
+            )
+            .unwrap()
+            .as_u64()
+    }
+}
+
+/// Parameters of the volatility based swap fee of a pool
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct DynamicFeeInfo {
+    /// Whether swaps are charged the dynamic fee instead of the amm config trade fee
+    pub enabled: bool,
+    /// The seconds of observation history the average tick is taken over
+    pub volatility_window: u32,
+    /// The fee rate added for every tick between the current tick and the average tick
+    pub volatility_fee_rate: u32,
+    /// The lower bound of the fee rate, denominated in hundredths of a bip (10^-6)
+    pub min_fee_rate: u32,
+    /// The upper bound of the fee rate, denominated in hundredths of a bip (10^-6)
+    pub max_fee_rate: u32,
+    /// padding for feature update
+    pub padding: [u8; 7],
+}
+
+impl DynamicFeeInfo {
+    pub const LEN: usize = 1 + 4 + 4 + 4 + 4 + 7;
+}
+
+/// Fee rates of a pool used instead of the ones of its amm config
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct PoolFeeOverride {
+    /// Whether the pool uses these fee rates instead of the amm config ones
+    pub enabled: bool,
+    /// The trade fee, denominated in hundredths of a bip (10^-6)
+    pub trade_fee_rate: u32,
+    /// The protocol fee, as a share of the trade fee
+    pub protocol_fee_rate: u32,
+    /// The fund fee, as a share of the trade fee
+    pub fund_fee_rate: u32,
+    /// padding for feature update
+    pub padding: [u8; 3],
+}
+
+impl PoolFeeOverride {
+    pub const LEN: usize = 1 + 4 + 4 + 4 + 3;
+}
+
+/// Denominator of the reward boost rates, a rate of `REWARD_BOOST_RATE_DENOMINATOR` is 1x
+pub const REWARD_BOOST_RATE_DENOMINATOR: u32 = 1_000_000;
+
+/// The shape of the reward boost against the width of a range
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardBoostCurve {
+    /// The boost is inversely proportional to the width of the range
+    Inverse = 0,
+    /// The boost falls linearly from the max boost to 1x at the reference width
+    Linear = 1,
+}
+
+/// Range tightness boost of the rewards of a pool. The liquidity of a range is weighted by
+/// a boost rate which grows as the range narrows, from 1x for ranges at least
+/// `reference_tick_width` wide up to `max_boost_rate`.
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct RewardBoost {
+    /// The bitmask of the rewards emitted over the boosted liquidity, bit i for reward i
+    pub boosted_reward_flags: u8,
+    /// The shape of the boost, see `RewardBoostCurve`
+    pub curve: u8,
+    /// The width in ticks of the ranges from which the liquidity is no longer boosted
+    pub reference_tick_width: u32,
+    /// The boost rate of the narrowest ranges, denominated in `REWARD_BOOST_RATE_DENOMINATOR`,
+    /// zero if no liquidity is boosted
+    pub max_boost_rate: u32,
+    /// padding for feature update
+    pub padding: [u8; 6],
+}
+
+impl RewardBoost {
+    pub const LEN: usize = 1 + 1 + 4 + 4 + 6;
+
+    pub fn is_boosted(&self, reward_index: usize) -> bool {
+        self.boosted_reward_flags & (1 << reward_index) != 0
+    }
+
+    /// Returns the boost rate of the liquidity of a range, zero if no liquidity is boosted
+    pub fn get_boost_rate(&self, tick_lower_index: i32, tick_upper_index: i32) -> u32 {
+        if self.max_boost_rate == 0 {
+            return 0;
+        }
+        let tick_width = u64::try_from(tick_upper_index - tick_lower_index).unwrap();
+        let reference_tick_width = u64::from(self.reference_tick_width);
+        let min_boost_rate = u64::from(REWARD_BOOST_RATE_DENOMINATOR);
+        let max_boost_rate = u64::from(self.max_boost_rate);
+        let boost_rate = if tick_width >= reference_tick_width {
+            min_boost_rate
+        } else if self.curve == RewardBoostCurve::Linear as u8 {
+            max_boost_rate - (max_boost_rate - min_boost_rate) * tick_width / reference_tick_width
+        } else {
+            min_boost_rate * reference_tick_width / tick_width
+        };
+        boost_rate.clamp(min_boost_rate, max_boost_rate) as u32
+    }
+
+    /// Returns the boosted liquidity of the given liquidity at the given boost rate
+    pub fn get_boosted_liquidity(liquidity: u128, boost_rate: u32) -> u128 {
+        U256::from(liquidity)
+            .mul_div_floor(
+                U256::from(boost_rate),
+                U256::from(REWARD_BOOST_RATE_DENOMINATOR),
+            )
+            .unwrap()
+            .as_u128()
+    }
+}
+
 /// Emitted when a pool is created and initialized with a starting price
 ///
 #[event]
@@ -749,6 +1058,9 @@ pub struct PoolCreatedEvent {
     pub token_vault_0: Pubkey,
     /// Vault of token_1
     pub token_vault_1: Pubkey,
+
+    /// The program called before and after every swap, the default pubkey if none
+    pub hook_program: Pubkey,
 }
 
 /// Emitted when the collected protocol fees are withdrawn by the factory owner
@@ -817,6 +1129,163 @@ pub struct SwapEvent {
 
     /// The log base 1.0001 of price of the pool after the swap
     pub tick: i32,
+
+    /// The trade fee rate charged by the swap, denominated in hundredths of a bip (10^-6)
+    pub trade_fee_rate: u32,
+
+    /// The total fee charged by the swap in the input token, including protocol, fund and referral fees
+    pub fee_amount: u64,
+
+    /// The part of the fee paid to the referrer in the input token
+    pub referral_fee: u64,
+}
+
+/// Emitted when a flash loan is repaid to a pool
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct FlashEvent {
+    /// The pool the tokens were borrowed from
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The address that initiated the flash loan
+    #[index]
+    pub sender: Pubkey,
+
+    /// The amount of token_0 lent
+    pub amount_0: u64,
+
+    /// The amount of token_1 lent
+    pub amount_1: u64,
+
+    /// The fee in token_0 the loan was charged
+    pub fee_0: u64,
+
+    /// The fee in token_1 the loan was charged
+    pub fee_1: u64,
+
+    /// The amount of token_0 paid to the pool on top of the loan, at least fee_0
+    pub paid_0: u64,
+
+    /// The amount of token_1 paid to the pool on top of the loan, at least fee_1
+    pub paid_1: u64,
+}
+
+/// Emitted when the dynamic fee parameters of a pool are updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolDynamicFeeChangeEvent {
+    /// The pool whose dynamic fee is updated
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// Whether the dynamic fee is enabled
+    pub enabled: bool,
+
+    /// The seconds of observation history the average tick is taken over
+    pub volatility_window: u32,
+
+    /// The fee rate added for every tick between the current tick and the average tick
+    pub volatility_fee_rate: u32,
+
+    /// The lower bound of the fee rate
+    pub min_fee_rate: u32,
+
+    /// The upper bound of the fee rate
+    pub max_fee_rate: u32,
+}
+
+/// Emitted when the fee override of a pool is updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolFeeChangeEvent {
+    /// The pool whose fee rates are updated
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// Whether the pool uses its own fee rates instead of the amm config ones
+    pub enabled: bool,
+
+    /// The trade fee rate of the pool
+    pub trade_fee_rate: u32,
+
+    /// The protocol fee rate of the pool
+    pub protocol_fee_rate: u32,
+
+    /// The fund fee rate of the pool
+    pub fund_fee_rate: u32,
+}
+
+/// Emitted when the reward boost of a pool is updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolRewardBoostChangeEvent {
+    /// The pool whose reward boost is updated
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The bitmask of the boosted rewards
+    pub boosted_reward_flags: u8,
+
+    /// The shape of the boost
+    pub curve: u8,
+
+    /// The width in ticks from which the liquidity is no longer boosted
+    pub reference_tick_width: u32,
+
+    /// The boost rate of the narrowest ranges
+    pub max_boost_rate: u32,
+}
+
+/// Emitted when the swap hook of a pool is updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct PoolHookChangeEvent {
+    /// The pool whose swap hook is updated
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The new hook program, the default pubkey if the hook is removed
+    pub hook_program: Pubkey,
+}
+
+/// Emitted when a reward is funded to extend its end time
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct RewardFundedEvent {
+    /// The pool of the reward
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The index of the reward in the pool
+    pub reward_index: u8,
+
+    /// The account which funded the reward
+    pub funder: Pubkey,
+
+    /// The amount received by the reward vault
+    pub amount: u64,
+
+    /// The new end time of the reward
+    pub end_time: u64,
+}
+
+/// Emitted when a running reward is cancelled
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct RewardCancelledEvent {
+    /// The pool of the reward
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The index of the reward in the pool
+    pub reward_index: u8,
+
+    /// The new end time of the reward
+    pub end_time: u64,
+
+    /// The unemitted amount refunded to the reward authority
+    pub refund_amount: u64,
 }
 
 /// Emitted pool liquidity change when increase and decrease liquidity
@@ -1071,6 +1540,261 @@ pub mod pool_test {
         }
     }
 
+    mod dynamic_fee_test {
+        use super::*;
+
+        fn build_observation_state(ticks: Vec<(u32, i32)>) -> ObservationState {
+            let mut observation_state = ObservationState::default();
+            for (block_timestamp, tick) in ticks {
+                observation_state.update(&mut [], block_timestamp, tick, 0);
+            }
+            observation_state
+        }
+
+        #[test]
+        fn pool_state_len_test() {
+            assert_eq!(PoolState::LEN, 8 + std::mem::size_of::<PoolState>());
+        }
+
+        #[test]
+        fn disabled_dynamic_fee_test() {
+            let mut pool_state = PoolState::default();
+            pool_state.tick_current = 1000;
+            let observation_state =
+                build_observation_state(vec![(1000, 100), (1015, 100), (1030, 100)]);
+            assert_eq!(
+                pool_state.get_trade_fee_rate(2500, &observation_state, &[], 1040),
+                2500
+            );
+        }
+
+        #[test]
+        fn dynamic_fee_follows_tick_deviation_test() {
+            let mut pool_state = PoolState::default();
+            pool_state.tick_current = 130;
+            pool_state.dynamic_fee_info = DynamicFeeInfo {
+                enabled: true,
+                volatility_window: 60,
+                volatility_fee_rate: 10,
+                min_fee_rate: 500,
+                max_fee_rate: 10000,
+                ..Default::default()
+            };
+            let observation_state =
+                build_observation_state(vec![(1000, 100), (1015, 100), (1030, 100)]);
+            assert_eq!(observation_state.get_tick_deviation(&[], 1040, 130, 60), 30);
+            assert_eq!(
+                pool_state.get_trade_fee_rate(2500, &observation_state, &[], 1040),
+                2500 + 30 * 10
+            );
+
+            // clamped by the max fee rate
+            pool_state.dynamic_fee_info.max_fee_rate = 2600;
+            assert_eq!(
+                pool_state.get_trade_fee_rate(2500, &observation_state, &[], 1040),
+                2600
+            );
+
+            // clamped by the min fee rate
+            pool_state.dynamic_fee_info.min_fee_rate = 2600;
+            pool_state.tick_current = 100;
+            assert_eq!(
+                pool_state.get_trade_fee_rate(2500, &observation_state, &[], 1040),
+                2600
+            );
+        }
+
+        #[test]
+        fn no_observation_history_test() {
+            let observation_state = build_observation_state(vec![(1000, 100)]);
+            assert_eq!(observation_state.get_tick_deviation(&[], 1040, 130, 60), 0);
+            assert_eq!(
+                ObservationState::default().get_tick_deviation(&[], 1040, 130, 60),
+                0
+            );
+        }
+    }
+
+    mod fee_override_test {
+        use super::*;
+
+        #[test]
+        fn get_fee_rates_test() {
+            let amm_config = AmmConfig {
+                trade_fee_rate: 2500,
+                protocol_fee_rate: 120000,
+                fund_fee_rate: 40000,
+                ..Default::default()
+            };
+            let mut pool_state = PoolState::default();
+            pool_state.fee_override = PoolFeeOverride {
+                enabled: false,
+                trade_fee_rate: 100,
+                protocol_fee_rate: 0,
+                fund_fee_rate: 10000,
+                ..Default::default()
+            };
+            assert_eq!(pool_state.get_fee_rates(&amm_config), (2500, 120000, 40000));
+
+            pool_state.fee_override.enabled = true;
+            assert_eq!(pool_state.get_fee_rates(&amm_config), (100, 0, 10000));
+        }
+    }
+
+    mod reward_boost_test {
+        use super::*;
+
+        #[test]
+        fn get_boost_rate_test() {
+            let mut reward_boost = RewardBoost::default();
+            // boost is off
+            assert_eq!(reward_boost.get_boost_rate(-10, 10), 0);
+
+            reward_boost.reference_tick_width = 1000;
+            reward_boost.max_boost_rate = 5 * REWARD_BOOST_RATE_DENOMINATOR;
+            assert_eq!(reward_boost.get_boost_rate(-1000, 1000), 1_000_000);
+            assert_eq!(reward_boost.get_boost_rate(0, 1000), 1_000_000);
+            assert_eq!(reward_boost.get_boost_rate(0, 500), 2_000_000);
+            // capped
+            assert_eq!(reward_boost.get_boost_rate(0, 100), 5_000_000);
+
+            reward_boost.curve = RewardBoostCurve::Linear as u8;
+            assert_eq!(reward_boost.get_boost_rate(0, 1000), 1_000_000);
+            assert_eq!(reward_boost.get_boost_rate(0, 500), 3_000_000);
+            assert_eq!(reward_boost.get_boost_rate(0, 100), 4_600_000);
+        }
+
+        #[test]
+        fn boosted_reward_emission_test() {
+            let pool_state = &mut PoolState::default();
+            pool_state.reward_infos[0].token_mint = Pubkey::new_unique();
+            pool_state.reward_infos[0].emissions_per_second_x64 = 1 << 64;
+            pool_state.reward_infos[0].end_time = 1000;
+            pool_state.reward_infos[1] = pool_state.reward_infos[0];
+            pool_state.reward_boost.boosted_reward_flags = 0b10;
+            pool_state.liquidity = 100;
+            pool_state.boosted_liquidity = 400;
+
+            let reward_infos = pool_state.update_reward_infos(100).unwrap();
+            assert_eq!({ reward_infos[0].reward_growth_global_x64 }, 1 << 64);
+            assert_eq!({ reward_infos[1].reward_growth_global_x64 }, 1 << 62);
+            assert_eq!({ reward_infos[1].reward_total_emissioned }, 100);
+        }
+    }
+
+    mod reward_epoch_test {
+        use super::*;
+
+        #[test]
+        fn roll_into_pending_epoch_test() {
+            let pool_state = &mut PoolState::default();
+            pool_state.liquidity = 1 << 64;
+            pool_state.reward_infos[0] = RewardInfo {
+                token_mint: Pubkey::new_unique(),
+                open_time: 100,
+                end_time: 200,
+                last_update_time: 100,
+                emissions_per_second_x64: 1 << 64,
+                ..Default::default()
+            };
+            let pending_epoch = RewardEpoch {
+                open_time: 300,
+                end_time: 400,
+                emissions_per_second_x64: 2 << 64,
+            };
+            pool_state.reward_pending_epochs[0] = pending_epoch;
+            pool_state.reward_scheduled_amounts[0] = pending_epoch.get_reward_amount();
+            assert_eq!(pending_epoch.get_reward_amount(), 200);
+
+            // the pending epoch is not due yet
+            pool_state.update_reward_infos(250).unwrap();
+            assert_eq!({ pool_state.reward_infos[0].reward_total_emissioned }, 100);
+            assert_eq!(pool_state.reward_pending_epochs[0], pending_epoch);
+
+            pool_state.update_reward_infos(350).unwrap();
+            let reward_info = pool_state.reward_infos[0];
+            assert_eq!({ reward_info.open_time }, 300);
+            assert_eq!({ reward_info.end_time }, 400);
+            assert_eq!({ reward_info.last_update_time }, 350);
+            assert_eq!({ reward_info.reward_total_emissioned }, 200);
+            assert_eq!({ reward_info.reward_growth_global_x64 }, 200);
+            assert!(!pool_state.reward_pending_epochs[0].initialized());
+            assert_eq!({ pool_state.reward_scheduled_amounts[0] }, 0);
+        }
+
+        #[test]
+        fn late_roll_test() {
+            let pool_state = &mut PoolState::default();
+            pool_state.liquidity = 1 << 64;
+            pool_state.reward_infos[0] = RewardInfo {
+                token_mint: Pubkey::new_unique(),
+                open_time: 100,
+                end_time: 200,
+                last_update_time: 100,
+                emissions_per_second_x64: 1 << 64,
+                ..Default::default()
+            };
+            pool_state.update_reward_infos(320).unwrap();
+
+            // the epoch is moved in after it opened, it still emits from its opening
+            pool_state.reward_pending_epochs[0] = RewardEpoch {
+                open_time: 300,
+                end_time: 400,
+                emissions_per_second_x64: 1 << 64,
+            };
+            pool_state.update_reward_infos(320).unwrap();
+            let reward_info = pool_state.reward_infos[0];
+            assert_eq!({ reward_info.open_time }, 300);
+            assert_eq!({ reward_info.last_update_time }, 320);
+            assert_eq!({ reward_info.reward_total_emissioned }, 120);
+
+            pool_state.update_reward_infos(350).unwrap();
+            assert_eq!({ pool_state.reward_infos[0].reward_total_emissioned }, 150);
+            pool_state.update_reward_infos(500).unwrap();
+            assert_eq!({ pool_state.reward_infos[0].reward_total_emissioned }, 200);
+        }
+    }
+
+    mod reward_fund_test {
+        use super::*;
+
+        #[test]
+        fn get_funded_period_test() {
+            let reward_info = RewardInfo {
+                emissions_per_second_x64: 3 << 63,
+                ..Default::default()
+            };
+            assert_eq!(reward_info.get_funded_period(3000), 2000);
+            assert_eq!(reward_info.get_funded_period(1), 0);
+        }
+    }
+
+    mod seconds_per_liquidity_test {
+        use super::*;
+        use std::convert::identity;
+
+        #[test]
+        fn seconds_per_liquidity_global_test() {
+            let mut pool_state = PoolState::default();
+            pool_state.liquidity = 4;
+            // starts counting from the first update
+            pool_state.update_seconds_per_liquidity(1000);
+            assert_eq!(identity(pool_state.seconds_per_liquidity_global_x64), 0);
+            assert_eq!(pool_state.get_seconds_per_liquidity_global(1020), 5 << 64);
+
+            pool_state.update_seconds_per_liquidity(1020);
+            pool_state.liquidity = 0;
+            // as if there was one unit without any liquidity
+            assert_eq!(
+                pool_state.get_seconds_per_liquidity_global(1030),
+                (5 << 64) + (10 << 64)
+            );
+            // time can't go backwards
+            pool_state.update_seconds_per_liquidity(1010);
+            assert_eq!(identity(pool_state.seconds_per_liquidity_update_time), 1020);
+        }
+    }
+
     mod update_reward_infos_test {
         use super::*;
         use anchor_lang::prelude::Pubkey;
@@ -1,6 +1,6 @@
 use crate::libraries::tick_math;
-use crate::libraries::{big_num::U128, full_math::MulDiv};
-use crate::pool::REWARD_NUM;
+use crate::libraries::{big_num::U128, full_math::MulDiv, U256};
+use crate::pool::{RewardBoost, REWARD_NUM};
 use crate::util::get_recent_epoch;
 use crate::{
     error::ErrorCode,
@@ -42,12 +42,17 @@ pub struct ProtocolPositionState {
     /// The fees owed to the position owner in token_1
     pub token_fees_owed_1: u64,
 
-    /// The reward growth per unit of liquidity as of the last update to liquidity
+    /// The reward growth per unit of liquidity as of the last update to liquidity,
+    /// for a boosted reward it is accumulated at the boost rate of the position
     pub reward_growth_inside: [u128; REWARD_NUM], // 24
     // account update recent epoch
     pub recent_epoch: u64,
+    /// The reward boost rate of the position as of the last update, zero if not boosted
+    pub boost_rate: u32,
+    /// The boosted reward growth per unit of boosted liquidity as of the last update
+    pub boosted_reward_growth_inside_last_x64: [u128; REWARD_NUM],
     // Unused bytes for future upgrades.
-    pub padding: [u64; 7],
+    pub padding: [u8; 4],
 }
 
 impl ProtocolPositionState {
@@ -61,6 +66,8 @@ impl ProtocolPositionState {
         fee_growth_inside_0_x64: u128,
         fee_growth_inside_1_x64: u128,
         reward_growths_inside: [u128; REWARD_NUM],
+        boosted_reward_flags: u8,
+        boost_rate: u32,
     ) -> Result<()> {
         require!(
             tick_lower_index >= tick_math::MIN_TICK && tick_lower_index <= tick_math::MAX_TICK,
@@ -89,6 +96,12 @@ impl ProtocolPositionState {
                 .unwrap()
                 .to_underflow_u64();
 
+        // Accumulate the boosted rewards at the boost rate they were earned at, before the
+        // liquidity changes
+        let reward_growths_inside =
+            self.accumulate_boosted_reward_growths(reward_growths_inside, boosted_reward_flags);
+        self.boost_rate = boost_rate;
+
         // Update the position
         if liquidity_delta != 0 {
             self.liquidity = liquidity_next;
@@ -111,8 +124,105 @@ impl ProtocolPositionState {
         Ok(())
     }
 
+    /// Returns the change of the boosted liquidity of the position when its liquidity changes by
+    /// `liquidity_delta` and its boost rate becomes `boost_rate`
+    pub fn get_boosted_liquidity_delta(
+        &self,
+        liquidity_delta: i128,
+        boost_rate: u32,
+    ) -> Result<i128> {
+        let boosted_liquidity_before =
+            RewardBoost::get_boosted_liquidity(self.liquidity, self.boost_rate);
+        let boosted_liquidity_after = RewardBoost::get_boosted_liquidity(
+            liquidity_math::add_delta(self.liquidity, liquidity_delta)?,
+            boost_rate,
+        );
+        Ok(i128::try_from(boosted_liquidity_after).unwrap()
+            - i128::try_from(boosted_liquidity_before).unwrap())
+    }
+
+    /// Converts the growths of the boosted rewards per unit of boosted liquidity into growths
+    /// per unit of liquidity of the position, leaving the other rewards as they are
+    fn accumulate_boosted_reward_growths(
+        &mut self,
+        reward_growths_inside: [u128; REWARD_NUM],
+        boosted_reward_flags: u8,
+    ) -> [u128; REWARD_NUM] {
+        let mut accumulated_reward_growths = reward_growths_inside;
+        let boosted_liquidity = RewardBoost::get_boosted_liquidity(self.liquidity, self.boost_rate);
+        for i in 0..REWARD_NUM {
+            if boosted_reward_flags & (1 << i) == 0 {
+                continue;
+            }
+            let growth_delta = reward_growths_inside[i]
+                .wrapping_sub(self.boosted_reward_growth_inside_last_x64[i]);
+            let accumulated_delta = if self.liquidity == 0 {
+                0
+            } else {
+                U256::from(growth_delta)
+                    .mul_div_floor(U256::from(boosted_liquidity), U256::from(self.liquidity))
+                    .unwrap()
+                    .as_u128()
+            };
+            accumulated_reward_growths[i] =
+                self.reward_growth_inside[i].wrapping_add(accumulated_delta);
+            self.boosted_reward_growth_inside_last_x64[i] = reward_growths_inside[i];
+        }
+        accumulated_reward_growths
+    }
+
     pub fn update_reward_growths_inside(&mut self, reward_growths_inside: [u128; REWARD_NUM]) {
         // just record, calculate reward owed in personal position
         self.reward_growth_inside = reward_growths_inside;
     }
 }
+
+#[cfg(test)]
+mod protocol_position_test {
+    use super::*;
+    use crate::pool::REWARD_BOOST_RATE_DENOMINATOR;
+
+    #[test]
+    fn accumulate_boosted_reward_growths_test() {
+        let protocol_position = &mut ProtocolPositionState::default();
+        protocol_position.liquidity = 1000;
+        protocol_position.boost_rate = 2 * REWARD_BOOST_RATE_DENOMINATOR;
+        protocol_position.reward_growth_inside = [10, 10, 10];
+        protocol_position.boosted_reward_growth_inside_last_x64 = [0, 100, 0];
+
+        // only the second reward is boosted
+        let reward_growths_inside =
+            protocol_position.accumulate_boosted_reward_growths([50, 150, 50], 0b10);
+        assert_eq!(reward_growths_inside, [50, 110, 50]);
+        assert_eq!(
+            protocol_position.boosted_reward_growth_inside_last_x64,
+            [0, 150, 0]
+        );
+    }
+
+    #[test]
+    fn get_boosted_liquidity_delta_test() {
+        let protocol_position = &mut ProtocolPositionState::default();
+        protocol_position.liquidity = 1000;
+        protocol_position.boost_rate = 2 * REWARD_BOOST_RATE_DENOMINATOR;
+        assert_eq!(
+            protocol_position
+                .get_boosted_liquidity_delta(500, 3 * REWARD_BOOST_RATE_DENOMINATOR)
+                .unwrap(),
+            2500
+        );
+        assert_eq!(
+            protocol_position
+                .get_boosted_liquidity_delta(-1000, 0)
+                .unwrap(),
+            -2000
+        );
+        // a lowered boost reprices the position without a liquidity change
+        assert_eq!(
+            protocol_position
+                .get_boosted_liquidity_delta(0, REWARD_BOOST_RATE_DENOMINATOR)
+                .unwrap(),
+            -1000
+        );
+    }
+}
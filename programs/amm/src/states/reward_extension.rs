@@ -0,0 +1,468 @@
+use crate::error::ErrorCode;
+use crate::libraries::{
+    big_num::{U128, U256},
+    fixed_point_64,
+    full_math::MulDiv,
+};
+use crate::states::{PositionRewardInfo, TickArrayState, TICK_ARRAY_SIZE_USIZE};
+use crate::util::AccountLoad;
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+/// Seed to derive account address and signature
+pub const REWARD_EXTENSION_SEED: &str = "pool_reward_extension";
+pub const TICK_ARRAY_REWARD_EXTENSION_SEED: &str = "tick_array_reward_extension";
+pub const POSITION_REWARD_EXTENSION_SEED: &str = "position_reward_extension";
+// Fixed number of reward streams of the reward extension, on top of the pool ones. Their
+// growths are kept in the pool state, so it can't grow without a pool account migration.
+pub const EXTENSION_REWARD_NUM: usize = 3;
+
+/// The emission state of a reward stream of the reward extension.
+/// It is kept in the pool state, so it is brought up to date on every change of the in range
+/// liquidity like the pool rewards, while the rest of the stream is kept in the `RewardExtension`.
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct ExtensionRewardGrowth {
+    /// Reward open time
+    pub open_time: u64,
+    /// Reward end time
+    pub end_time: u64,
+    /// Reward last update time
+    pub last_update_time: u64,
+    /// Q64.64 number indicates how many tokens per second are earned per unit of liquidity.
+    pub emissions_per_second_x64: u128,
+    /// The total amount of reward emissioned
+    pub reward_total_emissioned: u64,
+    /// Q64.64 number that tracks the total tokens earned per unit of liquidity since the reward
+    /// emissions were turned on.
+    pub reward_growth_global_x64: u128,
+}
+
+impl ExtensionRewardGrowth {
+    pub const LEN: usize = 8 + 8 + 8 + 16 + 8 + 16;
+
+    /// Returns true if this reward is initialized, the emissions of a reward are never zero
+    pub fn initialized(&self) -> bool {
+        self.emissions_per_second_x64 != 0
+    }
+
+    /// Accrues the emissions of the reward up to the given timestamp over the in range liquidity
+    pub fn update(&mut self, curr_timestamp: u64, liquidity: u128) -> Result<()> {
+        if !self.initialized() || curr_timestamp <= self.open_time {
+            return Ok(());
+        }
+        let latest_update_timestamp = curr_timestamp.min(self.end_time);
+        if liquidity != 0 {
+            require_gte!(latest_update_timestamp, self.last_update_time);
+            let time_delta = latest_update_timestamp - self.last_update_time;
+
+            let reward_growth_delta = U256::from(time_delta)
+                .mul_div_floor(
+                    U256::from(self.emissions_per_second_x64),
+                    U256::from(liquidity),
+                )
+                .unwrap();
+            self.reward_growth_global_x64 = self
+                .reward_growth_global_x64
+                .checked_add(reward_growth_delta.as_u128())
+                .unwrap();
+            self.reward_total_emissioned = self
+                .reward_total_emissioned
+                .checked_add(
+                    U128::from(time_delta)
+                        .mul_div_ceil(
+                            U128::from(self.emissions_per_second_x64),
+                            U128::from(fixed_point_64::Q64),
+                        )
+                        .unwrap()
+                        .as_u64(),
+                )
+                .unwrap();
+        }
+        self.last_update_time = latest_update_timestamp;
+        Ok(())
+    }
+
+    pub fn get_reward_growths(
+        reward_growths: &[ExtensionRewardGrowth; EXTENSION_REWARD_NUM],
+    ) -> [u128; EXTENSION_REWARD_NUM] {
+        let mut growths = [0u128; EXTENSION_REWARD_NUM];
+        for i in 0..EXTENSION_REWARD_NUM {
+            growths[i] = reward_growths[i].reward_growth_global_x64;
+        }
+        growths
+    }
+}
+
+/// The up to `EXTENSION_REWARD_NUM` reward streams a pool offers on top of its `REWARD_NUM` rewards
+///
+/// PDA of `[REWARD_EXTENSION_SEED, pool]`
+#[account]
+#[derive(Default, Debug)]
+pub struct RewardExtension {
+    /// Bump to identify PDA
+    pub bump: u8,
+
+    /// The pool the rewards are offered in
+    pub pool_id: Pubkey,
+
+    /// The rewards, their emissions are tracked by `PoolState::extension_reward_growths`
+    pub reward_infos: [ExtensionRewardInfo; EXTENSION_REWARD_NUM],
+
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 16],
+}
+
+impl RewardExtension {
+    pub const LEN: usize = 8 + 1 + 32 + ExtensionRewardInfo::LEN * EXTENSION_REWARD_NUM + 8 * 16;
+}
+
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Default, Debug, PartialEq)]
+pub struct ExtensionRewardInfo {
+    /// Reward token mint.
+    pub token_mint: Pubkey,
+    /// Reward vault token account.
+    pub token_vault: Pubkey,
+    /// The funder of the reward
+    pub authority: Pubkey,
+    /// The total amount of claimed reward
+    pub reward_claimed: u64,
+}
+
+impl ExtensionRewardInfo {
+    pub const LEN: usize = 32 + 32 + 32 + 8;
+
+    pub fn initialized(&self) -> bool {
+        self.token_mint != Pubkey::default()
+    }
+}
+
+/// The reward growths outside the ticks of a tick array for the rewards of the reward extension.
+/// Once it exists, every swap crossing a tick of the array must pass it in.
+///
+/// PDA of `[TICK_ARRAY_REWARD_EXTENSION_SEED, pool, start_tick_index]`
+#[account(zero_copy(unsafe))]
+#[repr(packed)]
+pub struct TickArrayRewardExtension {
+    pub pool_id: Pubkey,
+    pub start_tick_index: i32,
+    // Reward growth per unit of liquidity outside every tick of the tick array, array of Q64.64
+    pub reward_growths_outside_x64: [[u128; EXTENSION_REWARD_NUM]; TICK_ARRAY_SIZE_USIZE],
+    // Unused bytes for future upgrades.
+    pub padding: [u8; 64],
+}
+
+impl TickArrayRewardExtension {
+    pub const LEN: usize = 8 + 32 + 4 + 16 * EXTENSION_REWARD_NUM * TICK_ARRAY_SIZE_USIZE + 64;
+
+    /// Initializes the reward growths outside of the initialized ticks of the tick array,
+    /// assuming like the tick itself that all growth happened below them
+    pub fn initialize(
+        &mut self,
+        tick_array: &TickArrayState,
+        tick_current: i32,
+        reward_growths: &[ExtensionRewardGrowth; EXTENSION_REWARD_NUM],
+    ) {
+        self.pool_id = tick_array.pool_id;
+        self.start_tick_index = tick_array.start_tick_index;
+        let reward_growths_global = ExtensionRewardGrowth::get_reward_growths(reward_growths);
+        for (i, tick_state) in tick_array.ticks.iter().enumerate() {
+            if tick_state.is_initialized() && tick_state.tick <= tick_current {
+                self.reward_growths_outside_x64[i] = reward_growths_global;
+            }
+        }
+    }
+
+    fn get_tick_offset(&self, tick_index: i32, tick_spacing: u16) -> Result<usize> {
+        require_eq!(
+            TickArrayState::get_array_start_index(tick_index, tick_spacing),
+            self.start_tick_index,
+            ErrorCode::InvalidTickArray
+        );
+        Ok(((tick_index - self.start_tick_index) / i32::from(tick_spacing)) as usize)
+    }
+
+    pub fn get_reward_growths_outside(
+        &self,
+        tick_index: i32,
+        tick_spacing: u16,
+    ) -> Result<[u128; EXTENSION_REWARD_NUM]> {
+        let offset = self.get_tick_offset(tick_index, tick_spacing)?;
+        Ok(self.reward_growths_outside_x64[offset])
+    }
+
+    /// Flips the reward growths outside of a tick crossed by the price, like `TickState::cross`
+    pub fn cross(
+        &mut self,
+        tick_index: i32,
+        tick_spacing: u16,
+        reward_growths: &[ExtensionRewardGrowth; EXTENSION_REWARD_NUM],
+    ) -> Result<()> {
+        let offset = self.get_tick_offset(tick_index, tick_spacing)?;
+        let mut reward_growths_outside = self.reward_growths_outside_x64[offset];
+        for i in 0..EXTENSION_REWARD_NUM {
+            if !reward_growths[i].initialized() {
+                continue;
+            }
+            reward_growths_outside[i] = reward_growths[i]
+                .reward_growth_global_x64
+                .checked_sub(reward_growths_outside[i])
+                .unwrap();
+        }
+        self.reward_growths_outside_x64[offset] = reward_growths_outside;
+        Ok(())
+    }
+}
+
+/// The rewards of the reward extension owed to a personal position.
+/// While it exists, the liquidity of the position can only change once it is updated in the
+/// same transaction, see `PersonalPositionState::check_reward_extension_updated`.
+///
+/// PDA of `[POSITION_REWARD_EXTENSION_SEED, personal_position]`
+#[account]
+#[derive(Default, Debug)]
+pub struct PositionRewardExtension {
+    /// Bump to identify PDA
+    pub bump: u8,
+
+    /// The personal position earning the rewards
+    pub personal_position: Pubkey,
+
+    /// The range the reward growths inside were last taken over, the position may have been
+    /// moved to another one since
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+
+    // Position reward info
+    pub reward_infos: [PositionRewardInfo; EXTENSION_REWARD_NUM],
+
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 8],
+}
+
+impl PositionRewardExtension {
+    pub const LEN: usize =
+        8 + 1 + 32 + 4 + 4 + PositionRewardInfo::LEN * EXTENSION_REWARD_NUM + 8 * 8;
+
+    pub fn key(personal_position: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                POSITION_REWARD_EXTENSION_SEED.as_bytes(),
+                personal_position.as_ref(),
+            ],
+            &crate::id(),
+        )
+        .0
+    }
+
+    /// Accrues the rewards owed to the position since the last update. The growths are only
+    /// taken as a new starting point when the position moved to another range since.
+    pub fn update_rewards(
+        &mut self,
+        reward_growths_inside: [u128; EXTENSION_REWARD_NUM],
+        liquidity: u128,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+    ) {
+        let add_delta =
+            self.tick_lower_index == tick_lower_index && self.tick_upper_index == tick_upper_index;
+        for i in 0..EXTENSION_REWARD_NUM {
+            let curr_reward_info = self.reward_infos[i];
+            if add_delta {
+                let reward_growth_delta =
+                    reward_growths_inside[i].wrapping_sub(curr_reward_info.growth_inside_last_x64);
+                let amount_owed_delta = U256::from(reward_growth_delta)
+                    .mul_div_floor(U256::from(liquidity), U256::from(fixed_point_64::Q64))
+                    .unwrap()
+                    .to_underflow_u64();
+                self.reward_infos[i].reward_amount_owed = curr_reward_info
+                    .reward_amount_owed
+                    .checked_add(amount_owed_delta)
+                    .unwrap();
+            }
+            self.reward_infos[i].growth_inside_last_x64 = reward_growths_inside[i];
+        }
+        self.tick_lower_index = tick_lower_index;
+        self.tick_upper_index = tick_upper_index;
+    }
+}
+
+/// Calculates the extension reward growths inside of a range from the reward growths outside of
+/// its ticks, like `get_reward_growths_inside`
+pub fn get_extension_reward_growths_inside(
+    tick_lower_index: i32,
+    reward_growths_outside_lower: [u128; EXTENSION_REWARD_NUM],
+    tick_upper_index: i32,
+    reward_growths_outside_upper: [u128; EXTENSION_REWARD_NUM],
+    tick_current_index: i32,
+    reward_growths: &[ExtensionRewardGrowth; EXTENSION_REWARD_NUM],
+) -> [u128; EXTENSION_REWARD_NUM] {
+    let mut reward_growths_inside = [0; EXTENSION_REWARD_NUM];
+    for i in 0..EXTENSION_REWARD_NUM {
+        if !reward_growths[i].initialized() {
+            continue;
+        }
+        let reward_growth_global = reward_growths[i].reward_growth_global_x64;
+        let reward_growth_below = if tick_current_index >= tick_lower_index {
+            reward_growths_outside_lower[i]
+        } else {
+            reward_growth_global.wrapping_sub(reward_growths_outside_lower[i])
+        };
+        let reward_growth_above = if tick_current_index < tick_upper_index {
+            reward_growths_outside_upper[i]
+        } else {
+            reward_growth_global.wrapping_sub(reward_growths_outside_upper[i])
+        };
+        reward_growths_inside[i] = reward_growth_global
+            .wrapping_sub(reward_growth_below)
+            .wrapping_sub(reward_growth_above);
+    }
+    reward_growths_inside
+}
+
+/// Splits the tick array reward extensions out of the remaining accounts of a swap
+pub fn split_tick_array_reward_extensions<'c, 'info>(
+    remaining_accounts: Vec<&'c AccountInfo<'info>>,
+) -> (Vec<&'c AccountInfo<'info>>, Vec<&'c AccountInfo<'info>>) {
+    remaining_accounts.into_iter().partition(|account_info| {
+        *account_info.owner == crate::id()
+            && account_info
+                .try_borrow_data()
+                .map(|data| {
+                    data.len() >= 8 && data[..8] == TickArrayRewardExtension::discriminator()
+                })
+                .unwrap_or(false)
+    })
+}
+
+/// Flips the extension reward growths outside of the ticks crossed by a swap. The reward growths
+/// do not change during a swap, so the ticks can be flipped once it is done.
+pub fn cross_tick_array_reward_extensions(
+    pool_id: Pubkey,
+    tick_spacing: u16,
+    reward_growths: &[ExtensionRewardGrowth; EXTENSION_REWARD_NUM],
+    tick_array_reward_extensions: &[&AccountInfo],
+    crossed_ticks: &[i32],
+) -> Result<()> {
+    for tick_index in crossed_ticks {
+        let start_tick_index = TickArrayState::get_array_start_index(*tick_index, tick_spacing);
+        let mut tick_array_reward_extension = None;
+        for account_info in tick_array_reward_extensions {
+            let extension = AccountLoad::<TickArrayRewardExtension>::load_data_mut(account_info)?;
+            if extension.pool_id == pool_id && extension.start_tick_index == start_tick_index {
+                tick_array_reward_extension = Some(extension);
+                break;
+            }
+        }
+        tick_array_reward_extension
+            .ok_or(ErrorCode::MissingTickArrayRewardExtension)?
+            .cross(*tick_index, tick_spacing, reward_growths)?;
+    }
+    Ok(())
+}
+
+/// Emitted when the extension rewards of a position are collected
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct CollectExtensionRewardsEvent {
+    /// The ID of the token for which rewards were collected
+    pub position_nft_mint: Pubkey,
+
+    /// The amount of every extension reward collected
+    pub reward_amounts: [u64; EXTENSION_REWARD_NUM],
+}
+
+#[cfg(test)]
+mod reward_extension_test {
+    use super::*;
+
+    fn build_reward_growths(
+        reward_growth_global_x64: u128,
+    ) -> [ExtensionRewardGrowth; EXTENSION_REWARD_NUM] {
+        let mut reward_growths = [ExtensionRewardGrowth::default(); EXTENSION_REWARD_NUM];
+        reward_growths[0].emissions_per_second_x64 = fixed_point_64::Q64;
+        reward_growths[0].reward_growth_global_x64 = reward_growth_global_x64;
+        reward_growths
+    }
+
+    #[test]
+    fn update_test() {
+        let mut reward_growth = ExtensionRewardGrowth {
+            open_time: 100,
+            end_time: 200,
+            last_update_time: 100,
+            emissions_per_second_x64: 10 * fixed_point_64::Q64,
+            ..Default::default()
+        };
+        reward_growth.update(100, 10).unwrap();
+        assert_eq!({ reward_growth.reward_total_emissioned }, 0);
+
+        reward_growth.update(150, 10).unwrap();
+        assert_eq!({ reward_growth.reward_total_emissioned }, 500);
+        assert_eq!(
+            { reward_growth.reward_growth_global_x64 },
+            50 * fixed_point_64::Q64
+        );
+
+        // Nothing is emitted past the end time
+        reward_growth.update(300, 10).unwrap();
+        assert_eq!({ reward_growth.reward_total_emissioned }, 1000);
+        assert_eq!({ reward_growth.last_update_time }, 200);
+    }
+
+    #[test]
+    fn reward_growths_inside_test() {
+        let q64 = fixed_point_64::Q64;
+        // The range [-10, 10) was crossed left to right at growth 10 by its lower tick,
+        // its upper tick was initialized above the price
+        let mut reward_growths_outside_lower = [0; EXTENSION_REWARD_NUM];
+        reward_growths_outside_lower[0] = 10 * q64;
+        let reward_growths_outside_upper = [0; EXTENSION_REWARD_NUM];
+
+        let reward_growths_inside = get_extension_reward_growths_inside(
+            -10,
+            reward_growths_outside_lower,
+            10,
+            reward_growths_outside_upper,
+            0,
+            &build_reward_growths(30 * q64),
+        );
+        assert_eq!(reward_growths_inside[0], 20 * q64);
+        assert_eq!(reward_growths_inside[1], 0);
+
+        // Nothing more is earned inside once the price is above the range
+        let mut reward_growths_outside_upper = reward_growths_outside_upper;
+        reward_growths_outside_upper[0] = 30 * q64;
+        let reward_growths_inside = get_extension_reward_growths_inside(
+            -10,
+            reward_growths_outside_lower,
+            10,
+            reward_growths_outside_upper,
+            20,
+            &build_reward_growths(50 * q64),
+        );
+        assert_eq!(reward_growths_inside[0], 20 * q64);
+    }
+
+    #[test]
+    fn position_update_rewards_test() {
+        let q64 = fixed_point_64::Q64;
+        let mut position = PositionRewardExtension {
+            tick_lower_index: -10,
+            tick_upper_index: 10,
+            ..Default::default()
+        };
+        let mut reward_growths_inside = [0; EXTENSION_REWARD_NUM];
+        reward_growths_inside[0] = 5 * q64;
+        position.update_rewards(reward_growths_inside, 100, -10, 10);
+        assert_eq!(position.reward_infos[0].reward_amount_owed, 500);
+
+        // The growths of another range are a new starting point
+        reward_growths_inside[0] = 50 * q64;
+        position.update_rewards(reward_growths_inside, 100, 0, 20);
+        assert_eq!(position.reward_infos[0].reward_amount_owed, 500);
+        assert_eq!(position.reward_infos[0].growth_inside_last_x64, 50 * q64);
+        assert_eq!(position.tick_lower_index, 0);
+    }
+}
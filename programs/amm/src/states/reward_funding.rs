@@ -0,0 +1,185 @@
+use crate::error::ErrorCode;
+use crate::libraries::{big_num::U128, full_math::MulDiv};
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const REWARD_FUNDING_SEED: &str = "reward_funding";
+/// The number of funders a reward can track the deposits of
+pub const REWARD_FUNDER_NUM: usize = 8;
+
+/// A deposit of a funder into a reward
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct RewardDeposit {
+    /// The account which funded the reward
+    pub funder: Pubkey,
+    /// The amount received by the reward vault
+    pub amount: u64,
+}
+
+impl RewardDeposit {
+    pub const LEN: usize = 32 + 8;
+}
+
+/// The deposits made by `fund_reward` into the running reward. A deposit extends the end time
+/// of the reward, so the deposits are the last to be emitted and what is left of them is
+/// refunded to their funders when the reward is cancelled.
+///
+/// PDA of `[REWARD_FUNDING_SEED, pool, reward_index]`
+#[account(zero_copy(unsafe))]
+#[repr(packed)]
+#[derive(Default, Debug)]
+pub struct RewardFundingState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool the reward is offered in
+    pub pool_id: Pubkey,
+    /// The index of the reward in the pool
+    pub reward_index: u8,
+    /// The open time of the reward the deposits were made into
+    pub reward_open_time: u64,
+    /// The number of funders
+    pub deposit_count: u8,
+    /// The deposits, one per funder
+    pub deposits: [RewardDeposit; REWARD_FUNDER_NUM],
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 8],
+}
+
+impl RewardFundingState {
+    pub const LEN: usize = 8 + 1 + 32 + 1 + 8 + 1 + RewardDeposit::LEN * REWARD_FUNDER_NUM + 8 * 8;
+
+    /// Returns the deposits into the reward opened at `reward_open_time`, the deposits into
+    /// an earlier run of the reward have been emitted
+    pub fn get_deposits(&self, reward_open_time: u64) -> &[RewardDeposit] {
+        if self.reward_open_time != reward_open_time {
+            return &[];
+        }
+        &self.deposits[..usize::from(self.deposit_count)]
+    }
+
+    /// Records a deposit into the reward opened at `reward_open_time`
+    pub fn deposit(&mut self, reward_open_time: u64, funder: Pubkey, amount: u64) -> Result<()> {
+        if self.reward_open_time != reward_open_time {
+            self.clear();
+            self.reward_open_time = reward_open_time;
+        }
+        let deposit_count = usize::from(self.deposit_count);
+        if let Some(deposit) = self.deposits[..deposit_count]
+            .iter_mut()
+            .find(|deposit| deposit.funder == funder)
+        {
+            deposit.amount = deposit.amount.checked_add(amount).unwrap();
+            return Ok(());
+        }
+        require!(
+            deposit_count < REWARD_FUNDER_NUM,
+            ErrorCode::RewardFundersFull
+        );
+        self.deposits[deposit_count] = RewardDeposit { funder, amount };
+        self.deposit_count += 1;
+        Ok(())
+    }
+
+    /// Returns the refunds of the funders of the reward opened at `reward_open_time` when it
+    /// is cancelled with `unemitted_amount` of it left, pro rata to their deposits
+    pub fn get_refund_amounts(
+        &self,
+        reward_open_time: u64,
+        unemitted_amount: u64,
+    ) -> [u64; REWARD_FUNDER_NUM] {
+        let mut refund_amounts = [0; REWARD_FUNDER_NUM];
+        let deposits = self.get_deposits(reward_open_time);
+        let total_amount = deposits
+            .iter()
+            .map(|deposit| u128::from(deposit.amount))
+            .sum::<u128>();
+        if total_amount == 0 {
+            return refund_amounts;
+        }
+        let refund_total_amount = u128::from(unemitted_amount).min(total_amount);
+        for (i, deposit) in deposits.iter().enumerate() {
+            refund_amounts[i] = U128::from(deposit.amount)
+                .mul_div_floor(U128::from(refund_total_amount), U128::from(total_amount))
+                .unwrap()
+                .as_u64();
+        }
+        refund_amounts
+    }
+
+    pub fn clear(&mut self) {
+        self.deposit_count = 0;
+        self.deposits = [RewardDeposit::default(); REWARD_FUNDER_NUM];
+    }
+}
+
+#[cfg(test)]
+mod reward_funding_test {
+    use super::*;
+
+    #[test]
+    fn deposit_test() {
+        let funding = &mut RewardFundingState::default();
+        let funder_0 = Pubkey::new_unique();
+        let funder_1 = Pubkey::new_unique();
+        funding.deposit(100, funder_0, 10).unwrap();
+        funding.deposit(100, funder_1, 20).unwrap();
+        funding.deposit(100, funder_0, 5).unwrap();
+        assert_eq!(
+            funding.get_deposits(100),
+            [
+                RewardDeposit {
+                    funder: funder_0,
+                    amount: 15
+                },
+                RewardDeposit {
+                    funder: funder_1,
+                    amount: 20
+                },
+            ]
+        );
+        assert!(funding.get_deposits(200).is_empty());
+
+        // the reward was restarted, the earlier deposits are emitted
+        funding.deposit(200, funder_1, 7).unwrap();
+        assert_eq!(
+            funding.get_deposits(200),
+            [RewardDeposit {
+                funder: funder_1,
+                amount: 7
+            }]
+        );
+    }
+
+    #[test]
+    fn deposit_full_test() {
+        let funding = &mut RewardFundingState::default();
+        for _ in 0..REWARD_FUNDER_NUM {
+            funding.deposit(100, Pubkey::new_unique(), 1).unwrap();
+        }
+        assert!(funding.deposit(100, Pubkey::new_unique(), 1).is_err());
+    }
+
+    #[test]
+    fn get_refund_amounts_test() {
+        let funding = &mut RewardFundingState::default();
+        funding.deposit(100, Pubkey::new_unique(), 100).unwrap();
+        funding.deposit(100, Pubkey::new_unique(), 300).unwrap();
+
+        // nothing of the deposits was emitted
+        let refund_amounts = funding.get_refund_amounts(100, 1000);
+        assert_eq!(refund_amounts[..2], [100, 300]);
+
+        // half of the deposits was emitted
+        let refund_amounts = funding.get_refund_amounts(100, 200);
+        assert_eq!(refund_amounts[..2], [50, 150]);
+        assert_eq!(refund_amounts[2..], [0; REWARD_FUNDER_NUM - 2]);
+
+        // the deposits were made into an earlier run of the reward
+        assert_eq!(
+            funding.get_refund_amounts(200, 1000),
+            [0; REWARD_FUNDER_NUM]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::error::ErrorCode;
+use crate::states::pool::{PoolState, RewardEpoch};
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const REWARD_SCHEDULE_SEED: &str = "reward_schedule";
+/// The number of epochs a reward schedule can queue
+pub const REWARD_SCHEDULE_SIZE: usize = 8;
+
+/// The queued emission epochs of a reward, funded up front. The head epoch is moved to
+/// `PoolState::reward_pending_epochs` as soon as it is free, from where `update_reward_infos`
+/// rolls into it once the current epoch has ended.
+///
+/// PDA of `[REWARD_SCHEDULE_SEED, pool, reward_index]`
+#[account(zero_copy(unsafe))]
+#[repr(packed)]
+#[derive(Default, Debug)]
+pub struct RewardScheduleState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool the reward is offered in
+    pub pool_id: Pubkey,
+    /// The index of the reward in the pool
+    pub reward_index: u8,
+    /// The number of queued epochs
+    pub epoch_count: u8,
+    /// The queued epochs in time order
+    pub epochs: [RewardEpoch; REWARD_SCHEDULE_SIZE],
+    /// The account which funded each queued epoch
+    pub funders: [Pubkey; REWARD_SCHEDULE_SIZE],
+    /// The account which funded the pending epoch of the reward
+    pub pending_funder: Pubkey,
+    // Unused bytes for future upgrades.
+    pub padding: [u64; 8],
+}
+
+impl RewardScheduleState {
+    pub const LEN: usize =
+        8 + 1 + 32 + 1 + 1 + (RewardEpoch::LEN + 32) * REWARD_SCHEDULE_SIZE + 32 + 8 * 8;
+
+    /// Returns the end time of the last epoch of the reward, queued or not
+    pub fn get_last_end_time(&self, pool_state: &PoolState) -> u64 {
+        let reward_index = usize::from(self.reward_index);
+        if self.epoch_count > 0 {
+            return self.epochs[usize::from(self.epoch_count) - 1].end_time;
+        }
+        let pending_epoch = pool_state.reward_pending_epochs[reward_index];
+        if pending_epoch.initialized() {
+            return pending_epoch.end_time;
+        }
+        pool_state.reward_infos[reward_index].end_time
+    }
+
+    /// Queues an epoch funded by `funder` after the last one of the reward
+    pub fn push(&mut self, epoch: RewardEpoch, funder: Pubkey) -> Result<()> {
+        require!(
+            usize::from(self.epoch_count) < REWARD_SCHEDULE_SIZE,
+            ErrorCode::RewardScheduleFull
+        );
+        self.epochs[usize::from(self.epoch_count)] = epoch;
+        self.funders[usize::from(self.epoch_count)] = funder;
+        self.epoch_count += 1;
+        Ok(())
+    }
+
+    /// Moves the head epoch to the pending epoch of the reward if it is free
+    pub fn promote(&mut self, pool_state: &mut PoolState) {
+        let reward_index = usize::from(self.reward_index);
+        if self.epoch_count == 0 || pool_state.reward_pending_epochs[reward_index].initialized() {
+            return;
+        }
+        pool_state.reward_pending_epochs[reward_index] = self.epochs[0];
+        self.pending_funder = self.funders[0];
+        let epoch_count = usize::from(self.epoch_count);
+        self.epochs.copy_within(1..epoch_count, 0);
+        self.epochs[epoch_count - 1] = RewardEpoch::default();
+        self.funders.copy_within(1..epoch_count, 0);
+        self.funders[epoch_count - 1] = Pubkey::default();
+        self.epoch_count -= 1;
+    }
+
+    /// Returns the funder and the amount of the pending epoch and of every queued epoch of the
+    /// reward, in time order
+    pub fn get_epoch_fundings(&self, pool_state: &PoolState) -> Vec<(Pubkey, u64)> {
+        let mut epoch_fundings = Vec::new();
+        let pending_epoch = pool_state.reward_pending_epochs[usize::from(self.reward_index)];
+        if pending_epoch.initialized() {
+            epoch_fundings.push((self.pending_funder, pending_epoch.get_reward_amount()));
+        }
+        for i in 0..usize::from(self.epoch_count) {
+            epoch_fundings.push((self.funders[i], self.epochs[i].get_reward_amount()));
+        }
+        epoch_fundings
+    }
+
+    /// Rolls the reward into every queued epoch opened by now, in turn, then refills the pending
+    /// epoch from what is left of the schedule
+    pub fn advance(&mut self, pool_state: &mut PoolState, curr_timestamp: u64) -> Result<()> {
+        let reward_index = usize::from(self.reward_index);
+        loop {
+            pool_state.update_reward_infos(curr_timestamp)?;
+            if self.epoch_count == 0 || pool_state.reward_pending_epochs[reward_index].initialized()
+            {
+                return Ok(());
+            }
+            self.promote(pool_state);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.epoch_count = 0;
+        self.epochs = [RewardEpoch::default(); REWARD_SCHEDULE_SIZE];
+        self.funders = [Pubkey::default(); REWARD_SCHEDULE_SIZE];
+        self.pending_funder = Pubkey::default();
+    }
+}
+
+/// Emitted when an epoch is queued in the schedule of a reward
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+pub struct RewardEpochQueuedEvent {
+    /// The pool of the reward
+    #[index]
+    pub pool_state: Pubkey,
+
+    /// The index of the reward in the pool
+    pub reward_index: u8,
+
+    /// Epoch open time
+    pub open_time: u64,
+
+    /// Epoch end time
+    pub end_time: u64,
+
+    /// The emissions per second of the epoch, as a Q64.64
+    pub emissions_per_second_x64: u128,
+
+    /// The amount funded for the epoch
+    pub reward_amount: u64,
+}
+
+#[cfg(test)]
+mod reward_schedule_test {
+    use super::*;
+    use crate::states::pool::RewardInfo;
+
+    fn build_epoch(open_time: u64, end_time: u64) -> RewardEpoch {
+        RewardEpoch {
+            open_time,
+            end_time,
+            emissions_per_second_x64: 1 << 64,
+        }
+    }
+
+    #[test]
+    fn push_and_promote_test() {
+        let pool_state = &mut PoolState::default();
+        pool_state.reward_infos[1] = RewardInfo {
+            end_time: 100,
+            ..Default::default()
+        };
+        let schedule = &mut RewardScheduleState {
+            reward_index: 1,
+            ..Default::default()
+        };
+        let funder_0 = Pubkey::new_unique();
+        let funder_1 = Pubkey::new_unique();
+        assert_eq!(schedule.get_last_end_time(pool_state), 100);
+
+        schedule.push(build_epoch(100, 200), funder_0).unwrap();
+        schedule.push(build_epoch(200, 300), funder_1).unwrap();
+        assert_eq!(schedule.get_last_end_time(pool_state), 300);
+
+        schedule.promote(pool_state);
+        assert_eq!(pool_state.reward_pending_epochs[1], build_epoch(100, 200));
+        assert_eq!({ schedule.epoch_count }, 1);
+        assert_eq!(schedule.epochs[0], build_epoch(200, 300));
+        assert_eq!(
+            schedule.get_epoch_fundings(pool_state),
+            vec![(funder_0, 100), (funder_1, 100)]
+        );
+
+        // the pending epoch is not free yet
+        schedule.promote(pool_state);
+        assert_eq!({ schedule.epoch_count }, 1);
+        assert_eq!(schedule.get_last_end_time(pool_state), 300);
+    }
+
+    #[test]
+    fn advance_mid_epoch_test() {
+        let pool_state = &mut PoolState::default();
+        pool_state.liquidity = 1 << 64;
+        pool_state.reward_infos[1] = RewardInfo {
+            token_mint: Pubkey::new_unique(),
+            open_time: 100,
+            end_time: 200,
+            last_update_time: 100,
+            emissions_per_second_x64: 1 << 64,
+            ..Default::default()
+        };
+        let schedule = &mut RewardScheduleState {
+            reward_index: 1,
+            ..Default::default()
+        };
+        schedule
+            .push(build_epoch(300, 400), Pubkey::default())
+            .unwrap();
+        schedule
+            .push(build_epoch(400, 500), Pubkey::default())
+            .unwrap();
+        schedule
+            .push(build_epoch(600, 700), Pubkey::default())
+            .unwrap();
+
+        // The crank runs in the middle of the second queued epoch, both are emitted from their
+        // opening and the third one is pending
+        schedule.advance(pool_state, 450).unwrap();
+        let reward_info = pool_state.reward_infos[1];
+        assert_eq!({ reward_info.open_time }, 400);
+        assert_eq!({ reward_info.end_time }, 500);
+        assert_eq!({ reward_info.reward_total_emissioned }, 100 + 100 + 50);
+        assert_eq!(pool_state.reward_pending_epochs[1], build_epoch(600, 700));
+        assert_eq!({ schedule.epoch_count }, 0);
+
+        schedule.advance(pool_state, 800).unwrap();
+        assert_eq!(
+            { pool_state.reward_infos[1].reward_total_emissioned },
+            100 + 100 + 100 + 100
+        );
+    }
+
+    #[test]
+    fn push_full_test() {
+        let schedule = &mut RewardScheduleState::default();
+        for i in 0..REWARD_SCHEDULE_SIZE as u64 {
+            schedule
+                .push(build_epoch(i * 100, (i + 1) * 100), Pubkey::default())
+                .unwrap();
+        }
+        assert!(schedule
+            .push(build_epoch(800, 900), Pubkey::default())
+            .is_err());
+    }
+}
@@ -22,8 +22,12 @@ pub struct TickArrayState {
     pub initialized_tick_count: u8,
     // account update recent epoch
     pub recent_epoch: u64,
+    // Whether the tick array has a `TickArrayRewardExtension`, which swaps must then pass in
+    pub has_reward_extension: bool,
+    // Whether the tick array has a `TickArrayOracleExtension`, which swaps must then pass in
+    pub has_oracle_extension: bool,
     // Unused bytes for future upgrades.
-    pub padding: [u8; 107],
+    pub padding: [u8; 105],
 }
 
 impl TickArrayState {
@@ -128,6 +132,11 @@ impl TickArrayState {
         Ok(())
     }
 
+    pub fn get_tick_state(&self, tick_index: i32, tick_spacing: u16) -> Result<&TickState> {
+        let offset_in_array = self.get_tick_offset_in_array(tick_index, tick_spacing)?;
+        Ok(&self.ticks[offset_in_array])
+    }
+
     pub fn get_tick_state_mut(
         &mut self,
         tick_index: i32,
@@ -162,6 +171,18 @@ impl TickArrayState {
         Ok(offset_in_array)
     }
 
+    /// Returns the pending limit order liquidity of a tick, zero if the tick is out of the tick array
+    pub fn get_order_liquidity(&self, tick_index: i32, tick_spacing: u16) -> u128 {
+        if TickArrayState::get_array_start_index(tick_index, tick_spacing) != self.start_tick_index
+        {
+            return 0;
+        }
+        self.get_tick_offset_in_array(tick_index, tick_spacing)
+            .map_or(0, |offset_in_array| {
+                self.ticks[offset_in_array].order_liquidity
+            })
+    }
+
     /// Base on swap directioin, return the first initialized tick in the tick array.
     pub fn first_initialized_tick(&mut self, zero_for_one: bool) -> Result<&mut TickState> {
         if zero_for_one {
@@ -290,7 +311,9 @@ impl Default for TickArrayState {
             start_tick_index: 0,
             initialized_tick_count: 0,
             recent_epoch: 0,
-            padding: [0; 107],
+            has_reward_extension: false,
+            has_oracle_extension: false,
+            padding: [0; 105],
         }
     }
 }
@@ -313,12 +336,19 @@ pub struct TickState {
 
     // Reward growth per unit of liquidity like fee, array of Q64.64
     pub reward_growths_outside_x64: [u128; REWARD_NUM],
-    // Unused bytes for future upgrades.
-    pub padding: [u32; 13],
+    /// Liquidity of the pending limit orders that are filled when this tick is crossed
+    pub order_liquidity: u128,
+    /// Incremented every time the pending limit orders of this tick are filled
+    pub order_epoch: u32,
+    /// Seconds per unit of liquidity on the _other_ side of this tick, as a Q64.64,
+    /// only has relative meaning like the fee growth
+    pub seconds_per_liquidity_outside_x64: u128,
+    /// Amount of net boosted liquidity added (subtracted) when tick is crossed from left to right (right to left)
+    pub boosted_liquidity_net: i128,
 }
 
 impl TickState {
-    pub const LEN: usize = 4 + 16 + 16 + 16 + 16 + 16 * REWARD_NUM + 16 + 16 + 8 + 8 + 4;
+    pub const LEN: usize = 4 + 16 + 16 + 16 + 16 + 16 * REWARD_NUM + 16 + 4 + 16 + 16;
 
     pub fn initialize(&mut self, tick: i32, tick_spacing: u16) -> Result<()> {
         if TickState::check_is_out_of_boundary(tick) {
@@ -338,8 +368,40 @@ impl TickState {
         liquidity_delta: i128,
         fee_growth_global_0_x64: u128,
         fee_growth_global_1_x64: u128,
+        seconds_per_liquidity_global_x64: u128,
         upper: bool,
         reward_infos: &[RewardInfo; REWARD_NUM],
+    ) -> Result<bool> {
+        let flipped = self.update_liquidity_gross(
+            tick_current,
+            liquidity_delta,
+            fee_growth_global_0_x64,
+            fee_growth_global_1_x64,
+            seconds_per_liquidity_global_x64,
+            reward_infos,
+        )?;
+
+        // when the lower (upper) tick is crossed left to right (right to left),
+        // liquidity must be added (removed)
+        self.liquidity_net = if upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        }
+        .unwrap();
+        Ok(flipped)
+    }
+
+    /// Updates the gross liquidity of a tick only, as limit orders do since their liquidity is
+    /// not part of `liquidity_net`, and returns true if the tick was flipped
+    pub fn update_liquidity_gross(
+        &mut self,
+        tick_current: i32,
+        liquidity_delta: i128,
+        fee_growth_global_0_x64: u128,
+        fee_growth_global_1_x64: u128,
+        seconds_per_liquidity_global_x64: u128,
+        reward_infos: &[RewardInfo; REWARD_NUM],
     ) -> Result<bool> {
         let liquidity_gross_before = self.liquidity_gross;
         let liquidity_gross_after =
@@ -354,19 +416,11 @@ impl TickState {
                 self.fee_growth_outside_0_x64 = fee_growth_global_0_x64;
                 self.fee_growth_outside_1_x64 = fee_growth_global_1_x64;
                 self.reward_growths_outside_x64 = RewardInfo::get_reward_growths(reward_infos);
+                self.seconds_per_liquidity_outside_x64 = seconds_per_liquidity_global_x64;
             }
         }
 
         self.liquidity_gross = liquidity_gross_after;
-
-        // when the lower (upper) tick is crossed left to right (right to left),
-        // liquidity must be added (removed)
-        self.liquidity_net = if upper {
-            self.liquidity_net.checked_sub(liquidity_delta)
-        } else {
-            self.liquidity_net.checked_add(liquidity_delta)
-        }
-        .unwrap();
         Ok(flipped)
     }
 
@@ -379,6 +433,7 @@ impl TickState {
         &mut self,
         fee_growth_global_0_x64: u128, // The current global accumulated fees for token 0, represented in 64-bit fixed-point format.
         fee_growth_global_1_x64: u128, // The current global accumulated fees for token 1, represented in 64-bit fixed-point format.
+        seconds_per_liquidity_global_x64: u128, // The current seconds per liquidity accumulator of the pool.
         reward_infos: &[RewardInfo; REWARD_NUM], // Array of reward info data, where each element stores reward growth data for a specific reward.
     ) -> i128 {
         // Update `fee_growth_outside_0_x64` by calculating the difference between
@@ -395,6 +450,10 @@ impl TickState {
             .checked_sub(self.fee_growth_outside_1_x64)
             .unwrap();
 
+        // The accumulator may wrap around, only differences between its values are used
+        self.seconds_per_liquidity_outside_x64 =
+            seconds_per_liquidity_global_x64.wrapping_sub(self.seconds_per_liquidity_outside_x64);
+
         // Loop through each reward in `reward_infos`, updating the reward growth outside
         // this tick for each one.
         for i in 0..REWARD_NUM {
@@ -418,12 +477,51 @@ impl TickState {
         self.liquidity_net
     }
 
+    /// Updates the net boosted liquidity of a tick bounding a range whose boosted liquidity changed
+    pub fn update_boosted_liquidity_net(&mut self, boosted_liquidity_delta: i128, upper: bool) {
+        self.boosted_liquidity_net = if upper {
+            self.boosted_liquidity_net
+                .checked_sub(boosted_liquidity_delta)
+        } else {
+            self.boosted_liquidity_net
+                .checked_add(boosted_liquidity_delta)
+        }
+        .unwrap();
+    }
+
+    /// Adds (removes) pending limit order liquidity filled when this tick is crossed
+    pub fn update_order_liquidity(&mut self, liquidity_delta: i128) -> Result<()> {
+        self.order_liquidity = liquidity_math::add_delta(self.order_liquidity, liquidity_delta)?;
+        Ok(())
+    }
+
+    /// Fills all the pending limit orders of this tick, returning the filled liquidity.
+    /// The liquidity stays in `liquidity_gross` until the orders are claimed.
+    pub fn fill_limit_orders(&mut self) -> u128 {
+        let filled_liquidity = self.order_liquidity;
+        if filled_liquidity != 0 {
+            self.order_liquidity = 0;
+            self.order_epoch = self.order_epoch.checked_add(1).unwrap();
+        }
+        filled_liquidity
+    }
+
+    /// Releases the gross liquidity of claimed limit orders and returns true if the tick was flipped
+    /// from initialized to uninitialized
+    pub fn release_filled_liquidity(&mut self, filled_liquidity: u128) -> bool {
+        self.liquidity_gross = self.liquidity_gross.checked_sub(filled_liquidity).unwrap();
+        self.liquidity_gross == 0
+    }
+
     pub fn clear(&mut self) {
         self.liquidity_net = 0;
         self.liquidity_gross = 0;
         self.fee_growth_outside_0_x64 = 0;
         self.fee_growth_outside_1_x64 = 0;
         self.reward_growths_outside_x64 = [0; REWARD_NUM];
+        self.order_liquidity = 0;
+        self.seconds_per_liquidity_outside_x64 = 0;
+        self.boosted_liquidity_net = 0;
     }
 
     pub fn is_initialized(self) -> bool {
@@ -539,6 +637,30 @@ pub fn get_reward_growths_inside(
     reward_growths_inside
 }
 
+/// Calculates the seconds per liquidity inside of tick_lower and tick_upper, as a Q64.64.
+/// A position can tell how many seconds it was in range from the difference of two snapshots
+/// multiplied by its liquidity, as long as its liquidity did not change in between.
+pub fn get_seconds_per_liquidity_inside(
+    tick_lower: &TickState,
+    tick_upper: &TickState,
+    tick_current: i32,
+    seconds_per_liquidity_global_x64: u128,
+) -> u128 {
+    if tick_current < tick_lower.tick {
+        tick_lower
+            .seconds_per_liquidity_outside_x64
+            .wrapping_sub(tick_upper.seconds_per_liquidity_outside_x64)
+    } else if tick_current < tick_upper.tick {
+        seconds_per_liquidity_global_x64
+            .wrapping_sub(tick_lower.seconds_per_liquidity_outside_x64)
+            .wrapping_sub(tick_upper.seconds_per_liquidity_outside_x64)
+    } else {
+        tick_upper
+            .seconds_per_liquidity_outside_x64
+            .wrapping_sub(tick_lower.seconds_per_liquidity_outside_x64)
+    }
+}
+
 pub fn check_tick_array_start_index(
     tick_array_start_index: i32,
     tick_index: i32,
@@ -675,6 +797,22 @@ pub mod tick_array_test {
             );
         }
 
+        #[test]
+        fn get_order_liquidity_test() {
+            let tick_spacing = 10;
+            let tick_array_ref = build_tick_array(-600, tick_spacing, vec![0, 59]);
+            tick_array_ref.borrow_mut().ticks[0].order_liquidity = 100;
+            tick_array_ref.borrow_mut().ticks[59].order_liquidity = 200;
+
+            let tick_array = tick_array_ref.borrow();
+            assert_eq!(tick_array.get_order_liquidity(-600, tick_spacing), 100);
+            assert_eq!(tick_array.get_order_liquidity(-10, tick_spacing), 200);
+            assert_eq!(tick_array.get_order_liquidity(-300, tick_spacing), 0);
+            // out of the tick array
+            assert_eq!(tick_array.get_order_liquidity(0, tick_spacing), 0);
+            assert_eq!(tick_array.get_order_liquidity(-610, tick_spacing), 0);
+        }
+
         #[test]
         fn next_tick_arrary_start_index_test() {
             let tick_spacing = 15;
@@ -873,12 +1011,14 @@ pub mod tick_array_test {
                 tick_lower.cross(
                     fee_growth_global_0_x64,
                     fee_growth_global_1_x64,
+                    0,
                     &[RewardInfo::default(); 3],
                 );
             } else {
                 tick_upper.cross(
                     fee_growth_global_0_x64,
                     fee_growth_global_1_x64,
+                    0,
                     &[RewardInfo::default(); 3],
                 );
             }
@@ -1210,9 +1350,9 @@ pub mod tick_array_test {
 
             reward_growth_global_x64 = reward_growth_global_x64 + reward_growth_global_delta;
             if cross_tick_lower {
-                tick_lower.cross(0, 0, &build_reward_infos(reward_growth_global_x64));
+                tick_lower.cross(0, 0, 0, &build_reward_infos(reward_growth_global_x64));
             } else {
-                tick_upper.cross(0, 0, &build_reward_infos(reward_growth_global_x64));
+                tick_upper.cross(0, 0, 0, &build_reward_infos(reward_growth_global_x64));
             }
 
             tick_current = target_tick_current;
@@ -1358,4 +1498,56 @@ pub mod tick_array_test {
             assert_eq!(reward_frowth_inside_delta, 500);
         }
     }
+
+    mod get_seconds_per_liquidity_inside_test {
+        use crate::states::tick_array::{get_seconds_per_liquidity_inside, TickState};
+        use crate::states::RewardInfo;
+
+        #[test]
+        fn seconds_per_liquidity_inside_test() {
+            let mut tick_lower = TickState {
+                tick: -10,
+                ..Default::default()
+            };
+            let mut tick_upper = TickState {
+                tick: 10,
+                ..Default::default()
+            };
+            let reward_infos = &[RewardInfo::default(); 3];
+            // both ticks initialized with the price in range at 100, so all the time is below the lower tick
+            tick_lower
+                .update(0, 1000, 0, 0, 100, false, reward_infos)
+                .unwrap();
+            tick_upper
+                .update(0, 1000, 0, 0, 100, true, reward_infos)
+                .unwrap();
+            assert_eq!(
+                get_seconds_per_liquidity_inside(&tick_lower, &tick_upper, 0, 100),
+                0
+            );
+            assert_eq!(
+                get_seconds_per_liquidity_inside(&tick_lower, &tick_upper, 0, 150),
+                50
+            );
+
+            // the price moves out of the range at 150 and back at 180
+            tick_upper.cross(0, 0, 150, reward_infos);
+            assert_eq!(
+                get_seconds_per_liquidity_inside(&tick_lower, &tick_upper, 10, 170),
+                50
+            );
+            tick_upper.cross(0, 0, 180, reward_infos);
+            assert_eq!(
+                get_seconds_per_liquidity_inside(&tick_lower, &tick_upper, 0, 200),
+                70
+            );
+
+            // and below the range from 200
+            tick_lower.cross(0, 0, 200, reward_infos);
+            assert_eq!(
+                get_seconds_per_liquidity_inside(&tick_lower, &tick_upper, -11, 300),
+                70
+            );
+        }
+    }
 }
@@ -109,6 +109,53 @@ impl<'info, T: ZeroCopy + Owner> AccountLoad<'info, T> {
         }))
     }
 
+    /// Returns a `RefMut` to the account data structure of an account that may be read-only.
+    /// The runtime rejects any change to a read-only account, so the caller must only read from it.
+    pub fn load_data_mut_unchecked<'a>(acc_info: &'a AccountInfo) -> Result<RefMut<'a, T>> {
+        if acc_info.owner != &T::owner() {
+            return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                .with_pubkeys((*acc_info.owner, T::owner())));
+        }
+
+        let data = acc_info.try_borrow_mut_data()?;
+        if data.len() < T::discriminator().len() {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
+
+        let disc_bytes = array_ref![data, 0, 8];
+        if disc_bytes != &T::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+
+        Ok(RefMut::map(data, |data| {
+            bytemuck::from_bytes_mut(&mut data.deref_mut()[8..mem::size_of::<T>() + 8])
+        }))
+    }
+
+    /// Returns a `Ref` to the account data structure of an account for reading only.
+    /// There is no need to convert AccountInfo to AccountLoad.
+    /// So it is necessary to check the owner
+    pub fn load_data<'a>(acc_info: &'a AccountInfo) -> Result<Ref<'a, T>> {
+        if acc_info.owner != &T::owner() {
+            return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                .with_pubkeys((*acc_info.owner, T::owner())));
+        }
+
+        let data = acc_info.try_borrow_data()?;
+        if data.len() < T::discriminator().len() {
+            return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+        }
+
+        let disc_bytes = array_ref![data, 0, 8];
+        if disc_bytes != &T::discriminator() {
+            return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+        }
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::from_bytes(&data[8..mem::size_of::<T>() + 8])
+        }))
+    }
+
     /// Returns a Ref to the account data structure for reading.
     pub fn load(&self) -> Result<Ref<T>> {
         let data = self.acc_info.try_borrow_data()?;
@@ -9,3 +9,6 @@ pub use access_control::*;
 
 pub mod account_load;
 pub use account_load::*;
+
+pub mod swap_hook;
+pub use swap_hook::*;
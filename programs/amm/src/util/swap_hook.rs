@@ -0,0 +1,179 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+
+/// The instruction the hook program must implement to be called before a swap, as
+/// `before_swap(args: BeforeSwapArgs)` with the Anchor discriminator
+pub const BEFORE_SWAP_HOOK_NAME: &str = "global:before_swap";
+/// The instruction the hook program must implement to be called after a swap, as
+/// `after_swap(args: AfterSwapArgs)` with the Anchor discriminator
+pub const AFTER_SWAP_HOOK_NAME: &str = "global:after_swap";
+
+/// Arguments of the hook called before a swap, the hook rejects the swap by failing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BeforeSwapArgs {
+    /// The pool being swapped in
+    pub pool_state: Pubkey,
+    /// The user performing the swap
+    pub sender: Pubkey,
+    /// The swap direction
+    pub zero_for_one: bool,
+    /// swap base input or swap base output
+    pub is_base_input: bool,
+    /// The amount in for base input, or the amount out for base output
+    pub amount_specified: u64,
+    /// The Q64.64 sqrt price √P limit of the swap
+    pub sqrt_price_limit_x64: u128,
+}
+
+/// Arguments of the hook called after a swap, the hook rejects the swap by failing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AfterSwapArgs {
+    /// The pool being swapped in
+    pub pool_state: Pubkey,
+    /// The user performing the swap
+    pub sender: Pubkey,
+    /// The swap direction
+    pub zero_for_one: bool,
+    /// The amount of token_0 swapped in or out of the pool
+    pub amount_0: u64,
+    /// The amount of token_1 swapped in or out of the pool
+    pub amount_1: u64,
+    /// The sqrt(price) of the pool after the swap, as a Q64.64
+    pub sqrt_price_x64: u128,
+    /// The tick of the pool after the swap
+    pub tick: i32,
+}
+
+/// The hook program registered by a pool and the accounts passed along to it
+pub struct SwapHook<'info> {
+    pub program: AccountInfo<'info>,
+    pub accounts: Vec<AccountInfo<'info>>,
+}
+
+impl<'info> SwapHook<'info> {
+    pub fn before_swap(&self, args: BeforeSwapArgs) -> Result<()> {
+        self.invoke(BEFORE_SWAP_HOOK_NAME, args.try_to_vec()?)
+    }
+
+    pub fn after_swap(&self, args: AfterSwapArgs) -> Result<()> {
+        self.invoke(AFTER_SWAP_HOOK_NAME, args.try_to_vec()?)
+    }
+
+    fn invoke(&self, name: &str, args: Vec<u8>) -> Result<()> {
+        let mut data = hash(name.as_bytes()).to_bytes()[..8].to_vec();
+        data.extend(args);
+        // The hook is chosen by the pool, so it never gets the signer privilege of the user
+        let ix = Instruction {
+            program_id: self.program.key(),
+            accounts: self
+                .accounts
+                .iter()
+                .map(|account_info| AccountMeta {
+                    pubkey: account_info.key(),
+                    is_signer: false,
+                    is_writable: account_info.is_writable,
+                })
+                .collect(),
+            data,
+        };
+        let mut account_infos = self.accounts.clone();
+        account_infos.push(self.program.clone());
+        invoke(&ix, &account_infos)?;
+        Ok(())
+    }
+}
+
+/// Takes the hook program of the pool out of the remaining accounts, together with the accounts
+/// following it up to the first one owned by this program, which are passed to the hook.
+/// Returns the hook, if the pool has one, and the other remaining accounts.
+pub fn split_swap_hook<'c: 'info, 'info>(
+    hook_program: Pubkey,
+    remaining_accounts: &'c [AccountInfo<'info>],
+) -> Result<(Option<SwapHook<'info>>, Vec<&'c AccountInfo<'info>>)> {
+    if hook_program == Pubkey::default() {
+        return Ok((None, remaining_accounts.iter().collect()));
+    }
+    let hook_index = remaining_accounts
+        .iter()
+        .position(|account_info| account_info.key() == hook_program)
+        .ok_or(ErrorCode::MissingSwapHookProgram)?;
+    let hook_accounts_len = remaining_accounts[hook_index + 1..]
+        .iter()
+        .take_while(|account_info| *account_info.owner != crate::id())
+        .count();
+    let hook_accounts_end = hook_index + 1 + hook_accounts_len;
+
+    let hook = SwapHook {
+        program: remaining_accounts[hook_index].clone(),
+        accounts: remaining_accounts[hook_index + 1..hook_accounts_end].to_vec(),
+    };
+    let others = remaining_accounts[..hook_index]
+        .iter()
+        .chain(remaining_accounts[hook_accounts_end..].iter())
+        .collect();
+    Ok((Some(hook), others))
+}
+
+/// Checks an account can be registered as the swap hook of a pool
+pub fn check_swap_hook_program(hook_program: &AccountInfo) -> Result<()> {
+    require!(
+        hook_program.executable && hook_program.key() != crate::id(),
+        ErrorCode::InvalidSwapHookProgram
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod swap_hook_test {
+    use super::*;
+
+    #[test]
+    fn split_swap_hook_test() {
+        let keys: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let foreign_owner = Pubkey::new_unique();
+        let program_id = crate::id();
+        let owners = [
+            &program_id,
+            &foreign_owner,
+            &foreign_owner,
+            &foreign_owner,
+            &program_id,
+        ];
+        let mut lamports = [0u64; 5];
+        let mut data = [[0u8; 0]; 5];
+        let accounts: Vec<AccountInfo> = lamports
+            .iter_mut()
+            .zip(data.iter_mut())
+            .enumerate()
+            .map(|(i, (lamports, data))| {
+                AccountInfo::new(&keys[i], false, true, lamports, data, owners[i], false, 0)
+            })
+            .collect();
+
+        // no hook, every account is kept
+        let (hook, others) = split_swap_hook(Pubkey::default(), &accounts).unwrap();
+        assert!(hook.is_none());
+        assert_eq!(others.len(), 5);
+
+        // the hook takes the accounts following it up to the first one owned by this program
+        let (hook, others) = split_swap_hook(keys[1], &accounts).unwrap();
+        let hook = hook.unwrap();
+        assert_eq!(hook.program.key(), keys[1]);
+        assert_eq!(
+            hook.accounts.iter().map(|a| a.key()).collect::<Vec<_>>(),
+            vec![keys[2], keys[3]]
+        );
+        assert_eq!(
+            others.iter().map(|a| a.key()).collect::<Vec<_>>(),
+            vec![keys[0], keys[4]]
+        );
+
+        // the pool has a hook which is not given
+        assert!(split_swap_hook(Pubkey::new_unique(), &accounts).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::ErrorCode;
 use anchor_lang::{prelude::*, system_program};
 
 pub fn create_or_allocate_account<'a>(
@@ -68,6 +69,16 @@ pub fn create_or_allocate_account<'a>(
     Ok(())
 }
 
+/// Rejects the transaction once the cluster clock has passed the `deadline` unix timestamp
+pub fn check_deadline(deadline: i64) -> Result<()> {
+    require_gte!(
+        deadline,
+        Clock::get()?.unix_timestamp,
+        ErrorCode::TransactionTooOld
+    );
+    Ok(())
+}
+
 #[cfg(not(test))]
 pub fn get_recent_epoch() -> Result<u64> {
     Ok(Clock::get()?.epoch)